@@ -0,0 +1,247 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::time::SystemTime;
+
+use rw3d_net::connection::WitcherPort;
+use rw3d_net::messages::{MessageDirection, MessageIdRegistry, MessageMetadata};
+use rw3d_net::protocol::{DecodeLimits, WitcherPacket};
+
+use super::error::CaptureResult;
+use super::file::{read_frames, CaptureFrame};
+use super::link::TcpSegment;
+use super::reassembly::{StreamEvent, StreamIssue, TcpStreamReassembler};
+
+
+/// Packet of the game's protocol found in a capture
+#[derive(Debug, Clone)]
+pub struct CapturedPacket {
+    /// Time at which the last byte of the packet was captured
+    pub timestamp: SystemTime,
+    pub direction: MessageDirection,
+    pub client: SocketAddr,
+    pub server: SocketAddr,
+    pub packet: WitcherPacket,
+    /// Bytes of the packet exactly as they were captured
+    pub frame: Vec<u8>,
+    /// Message the packet was identified as, `None` if it's not known to the library
+    pub message: Option<MessageMetadata>
+}
+
+impl CapturedPacket {
+    /// Type name of the message or "Unknown"
+    #[inline]
+    pub fn message_name(&self) -> &str {
+        self.message.as_ref().map(|meta| meta.type_name.as_ref()).unwrap_or("Unknown")
+    }
+}
+
+/// Part of a TCP stream of the game's protocol that couldn't be decoded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedIssue {
+    pub timestamp: SystemTime,
+    pub direction: MessageDirection,
+    pub client: SocketAddr,
+    pub server: SocketAddr,
+    pub issue: StreamIssue
+}
+
+/// Number and total size of packets of a single message going in given direction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageStats {
+    pub direction: MessageDirection,
+    /// Type name of the message, `None` for packets that weren't identified
+    pub message: Option<String>,
+    pub count: usize,
+    pub bytes: usize
+}
+
+
+/// Everything that went through the game's ports, in the order it was captured
+#[derive(Debug, Clone, Default)]
+pub struct Conversation {
+    pub packets: Vec<CapturedPacket>,
+    pub issues: Vec<CapturedIssue>,
+    /// Number of frames which weren't TCP segments going to or from the game's ports
+    pub ignored_frames: usize
+}
+
+impl Conversation {
+    /// Time of the first captured packet
+    pub fn start_time(&self) -> Option<SystemTime> {
+        self.packets.iter().map(|p| p.timestamp).min()
+    }
+
+    /// Statistics of every message that appears in the conversation, in order of first appearance
+    pub fn stats(&self) -> Vec<MessageStats> {
+        let mut stats: Vec<MessageStats> = Vec::new();
+        for p in &self.packets {
+            let message = p.message.as_ref().map(|meta| meta.type_name.as_ref());
+            let size = p.frame.len();
+            match stats.iter_mut().find(|s| s.direction == p.direction && s.message.as_deref() == message) {
+                Some(s) => {
+                    s.count += 1;
+                    s.bytes += size;
+                },
+                None => stats.push(MessageStats {
+                    direction: p.direction,
+                    message: message.map(String::from),
+                    count: 1,
+                    bytes: size
+                })
+            }
+        }
+
+        stats
+    }
+}
+
+
+/// Reads packet captures made by tools like Wireshark or tcpdump and extracts the game's protocol out of them.
+///
+/// TCP streams going to or from the game's ports are reassembled, split into packets
+/// and those get identified using a [`MessageIdRegistry`] with all messages known to the library.
+#[derive(Debug)]
+pub struct CaptureDecoder {
+    server_ports: Vec<u16>,
+    limits: DecodeLimits,
+    registry: MessageIdRegistry
+}
+
+impl Default for CaptureDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CaptureDecoder {
+    /// Creates a decoder looking at both the standalone game's and the editor's port
+    pub fn new() -> Self {
+        let mut registry = MessageIdRegistry::new();
+        registry.register_client_messages().expect("Client messages should have unambiguous ids");
+        registry.register_server_messages().expect("Server messages should have unambiguous ids");
+
+        Self {
+            server_ports: vec![WitcherPort::Editor.as_number(), WitcherPort::Game.as_number()],
+            limits: DecodeLimits::default(),
+            registry
+        }
+    }
+
+    /// Ports on which the game listens, replaces the default ones
+    pub fn with_server_ports<I: IntoIterator<Item = u16>>(mut self, ports: I) -> Self {
+        self.server_ports = ports.into_iter().collect();
+        self
+    }
+
+    pub fn with_limits(mut self, limits: DecodeLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Registry used to identify messages, can be used to add ones not known to the library
+    #[inline]
+    pub fn registry_mut(&mut self) -> &mut MessageIdRegistry {
+        &mut self.registry
+    }
+
+
+    /// Reads and decodes a pcap or pcapng file
+    pub fn decode_file<P: AsRef<Path>>(&self, path: P) -> CaptureResult<Conversation> {
+        let bytes = std::fs::read(path)?;
+        self.decode(&bytes)
+    }
+
+    /// Decodes the contents of a pcap or pcapng file
+    pub fn decode(&self, bytes: &[u8]) -> CaptureResult<Conversation> {
+        let frames = read_frames(bytes)?;
+        Ok(self.decode_frames(&frames))
+    }
+
+    pub fn decode_frames(&self, frames: &[CaptureFrame]) -> Conversation {
+        let mut conv = Conversation::default();
+        let mut streams: HashMap<(SocketAddr, SocketAddr), Stream> = HashMap::new();
+
+        for frame in frames {
+            let Some(segment) = TcpSegment::parse(frame.link_type, &frame.data) else {
+                conv.ignored_frames += 1;
+                continue;
+            };
+
+            let (direction, client, server) = if self.server_ports.contains(&segment.dst.port()) {
+                (MessageDirection::ClientToServer, segment.src, segment.dst)
+            } else if self.server_ports.contains(&segment.src.port()) {
+                (MessageDirection::ServerToClient, segment.dst, segment.src)
+            } else {
+                conv.ignored_frames += 1;
+                continue;
+            };
+
+            let stream = streams.entry((segment.src, segment.dst)).or_insert_with(|| Stream {
+                direction,
+                client,
+                server,
+                last_timestamp: frame.timestamp,
+                reassembler: TcpStreamReassembler::new(self.limits)
+            });
+            stream.last_timestamp = frame.timestamp;
+
+            let mut events = Vec::new();
+            let mut seq = segment.seq;
+            if segment.syn {
+                events.extend(stream.reassembler.syn(seq));
+                // SYN takes up one sequence number
+                seq = seq.wrapping_add(1);
+            }
+            events.extend(stream.reassembler.segment(seq, segment.payload));
+            if segment.fin || segment.rst {
+                events.extend(stream.reassembler.finish());
+            }
+
+            self.push_events(&mut conv, stream, frame.timestamp, events);
+        }
+
+        // data still waiting for missing segments or unfinished packets
+        let mut remaining: Vec<_> = streams.into_values().collect();
+        remaining.sort_by_key(|s| s.last_timestamp);
+        for mut stream in remaining {
+            let events = stream.reassembler.finish();
+            self.push_events(&mut conv, &stream, stream.last_timestamp, events);
+        }
+
+        conv
+    }
+
+    fn push_events(&self, conv: &mut Conversation, stream: &Stream, timestamp: SystemTime, events: Vec<StreamEvent>) {
+        for event in events {
+            match event {
+                StreamEvent::Packet(packet, frame) => conv.packets.push(CapturedPacket {
+                    timestamp,
+                    direction: stream.direction,
+                    client: stream.client,
+                    server: stream.server,
+                    message: self.registry.probe_message_in(&packet, stream.direction).cloned(),
+                    packet,
+                    frame
+                }),
+                StreamEvent::Issue(issue) => conv.issues.push(CapturedIssue {
+                    timestamp,
+                    direction: stream.direction,
+                    client: stream.client,
+                    server: stream.server,
+                    issue
+                })
+            }
+        }
+    }
+}
+
+/// Data going in one direction of a TCP connection
+#[derive(Debug)]
+struct Stream {
+    direction: MessageDirection,
+    client: SocketAddr,
+    server: SocketAddr,
+    last_timestamp: SystemTime,
+    reassembler: TcpStreamReassembler
+}
@@ -0,0 +1,23 @@
+use thiserror::Error;
+
+
+/// Errors that can occur when reading a packet capture file
+#[derive(Debug, Error)]
+pub enum CaptureError {
+    #[error("Failed to read the capture file")]
+    Io(#[from] std::io::Error),
+    #[error("Unrecognised capture file format, magic number {0:02X?}")]
+    UnknownFormat([u8; 4]),
+    #[error("Capture file ends in the middle of {what} at offset {offset}")]
+    Truncated {
+        what: &'static str,
+        offset: usize
+    },
+    #[error("Invalid {what} at offset {offset}")]
+    InvalidBlock {
+        what: &'static str,
+        offset: usize
+    },
+}
+
+pub type CaptureResult<T> = Result<T, CaptureError>;
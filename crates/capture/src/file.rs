@@ -0,0 +1,319 @@
+use std::time::{Duration, SystemTime};
+
+use super::error::{CaptureError, CaptureResult};
+
+
+/// Link-layer header type as defined by tcpdump, says how to interpret captured frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LinkType(pub u16);
+
+impl LinkType {
+    /// BSD loopback, family of the protocol is written in host byte order
+    pub const NULL: LinkType = LinkType(0);
+    pub const ETHERNET: LinkType = LinkType(1);
+    pub const RAW: LinkType = LinkType(101);
+    /// OpenBSD loopback, family of the protocol is written in network byte order
+    pub const LOOP: LinkType = LinkType(108);
+    pub const LINUX_SLL: LinkType = LinkType(113);
+    pub const IPV4: LinkType = LinkType(228);
+    pub const IPV6: LinkType = LinkType(229);
+    pub const LINUX_SLL2: LinkType = LinkType(276);
+}
+
+
+/// Single frame read from a capture file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptureFrame {
+    pub timestamp: SystemTime,
+    pub link_type: LinkType,
+    /// Captured bytes of the frame, may be shorter than the original frame if the capture was limited in size
+    pub data: Vec<u8>
+}
+
+
+/// Reads all frames from the contents of a pcap or pcapng file.
+/// The format is detected based on the beginning of the file.
+pub fn read_frames(bytes: &[u8]) -> CaptureResult<Vec<CaptureFrame>> {
+    if bytes.len() < 4 {
+        return Err(CaptureError::Truncated { what: "file header", offset: 0 });
+    }
+
+    let magic = [bytes[0], bytes[1], bytes[2], bytes[3]];
+    if magic == PCAPNG_SECTION_HEADER.to_le_bytes() {
+        read_pcapng_frames(bytes)
+    } else if PcapFormat::from_magic(magic).is_some() {
+        read_pcap_frames(bytes)
+    } else {
+        Err(CaptureError::UnknownFormat(magic))
+    }
+}
+
+
+
+/// Reads numbers in byte order of the file
+#[derive(Debug, Clone, Copy)]
+struct Reader<'a> {
+    bytes: &'a [u8],
+    big_endian: bool
+}
+
+impl<'a> Reader<'a> {
+    fn slice(&self, offset: usize, len: usize, what: &'static str) -> CaptureResult<&'a [u8]> {
+        offset.checked_add(len)
+            .and_then(|end| self.bytes.get(offset..end))
+            .ok_or(CaptureError::Truncated { what, offset })
+    }
+
+    fn u16(&self, offset: usize, what: &'static str) -> CaptureResult<u16> {
+        let b = self.slice(offset, 2, what)?;
+        let b = [b[0], b[1]];
+        Ok(if self.big_endian { u16::from_be_bytes(b) } else { u16::from_le_bytes(b) })
+    }
+
+    fn u32(&self, offset: usize, what: &'static str) -> CaptureResult<u32> {
+        let b = self.slice(offset, 4, what)?;
+        let b = [b[0], b[1], b[2], b[3]];
+        Ok(if self.big_endian { u32::from_be_bytes(b) } else { u32::from_le_bytes(b) })
+    }
+}
+
+
+
+#[derive(Debug, Clone, Copy)]
+struct PcapFormat {
+    big_endian: bool,
+    nanosecond: bool
+}
+
+impl PcapFormat {
+    const MAGIC_MICROS: u32 = 0xA1B2C3D4;
+    const MAGIC_NANOS: u32 = 0xA1B23C4D;
+
+    fn from_magic(magic: [u8; 4]) -> Option<Self> {
+        for big_endian in [false, true] {
+            let value = if big_endian { u32::from_be_bytes(magic) } else { u32::from_le_bytes(magic) };
+            match value {
+                Self::MAGIC_MICROS => return Some(Self { big_endian, nanosecond: false }),
+                Self::MAGIC_NANOS => return Some(Self { big_endian, nanosecond: true }),
+                _ => {}
+            }
+        }
+
+        None
+    }
+}
+
+fn read_pcap_frames(bytes: &[u8]) -> CaptureResult<Vec<CaptureFrame>> {
+    const FILE_HEADER_SIZE: usize = 24;
+    const RECORD_HEADER_SIZE: usize = 16;
+
+    let format = PcapFormat::from_magic([bytes[0], bytes[1], bytes[2], bytes[3]])
+        .ok_or(CaptureError::UnknownFormat([bytes[0], bytes[1], bytes[2], bytes[3]]))?;
+    let reader = Reader { bytes, big_endian: format.big_endian };
+
+    // upper bits can hold information about frame check sequence
+    let link_type = LinkType((reader.u32(20, "file header")? & 0xFFFF) as u16);
+
+    let mut frames = Vec::new();
+    let mut offset = FILE_HEADER_SIZE;
+    while offset < bytes.len() {
+        let secs = reader.u32(offset, "record header")?;
+        let frac = reader.u32(offset + 4, "record header")?;
+        let captured_len = reader.u32(offset + 8, "record header")? as usize;
+        let data = reader.slice(offset + RECORD_HEADER_SIZE, captured_len, "record")?;
+
+        let nanos = if format.nanosecond { frac } else { frac.saturating_mul(1000) };
+        frames.push(CaptureFrame {
+            timestamp: SystemTime::UNIX_EPOCH + Duration::from_secs(secs as u64) + Duration::from_nanos(nanos as u64),
+            link_type,
+            data: data.to_vec()
+        });
+
+        offset += RECORD_HEADER_SIZE + captured_len;
+    }
+
+    Ok(frames)
+}
+
+
+
+const PCAPNG_SECTION_HEADER: u32 = 0x0A0D0D0A;
+const PCAPNG_INTERFACE_DESCRIPTION: u32 = 0x00000001;
+const PCAPNG_OBSOLETE_PACKET: u32 = 0x00000002;
+const PCAPNG_SIMPLE_PACKET: u32 = 0x00000003;
+const PCAPNG_ENHANCED_PACKET: u32 = 0x00000006;
+const PCAPNG_BYTE_ORDER_MAGIC: u32 = 0x1A2B3C4D;
+const PCAPNG_OPTION_END: u16 = 0;
+const PCAPNG_OPTION_TSRESOL: u16 = 9;
+
+#[derive(Debug, Clone, Copy)]
+struct PcapngInterface {
+    link_type: LinkType,
+    /// Value of `if_tsresol` option
+    ts_resolution: u8
+}
+
+impl PcapngInterface {
+    /// `None` if the time doesn't fit in a `SystemTime`
+    fn timestamp(&self, ts: u64) -> Option<SystemTime> {
+        let (secs, nanos) = if self.ts_resolution & 0x80 == 0 {
+            let units_per_sec = 10u128.pow(self.ts_resolution.min(38) as u32);
+            let ts = ts as u128;
+            (ts / units_per_sec, (ts % units_per_sec) * 1_000_000_000 / units_per_sec)
+        } else {
+            let shift = (self.ts_resolution & 0x7F).min(127) as u32;
+            let ts = ts as u128;
+            (ts >> shift, ((ts & ((1u128 << shift) - 1)) * 1_000_000_000) >> shift)
+        };
+
+        let since_epoch = Duration::from_secs(u64::try_from(secs).ok()?) + Duration::from_nanos(nanos as u64);
+        SystemTime::UNIX_EPOCH.checked_add(since_epoch)
+    }
+}
+
+fn read_pcapng_frames(bytes: &[u8]) -> CaptureResult<Vec<CaptureFrame>> {
+    const BLOCK_OVERHEAD: usize = 12;
+
+    let mut reader = Reader { bytes, big_endian: false };
+    let mut interfaces: Vec<PcapngInterface> = Vec::new();
+    let mut last_timestamp = SystemTime::UNIX_EPOCH;
+    let mut frames = Vec::new();
+
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let block_type = reader.u32(offset, "block header")?;
+
+        if block_type == PCAPNG_SECTION_HEADER {
+            // every section can have different byte order
+            let magic = reader.slice(offset + 8, 4, "section header")?;
+            let magic = [magic[0], magic[1], magic[2], magic[3]];
+            reader.big_endian = if u32::from_le_bytes(magic) == PCAPNG_BYTE_ORDER_MAGIC {
+                false
+            } else if u32::from_be_bytes(magic) == PCAPNG_BYTE_ORDER_MAGIC {
+                true
+            } else {
+                return Err(CaptureError::InvalidBlock { what: "section header byte order", offset });
+            };
+
+            interfaces.clear();
+        }
+
+        let block_len = reader.u32(offset + 4, "block header")? as usize;
+        if block_len < BLOCK_OVERHEAD || !block_len.is_multiple_of(4) {
+            return Err(CaptureError::InvalidBlock { what: "block length", offset });
+        }
+        let body = Reader {
+            bytes: reader.slice(offset + 8, block_len - BLOCK_OVERHEAD, "block")?,
+            big_endian: reader.big_endian
+        };
+
+        match block_type {
+            PCAPNG_INTERFACE_DESCRIPTION => {
+                let link_type = LinkType(body.u16(0, "interface description")?);
+                interfaces.push(PcapngInterface {
+                    link_type,
+                    ts_resolution: read_tsresol_option(body, 8)?
+                });
+            },
+            PCAPNG_ENHANCED_PACKET | PCAPNG_OBSOLETE_PACKET => {
+                let interface_id = if block_type == PCAPNG_ENHANCED_PACKET {
+                    body.u32(0, "packet block")? as usize
+                } else {
+                    body.u16(0, "packet block")? as usize
+                };
+                let interface = interfaces.get(interface_id)
+                    .ok_or(CaptureError::InvalidBlock { what: "packet interface id", offset })?;
+
+                let ts = ((body.u32(4, "packet block")? as u64) << 32) | body.u32(8, "packet block")? as u64;
+                let captured_len = body.u32(12, "packet block")? as usize;
+                let data = body.slice(20, captured_len, "packet block")?;
+
+                last_timestamp = interface.timestamp(ts)
+                    .ok_or(CaptureError::InvalidBlock { what: "packet timestamp", offset })?;
+                frames.push(CaptureFrame {
+                    timestamp: last_timestamp,
+                    link_type: interface.link_type,
+                    data: data.to_vec()
+                });
+            },
+            PCAPNG_SIMPLE_PACKET => {
+                let interface = interfaces.first()
+                    .ok_or(CaptureError::InvalidBlock { what: "simple packet block without an interface", offset })?;
+
+                let original_len = body.u32(0, "simple packet block")? as usize;
+                let data = &body.bytes[4..];
+                let data = &data[..original_len.min(data.len())];
+
+                // these blocks have no timestamp, so take the one of the last packet
+                frames.push(CaptureFrame {
+                    timestamp: last_timestamp,
+                    link_type: interface.link_type,
+                    data: data.to_vec()
+                });
+            },
+            _ => {
+                // name resolution, statistics, custom blocks and others are of no use here
+            }
+        }
+
+        offset += block_len;
+    }
+
+    Ok(frames)
+}
+
+fn read_tsresol_option(body: Reader, mut offset: usize) -> CaptureResult<u8> {
+    const DEFAULT_RESOLUTION: u8 = 6;
+
+    while offset + 4 <= body.bytes.len() {
+        let code = body.u16(offset, "interface option")?;
+        let len = body.u16(offset + 2, "interface option")? as usize;
+        match code {
+            PCAPNG_OPTION_END => break,
+            PCAPNG_OPTION_TSRESOL if len == 1 => return Ok(body.slice(offset + 4, 1, "interface option")?[0]),
+            _ => {}
+        }
+
+        // options are padded to 4 bytes
+        offset += 4 + len.div_ceil(4) * 4;
+    }
+
+    Ok(DEFAULT_RESOLUTION)
+}
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn pcapng_timestamp_resolution_test() {
+        let micros = PcapngInterface { link_type: LinkType::ETHERNET, ts_resolution: 6 };
+        assert_eq!(micros.timestamp(1_500_000), Some(SystemTime::UNIX_EPOCH + Duration::from_millis(1500)));
+
+        let nanos = PcapngInterface { link_type: LinkType::ETHERNET, ts_resolution: 9 };
+        assert_eq!(nanos.timestamp(2_000_000_001), Some(SystemTime::UNIX_EPOCH + Duration::new(2, 1)));
+
+        let binary = PcapngInterface { link_type: LinkType::ETHERNET, ts_resolution: 0x80 | 10 };
+        assert_eq!(binary.timestamp(3 * 1024 + 512), Some(SystemTime::UNIX_EPOCH + Duration::new(3, 500_000_000)));
+    }
+
+    #[test]
+    fn pcapng_timestamp_overflow_test() {
+        let secs = PcapngInterface { link_type: LinkType::ETHERNET, ts_resolution: 0 };
+        assert_eq!(secs.timestamp(u64::MAX), None);
+
+        let binary = PcapngInterface { link_type: LinkType::ETHERNET, ts_resolution: 0x80 };
+        assert_eq!(binary.timestamp(u64::MAX), None);
+    }
+
+    #[test]
+    fn unknown_format_test() {
+        assert!(matches!(read_frames(b"RIFF...."), Err(CaptureError::UnknownFormat(_))));
+        assert!(matches!(read_frames(&[0xD4, 0xC3, 0xB2, 0xA1, 0, 0]), Err(CaptureError::Truncated { .. })));
+    }
+}
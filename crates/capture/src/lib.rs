@@ -0,0 +1,16 @@
+//! Reading traffic between the game and debugging tools out of packet captures made by Wireshark, tcpdump and similar tools.
+
+mod error;
+pub use error::*;
+
+mod file;
+pub use file::*;
+
+mod link;
+pub use link::*;
+
+mod reassembly;
+pub use reassembly::StreamIssue;
+
+mod conversation;
+pub use conversation::*;
@@ -0,0 +1,150 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use super::file::LinkType;
+
+
+/// TCP segment extracted from a captured frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TcpSegment<'a> {
+    pub src: SocketAddr,
+    pub dst: SocketAddr,
+    pub seq: u32,
+    pub syn: bool,
+    pub fin: bool,
+    pub rst: bool,
+    pub payload: &'a [u8]
+}
+
+impl<'a> TcpSegment<'a> {
+    /// Goes through link, network and transport layer headers of the frame.
+    /// Returns `None` if it's not a TCP segment, is an IP fragment, uses an unsupported link type or is too short.
+    pub fn parse(link_type: LinkType, frame: &'a [u8]) -> Option<Self> {
+        let (ether_type, packet) = match link_type {
+            LinkType::ETHERNET => {
+                let mut ether_type = read_u16(frame, 12)?;
+                let mut header_len = 14;
+                // skip VLAN tags
+                while ether_type == ETHER_TYPE_VLAN || ether_type == ETHER_TYPE_QINQ {
+                    ether_type = read_u16(frame, header_len + 2)?;
+                    header_len += 4;
+                }
+                (ether_type, frame.get(header_len..)?)
+            },
+            LinkType::NULL | LinkType::LOOP => {
+                let family = frame.get(0..4)?;
+                let family = if link_type == LinkType::LOOP {
+                    u32::from_be_bytes([family[0], family[1], family[2], family[3]])
+                } else if family[0] != 0 {
+                    // host byte order of the machine that made the capture, which is little endian in practice
+                    u32::from_le_bytes([family[0], family[1], family[2], family[3]])
+                } else {
+                    u32::from_be_bytes([family[0], family[1], family[2], family[3]])
+                };
+                let ether_type = match family {
+                    2 => ETHER_TYPE_IPV4,
+                    // BSD systems don't agree on the value for IPv6
+                    24 | 28 | 30 => ETHER_TYPE_IPV6,
+                    _ => return None
+                };
+                (ether_type, frame.get(4..)?)
+            },
+            LinkType::RAW => match frame.first()? >> 4 {
+                4 => (ETHER_TYPE_IPV4, frame),
+                6 => (ETHER_TYPE_IPV6, frame),
+                _ => return None
+            },
+            LinkType::IPV4 => (ETHER_TYPE_IPV4, frame),
+            LinkType::IPV6 => (ETHER_TYPE_IPV6, frame),
+            LinkType::LINUX_SLL => (read_u16(frame, 14)?, frame.get(16..)?),
+            LinkType::LINUX_SLL2 => (read_u16(frame, 0)?, frame.get(20..)?),
+            _ => return None
+        };
+
+        let (src_ip, dst_ip, segment) = match ether_type {
+            ETHER_TYPE_IPV4 => parse_ipv4(packet)?,
+            ETHER_TYPE_IPV6 => parse_ipv6(packet)?,
+            _ => return None
+        };
+
+        let data_offset = (*segment.get(12)? >> 4) as usize * 4;
+        let flags = *segment.get(13)?;
+        Some(Self {
+            src: SocketAddr::new(src_ip, read_u16(segment, 0)?),
+            dst: SocketAddr::new(dst_ip, read_u16(segment, 2)?),
+            seq: read_u32(segment, 4)?,
+            fin: flags & 0x01 != 0,
+            syn: flags & 0x02 != 0,
+            rst: flags & 0x04 != 0,
+            payload: segment.get(data_offset..)?
+        })
+    }
+}
+
+
+const ETHER_TYPE_IPV4: u16 = 0x0800;
+const ETHER_TYPE_IPV6: u16 = 0x86DD;
+const ETHER_TYPE_VLAN: u16 = 0x8100;
+const ETHER_TYPE_QINQ: u16 = 0x88A8;
+const IP_PROTOCOL_TCP: u8 = 6;
+
+fn parse_ipv4(packet: &[u8]) -> Option<(IpAddr, IpAddr, &[u8])> {
+    if packet.first()? >> 4 != 4 {
+        return None;
+    }
+
+    let header_len = (packet[0] & 0x0F) as usize * 4;
+    let total_len = read_u16(packet, 2)? as usize;
+    let fragment = read_u16(packet, 6)?;
+    // more fragments flag or non-zero fragment offset
+    if fragment & 0x3FFF != 0 || *packet.get(9)? != IP_PROTOCOL_TCP {
+        return None;
+    }
+
+    let src = Ipv4Addr::from(read_u32(packet, 12)?);
+    let dst = Ipv4Addr::from(read_u32(packet, 16)?);
+    // frames can be padded at the end, but also cut short by the capture's size limit
+    let end = if total_len >= header_len { total_len.min(packet.len()) } else { packet.len() };
+    Some((src.into(), dst.into(), packet.get(header_len..end)?))
+}
+
+fn parse_ipv6(packet: &[u8]) -> Option<(IpAddr, IpAddr, &[u8])> {
+    const HEADER_LEN: usize = 40;
+
+    if packet.first()? >> 4 != 6 {
+        return None;
+    }
+
+    let payload_len = read_u16(packet, 4)? as usize;
+    let src = Ipv6Addr::from(<[u8; 16]>::try_from(packet.get(8..24)?).ok()?);
+    let dst = Ipv6Addr::from(<[u8; 16]>::try_from(packet.get(24..40)?).ok()?);
+
+    let end = (HEADER_LEN + payload_len).min(packet.len());
+    let mut next_header = *packet.get(6)?;
+    let mut offset = HEADER_LEN;
+    loop {
+        match next_header {
+            IP_PROTOCOL_TCP => break,
+            // hop-by-hop, routing and destination options
+            0 | 43 | 60 => {
+                next_header = *packet.get(offset)?;
+                offset += (*packet.get(offset + 1)? as usize + 1) * 8;
+            },
+            // fragments and anything else
+            _ => return None
+        }
+    }
+
+    Some((src.into(), dst.into(), packet.get(offset..end)?))
+}
+
+#[inline]
+fn read_u16(bytes: &[u8], offset: usize) -> Option<u16> {
+    let b = bytes.get(offset..offset + 2)?;
+    Some(u16::from_be_bytes([b[0], b[1]]))
+}
+
+#[inline]
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    let b = bytes.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
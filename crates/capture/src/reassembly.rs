@@ -0,0 +1,243 @@
+use rw3d_net::protocol::{DecodeLimits, SkippedData, WitcherPacket, WitcherPacketFramer};
+
+
+/// Something that made a part of the stream impossible to decode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamIssue {
+    /// Data that didn't form a correct packet and had to be skipped
+    Skipped(SkippedData),
+    /// Some segments never made it into the capture, so any packet that was being received at the time is lost
+    MissingData {
+        bytes: u32
+    },
+    /// The capture ended or the connection was reset before the whole packet could be received
+    UnfinishedPacket {
+        bytes: usize
+    }
+}
+
+impl std::fmt::Display for StreamIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StreamIssue::Skipped(skipped) => write!(f, "{}, skipped {} bytes", skipped.reason, skipped.bytes.len()),
+            StreamIssue::MissingData { bytes } => write!(f, "{} bytes missing from the capture", bytes),
+            StreamIssue::UnfinishedPacket { bytes } => write!(f, "Unfinished packet, {} bytes received", bytes),
+        }
+    }
+}
+
+/// Output of [`TcpStreamReassembler`]
+#[derive(Debug)]
+pub(crate) enum StreamEvent {
+    /// Decoded packet and the bytes of its frame
+    Packet(WitcherPacket, Vec<u8>),
+    Issue(StreamIssue)
+}
+
+
+/// Puts TCP segments going in one direction back in order and splits the stream into packets.
+#[derive(Debug)]
+pub(crate) struct TcpStreamReassembler {
+    /// Sequence number of the next byte that's expected, unknown until the first segment comes
+    next_seq: Option<u32>,
+    /// Segments that came before the ones preceding them
+    out_of_order: Vec<(u32, Vec<u8>)>,
+    framer: WitcherPacketFramer
+}
+
+impl TcpStreamReassembler {
+    /// Number of segments waiting for a missing one, after which it's considered lost
+    const MAX_OUT_OF_ORDER: usize = 64;
+
+
+    pub fn new(limits: DecodeLimits) -> Self {
+        Self {
+            next_seq: None,
+            out_of_order: Vec::new(),
+            framer: WitcherPacketFramer::with_limits(limits)
+        }
+    }
+
+    /// Start of a new connection, anything left from the previous one is dropped
+    pub fn syn(&mut self, seq: u32) -> Vec<StreamEvent> {
+        let events = self.finish();
+        self.next_seq = Some(seq.wrapping_add(1));
+        events
+    }
+
+    pub fn segment(&mut self, seq: u32, payload: &[u8]) -> Vec<StreamEvent> {
+        let mut events = Vec::new();
+        if payload.is_empty() {
+            return events;
+        }
+
+        // capture started in the middle of the connection, the framer will find the first packet head
+        let next_seq = *self.next_seq.get_or_insert(seq);
+        if seq_diff(seq, next_seq) > 0 {
+            self.out_of_order.push((seq, payload.to_vec()));
+            if self.out_of_order.len() > Self::MAX_OUT_OF_ORDER {
+                self.skip_gap(&mut events);
+            }
+        } else {
+            self.append(seq, payload);
+        }
+
+        self.drain_out_of_order();
+        self.take_packets(&mut events);
+        events
+    }
+
+    /// No more data is going to come, everything still waiting is processed
+    pub fn finish(&mut self) -> Vec<StreamEvent> {
+        let mut events = Vec::new();
+        while !self.out_of_order.is_empty() {
+            self.skip_gap(&mut events);
+            self.drain_out_of_order();
+            self.take_packets(&mut events);
+        }
+
+        let leftover = self.framer.buffered_len();
+        if leftover > 0 {
+            events.push(StreamEvent::Issue(StreamIssue::UnfinishedPacket { bytes: leftover }));
+        }
+
+        *self = Self::new(*self.framer.limits());
+        events
+    }
+
+
+    /// Appends the part of the segment that hasn't been received yet
+    fn append(&mut self, seq: u32, payload: &[u8]) {
+        let Some(next_seq) = self.next_seq else { return };
+
+        let already_received = next_seq.wrapping_sub(seq) as usize;
+        if already_received < payload.len() {
+            let new_data = &payload[already_received..];
+            self.framer.extend_from_slice(new_data);
+            self.next_seq = Some(next_seq.wrapping_add(new_data.len() as u32));
+        }
+    }
+
+    fn drain_out_of_order(&mut self) {
+        while let Some(next_seq) = self.next_seq {
+            match self.out_of_order.iter().position(|(seq, _)| seq_diff(*seq, next_seq) <= 0) {
+                Some(i) => {
+                    let (seq, payload) = self.out_of_order.swap_remove(i);
+                    self.append(seq, &payload);
+                },
+                None => break
+            }
+        }
+    }
+
+    /// Gives up on the missing segment and continues from the earliest one that came after it
+    fn skip_gap(&mut self, events: &mut Vec<StreamEvent>) {
+        let Some(next_seq) = self.next_seq else { return };
+        let Some(earliest) = self.out_of_order.iter().map(|(seq, _)| *seq).min_by_key(|seq| seq_diff(*seq, next_seq)) else { return };
+
+        events.push(StreamEvent::Issue(StreamIssue::MissingData { bytes: earliest.wrapping_sub(next_seq) }));
+        let leftover = self.framer.buffered_len();
+        if leftover > 0 {
+            events.push(StreamEvent::Issue(StreamIssue::UnfinishedPacket { bytes: leftover }));
+        }
+
+        self.framer = WitcherPacketFramer::with_limits(*self.framer.limits());
+        self.next_seq = Some(earliest);
+    }
+
+    fn take_packets(&mut self, events: &mut Vec<StreamEvent>) {
+        let mut skipped = Vec::new();
+        while let Some((packet, frame)) = self.framer.next_frame_recovering(|s| skipped.push(s)) {
+            events.extend(skipped.drain(..).map(|s| StreamEvent::Issue(StreamIssue::Skipped(s))));
+            events.push(StreamEvent::Packet(packet, frame));
+        }
+        events.extend(skipped.drain(..).map(|s| StreamEvent::Issue(StreamIssue::Skipped(s))));
+    }
+}
+
+/// Distance between sequence numbers taking wrapping into account
+#[inline]
+fn seq_diff(seq: u32, base: u32) -> i32 {
+    seq.wrapping_sub(base) as i32
+}
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use rw3d_net::protocol::*;
+
+    use super::*;
+
+
+    fn packet_bytes(text: &str) -> Vec<u8> {
+        let packet = WitcherPacketAssembler::new().string_utf8(text).finish();
+        let mut bytes = Vec::new();
+        packet.encode_into(&mut bytes).unwrap();
+        bytes
+    }
+
+    fn packets(events: Vec<StreamEvent>) -> Vec<WitcherPacket> {
+        events.into_iter()
+            .filter_map(|e| match e {
+                StreamEvent::Packet(p, _) => Some(p),
+                StreamEvent::Issue(_) => None
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reassembly_out_of_order_test() {
+        let mut bytes = packet_bytes("first");
+        bytes.extend(packet_bytes("second"));
+
+        let mut stream = TcpStreamReassembler::new(DecodeLimits::default());
+        assert!(stream.syn(u32::MAX - 5).is_empty());
+
+        // sequence numbers wrap around in the middle
+        let base = u32::MAX - 4;
+        let (a, rest) = bytes.split_at(7);
+        let (b, c) = rest.split_at(15);
+        assert!(stream.segment(base.wrapping_add(22), c).is_empty());
+        assert!(stream.segment(base.wrapping_add(7), b).is_empty());
+        // retransmission overlapping with data already received
+        let received = packets(stream.segment(base, &bytes[..10]));
+        assert_eq!(received.len(), 2);
+        assert_eq!(received[0].payload()[0], WitcherPacketData::new_string_utf8("first"));
+        assert_eq!(received[1].payload()[0], WitcherPacketData::new_string_utf8("second"));
+        assert!(stream.segment(base, a).is_empty());
+        assert!(stream.finish().is_empty());
+    }
+
+    #[test]
+    fn reassembly_stale_segment_test() {
+        let bytes = packet_bytes("first");
+
+        let mut stream = TcpStreamReassembler::new(DecodeLimits::default());
+        stream.syn(0);
+        assert_eq!(packets(stream.segment(1, &bytes)).len(), 1);
+        // half of the sequence space behind, still counts as data received long ago
+        let next_seq = 1 + bytes.len() as u32;
+        assert!(stream.segment(next_seq.wrapping_sub(1 << 31), &bytes).is_empty());
+        assert!(stream.finish().is_empty());
+    }
+
+    #[test]
+    fn reassembly_missing_data_test() {
+        let first = packet_bytes("first");
+        let second = packet_bytes("second");
+
+        let mut stream = TcpStreamReassembler::new(DecodeLimits::default());
+        stream.syn(0);
+        assert!(stream.segment(1, &first[..4]).is_empty());
+        // the rest of the first packet is lost
+        assert!(stream.segment(1 + first.len() as u32, &second).is_empty());
+
+        let events = stream.finish();
+        assert!(matches!(events[0], StreamEvent::Issue(StreamIssue::MissingData { bytes }) if bytes as usize == first.len() - 4));
+        assert!(matches!(events[1], StreamEvent::Issue(StreamIssue::UnfinishedPacket { bytes: 4 })));
+        assert!(matches!(&events[2], StreamEvent::Packet(p, _) if p.payload()[0] == WitcherPacketData::new_string_utf8("second")));
+    }
+}
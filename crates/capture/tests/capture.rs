@@ -0,0 +1,256 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::time::{Duration, SystemTime};
+
+use rw3d_capture::*;
+use rw3d_net::{messages::{requests::*, *}, protocol::*};
+
+
+struct Segment {
+    micros: u64,
+    to_server: bool,
+    seq: u32,
+    flags: u8,
+    payload: Vec<u8>
+}
+
+const SYN: u8 = 0x02;
+const PSH: u8 = 0x08;
+const FIN: u8 = 0x01;
+
+const CLIENT_PORT: u16 = 51234;
+const SERVER_PORT: u16 = 37001;
+
+
+fn encode(packet: WitcherPacket) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    packet.encode_into(&mut bytes).unwrap();
+    bytes
+}
+
+/// Client asks for the root path, the response is split into two segments which come out of order
+fn conversation_segments() -> Vec<Segment> {
+    let request = encode(ScriptsRootPath::assemble_packet(()));
+    let response = encode(ScriptsRootPathResponse::assemble_packet(ScriptsRootPathResult { abs_path: "C:/Witcher 3/content".into() }));
+    let (resp_a, resp_b) = response.split_at(10);
+
+    vec![
+        Segment { micros: 0, to_server: true, seq: 1000, flags: SYN, payload: vec![] },
+        Segment { micros: 100, to_server: false, seq: 5000, flags: SYN, payload: vec![] },
+        Segment { micros: 1_000, to_server: true, seq: 1001, flags: PSH, payload: request.clone() },
+        Segment { micros: 2_500, to_server: false, seq: 5001 + resp_a.len() as u32, flags: PSH, payload: resp_b.to_vec() },
+        // retransmission of the request
+        Segment { micros: 2_700, to_server: true, seq: 1001, flags: PSH, payload: request.clone() },
+        Segment { micros: 3_000, to_server: false, seq: 5001, flags: PSH, payload: resp_a.to_vec() },
+        Segment { micros: 4_000, to_server: true, seq: 1001 + request.len() as u32, flags: FIN, payload: vec![] },
+    ]
+}
+
+fn tcp(seg: &Segment) -> Vec<u8> {
+    let (src, dst) = if seg.to_server { (CLIENT_PORT, SERVER_PORT) } else { (SERVER_PORT, CLIENT_PORT) };
+    let mut bytes = Vec::new();
+    bytes.extend(src.to_be_bytes());
+    bytes.extend(dst.to_be_bytes());
+    bytes.extend(seg.seq.to_be_bytes());
+    bytes.extend(0u32.to_be_bytes()); // ack
+    bytes.push(5 << 4);
+    bytes.push(seg.flags | 0x10);
+    bytes.extend(65535u16.to_be_bytes()); // window
+    bytes.extend([0, 0, 0, 0]); // checksum and urgent pointer
+    bytes.extend(&seg.payload);
+    bytes
+}
+
+fn ipv4(seg: &Segment, client: Ipv4Addr, server: Ipv4Addr) -> Vec<u8> {
+    let (src, dst) = if seg.to_server { (client, server) } else { (server, client) };
+    let tcp = tcp(seg);
+    let mut bytes = vec![0x45, 0];
+    bytes.extend((20 + tcp.len() as u16).to_be_bytes());
+    bytes.extend([0, 0, 0x40, 0, 64, 6, 0, 0]);
+    bytes.extend(src.octets());
+    bytes.extend(dst.octets());
+    bytes.extend(tcp);
+    bytes
+}
+
+fn ipv6(seg: &Segment, client: Ipv6Addr, server: Ipv6Addr) -> Vec<u8> {
+    let (src, dst) = if seg.to_server { (client, server) } else { (server, client) };
+    let tcp = tcp(seg);
+    let mut bytes = vec![0x60, 0, 0, 0];
+    bytes.extend((tcp.len() as u16).to_be_bytes());
+    bytes.extend([6, 64]);
+    bytes.extend(src.octets());
+    bytes.extend(dst.octets());
+    bytes.extend(tcp);
+    bytes
+}
+
+fn ethernet(ip: Vec<u8>) -> Vec<u8> {
+    let mut bytes = vec![0x02, 0, 0, 0, 0, 1, 0x02, 0, 0, 0, 0, 2, 0x08, 0x00];
+    bytes.extend(ip);
+    // minimum frame size padding
+    while bytes.len() < 60 {
+        bytes.push(0);
+    }
+    bytes
+}
+
+const START_SECS: u32 = 1_700_000_000;
+
+fn pcap_file(frames: Vec<(u64, Vec<u8>)>) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend(0xA1B2C3D4u32.to_le_bytes());
+    bytes.extend(2u16.to_le_bytes());
+    bytes.extend(4u16.to_le_bytes());
+    bytes.extend([0; 8]);
+    bytes.extend(65535u32.to_le_bytes());
+    bytes.extend(1u32.to_le_bytes()); // ethernet
+
+    for (micros, data) in frames {
+        bytes.extend((START_SECS + (micros / 1_000_000) as u32).to_le_bytes());
+        bytes.extend(((micros % 1_000_000) as u32).to_le_bytes());
+        bytes.extend((data.len() as u32).to_le_bytes());
+        bytes.extend((data.len() as u32).to_le_bytes());
+        bytes.extend(data);
+    }
+    bytes
+}
+
+fn pcapng_block(block_type: u32, body: Vec<u8>) -> Vec<u8> {
+    let mut body = body;
+    while !body.len().is_multiple_of(4) {
+        body.push(0);
+    }
+    let len = (body.len() + 12) as u32;
+    let mut bytes = Vec::new();
+    bytes.extend(block_type.to_be_bytes());
+    bytes.extend(len.to_be_bytes());
+    bytes.extend(body);
+    bytes.extend(len.to_be_bytes());
+    bytes
+}
+
+/// Big endian section with a raw IP interface with nanosecond timestamps
+fn pcapng_file(frames: Vec<(u64, Vec<u8>)>) -> Vec<u8> {
+    let mut shb = Vec::new();
+    shb.extend(0x1A2B3C4Du32.to_be_bytes());
+    shb.extend([0, 1, 0, 0]);
+    shb.extend((-1i64).to_be_bytes());
+    let mut bytes = pcapng_block(0x0A0D0D0A, shb);
+
+    let mut idb = Vec::new();
+    idb.extend(101u16.to_be_bytes());
+    idb.extend([0, 0]);
+    idb.extend(0u32.to_be_bytes());
+    idb.extend([0, 9, 0, 1, 9, 0, 0, 0]); // if_tsresol
+    idb.extend([0, 0, 0, 0]);
+    bytes.extend(pcapng_block(1, idb));
+
+    // name resolution block, which should be ignored
+    bytes.extend(pcapng_block(4, vec![0, 0, 0, 0]));
+
+    for (micros, data) in frames {
+        let ts = (START_SECS as u64 * 1_000_000 + micros) * 1000;
+        let mut epb = Vec::new();
+        epb.extend(0u32.to_be_bytes());
+        epb.extend(((ts >> 32) as u32).to_be_bytes());
+        epb.extend((ts as u32).to_be_bytes());
+        epb.extend((data.len() as u32).to_be_bytes());
+        epb.extend((data.len() as u32).to_be_bytes());
+        epb.extend(data);
+        bytes.extend(pcapng_block(6, epb));
+    }
+    bytes
+}
+
+fn check_conversation(conv: &Conversation) {
+    let start = SystemTime::UNIX_EPOCH + Duration::from_secs(START_SECS as u64);
+
+    assert!(conv.issues.is_empty(), "{:?}", conv.issues);
+    assert_eq!(conv.packets.len(), 2);
+
+    let request = &conv.packets[0];
+    assert_eq!(request.direction, MessageDirection::ClientToServer);
+    assert_eq!(request.message_name(), "ScriptsRootPath");
+    assert_eq!(request.server.port(), SERVER_PORT);
+    assert_eq!(request.client.port(), CLIENT_PORT);
+    assert_eq!(request.timestamp, start + Duration::from_micros(1_000));
+
+    let response = &conv.packets[1];
+    assert_eq!(response.direction, MessageDirection::ServerToClient);
+    assert_eq!(response.message_name(), "ScriptsRootPathResponse");
+    assert_eq!(response.timestamp, start + Duration::from_micros(3_000));
+    let result = ScriptsRootPathResponse::disassemble_packet(response.packet.clone()).unwrap();
+    assert_eq!(result.abs_path, std::path::PathBuf::from("C:/Witcher 3/content"));
+
+    assert_eq!(conv.start_time(), Some(request.timestamp));
+
+    let stats = conv.stats();
+    assert_eq!(stats.len(), 2);
+    assert_eq!(stats[0].message.as_deref(), Some("ScriptsRootPath"));
+    assert_eq!(stats[0].count, 1);
+    assert_eq!(stats[0].bytes, request.packet.encoded_size());
+    assert_eq!(stats[1].direction, MessageDirection::ServerToClient);
+}
+
+
+#[test]
+fn pcap_ethernet_ipv4_test() {
+    let client = Ipv4Addr::new(192, 168, 0, 10);
+    let server = Ipv4Addr::new(192, 168, 0, 20);
+    let mut frames: Vec<_> = conversation_segments().iter()
+        .map(|seg| (seg.micros, ethernet(ipv4(seg, client, server))))
+        .collect();
+    // unrelated traffic
+    frames.insert(1, (50, ethernet(vec![0x45, 0, 0, 28, 0, 0, 0, 0, 64, 17, 0, 0, 10, 0, 0, 1, 10, 0, 0, 2])));
+
+    let conv = CaptureDecoder::new().decode(&pcap_file(frames)).unwrap();
+    check_conversation(&conv);
+    assert_eq!(conv.ignored_frames, 1);
+    assert_eq!(conv.packets[0].client.ip(), IpAddr::V4(client));
+}
+
+#[test]
+fn pcapng_raw_ipv6_test() {
+    let client = Ipv6Addr::LOCALHOST;
+    let server = Ipv6Addr::LOCALHOST;
+    let frames: Vec<_> = conversation_segments().iter()
+        .map(|seg| (seg.micros, ipv6(seg, client, server)))
+        .collect();
+
+    let conv = CaptureDecoder::new().decode(&pcapng_file(frames)).unwrap();
+    check_conversation(&conv);
+    assert_eq!(conv.ignored_frames, 0);
+}
+
+#[test]
+fn custom_port_test() {
+    let frames: Vec<_> = conversation_segments().iter()
+        .map(|seg| (seg.micros, ethernet(ipv4(seg, Ipv4Addr::LOCALHOST, Ipv4Addr::LOCALHOST))))
+        .collect();
+
+    let conv = CaptureDecoder::new()
+        .with_server_ports([37000])
+        .decode(&pcap_file(frames))
+        .unwrap();
+    assert!(conv.packets.is_empty());
+    assert_eq!(conv.ignored_frames, 7);
+}
+
+#[test]
+fn unfinished_packet_test() {
+    let mut segments = conversation_segments();
+    // connection is dropped before the rest of the response is sent
+    segments.remove(5);
+
+    let frames: Vec<_> = segments.iter()
+        .map(|seg| (seg.micros, ethernet(ipv4(seg, Ipv4Addr::LOCALHOST, Ipv4Addr::LOCALHOST))))
+        .collect();
+
+    let conv = CaptureDecoder::new().decode(&pcap_file(frames)).unwrap();
+    assert_eq!(conv.packets.len(), 1);
+    assert_eq!(conv.issues.len(), 2);
+    assert_eq!(conv.issues[0].direction, MessageDirection::ServerToClient);
+    assert!(matches!(conv.issues[0].issue, StreamIssue::MissingData { bytes: 10 }));
+    // the rest of the response can't be decoded without its beginning
+    assert!(matches!(&conv.issues[1].issue, StreamIssue::Skipped(skipped) if skipped.reason == SkipReason::InvalidHead));
+}
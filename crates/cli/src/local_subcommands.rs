@@ -1,13 +1,17 @@
-use std::{thread, time::Duration};
+use std::{path::PathBuf, thread, time::{Duration, SystemTime}};
 
 use clap::{ Parser, Subcommand};
 use colored::{Colorize, Color};
 
-use crate::{logging::println_log, CliOptions};
+use rw3d_capture::{CaptureDecoder, CapturedIssue, CapturedPacket, Conversation};
+use rw3d_net::{messages::MessageDirection, protocol::WitcherPacketRef};
+
+use crate::{logging::{println_log, println_output}, CliOptions};
 
 
 /// Subcommands that can be executed without connecting to game's socket
 #[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)] // parsed only once
 pub(crate) enum LocalSubcommands {
     /// Prints game's script logs onto console
     Scriptslog {
@@ -27,6 +31,27 @@ pub(crate) enum LocalSubcommands {
         /// Colors inside mean the color of the background of the highlighted line
         #[clap(flatten)]
         colors: ScriptslogColors,
+    },
+    /// Generates a Lua dissector for Wireshark, which decodes communication with the game.
+    /// Put the file in Wireshark's personal Lua plugins directory.
+    WiresharkDissector {
+        /// File the dissector should be written to. If not specified, it is printed onto console
+        #[clap(short='o', long, display_order=0)]
+        output: Option<PathBuf>,
+    },
+    /// Reads communication with the game from a Wireshark or tcpdump capture file (pcap or pcapng).
+    /// Prints the decoded packets and statistics of the messages.
+    Capture {
+        /// Path to the capture file
+        file: PathBuf,
+
+        /// Port the game listens on. By default both the standalone game's and the editor's ports are used
+        #[clap(short='p', long="port", value_name="PORT", display_order=0)]
+        ports: Vec<u16>,
+
+        /// Print only statistics of the messages, without the packets
+        #[clap(short='s', long, display_order=1)]
+        stats_only: bool,
     }
 }
 
@@ -75,6 +100,27 @@ pub(crate) fn handle_local_subcommand( cmd: LocalSubcommands, options: CliOption
                 println_log(err);
             }
         }
+        LocalSubcommands::WiresharkDissector { output } => {
+            let lua = rw3d_net::wireshark::lua_dissector();
+            match output {
+                Some(path) => match std::fs::write(&path, lua) {
+                    Ok(_) => println_log(format!("Dissector written to {}", path.display())),
+                    Err(err) => println_log(format!("Failed to write the dissector: {}", err)),
+                },
+                None => println_output(lua)
+            }
+        }
+        LocalSubcommands::Capture { file, ports, stats_only } => {
+            let mut decoder = CaptureDecoder::new();
+            if !ports.is_empty() {
+                decoder = decoder.with_server_ports(ports);
+            }
+
+            match decoder.decode_file(&file) {
+                Ok(conv) => print_capture_conversation(conv, options.verbose, stats_only),
+                Err(err) => println_log(format!("Failed to read the capture: {:#}", anyhow::Error::new(err))),
+            }
+        }
     }
 }
 
@@ -152,7 +198,7 @@ fn scriptslog_colors_to_highlight_records(colors: ScriptslogColors) -> Vec<Scrip
 // The color chosen is not used anywhere else for that matter
 const NO_COLORING_PLACEHOLDER: Color = Color::BrightBlack;
 
-fn scriptslog_printer( text: &String, highlights: &Vec<ScriptslogHighlightRecord>, filter_non_highlighted: bool ) {
+fn scriptslog_printer( text: &str, highlights: &Vec<ScriptslogHighlightRecord>, filter_non_highlighted: bool ) {
     let lines = text.split("\n");
 
     for line in lines {
@@ -172,4 +218,73 @@ fn scriptslog_printer( text: &String, highlights: &Vec<ScriptslogHighlightRecord
             println!("{}", line);
         }
     }
-}
\ No newline at end of file
+}
+
+
+
+enum ConversationEntry {
+    Packet(CapturedPacket),
+    Issue(CapturedIssue)
+}
+
+impl ConversationEntry {
+    fn timestamp(&self) -> SystemTime {
+        match self {
+            ConversationEntry::Packet(p) => p.timestamp,
+            ConversationEntry::Issue(i) => i.timestamp,
+        }
+    }
+}
+
+fn print_capture_conversation(conv: Conversation, verbosity: u8, stats_only: bool) {
+    let stats = conv.stats();
+    let start = conv.start_time().unwrap_or(SystemTime::UNIX_EPOCH);
+
+    if !stats_only {
+        let mut entries: Vec<_> = conv.packets.into_iter().map(ConversationEntry::Packet)
+            .chain(conv.issues.into_iter().map(ConversationEntry::Issue))
+            .collect();
+        entries.sort_by_key(|e| e.timestamp());
+
+        for entry in entries {
+            let time = entry.timestamp().duration_since(start).unwrap_or_default().as_secs_f64();
+            match entry {
+                ConversationEntry::Packet(p) => {
+                    let arrow = match p.direction {
+                        MessageDirection::ClientToServer => format!("{} -> {}", p.client, p.server),
+                        MessageDirection::ServerToClient => format!("{} <- {}", p.client, p.server),
+                    };
+                    println_output(format!("[{:>10.6}s] {} {}", time, arrow, p.message_name().bold()));
+
+                    if verbosity > 0 {
+                        println_output(format!("  {}", p.packet));
+                    }
+                    if verbosity > 1 {
+                        match WitcherPacketRef::new(&p.frame) {
+                            Ok(frame) => println_output(frame.hexdump().with_message_name(p.message_name())),
+                            Err(err) => println_log(format!("  [Warning] Can't show the bytes of the packet. {}", err).yellow())
+                        }
+                    }
+                },
+                ConversationEntry::Issue(i) => {
+                    println_log(format!("[{:>10.6}s] [Warning] {} ({} <-> {})", time, i.issue, i.client, i.server).yellow());
+                }
+            }
+        }
+
+        println_output("");
+    }
+
+    println_output(format!("{:<16} {:<32} {:>8} {:>10}", "Direction", "Message", "Count", "Bytes").bold());
+    for s in &stats {
+        let direction = match s.direction {
+            MessageDirection::ClientToServer => "client -> game",
+            MessageDirection::ServerToClient => "game -> client",
+        };
+        println_output(format!("{:<16} {:<32} {:>8} {:>10}", direction, s.message.as_deref().unwrap_or("Unknown"), s.count, s.bytes));
+    }
+    println_output(format!("{:<16} {:<32} {:>8} {:>10}", "", "Total", stats.iter().map(|s| s.count).sum::<usize>(), stats.iter().map(|s| s.bytes).sum::<usize>()));
+    if conv.ignored_frames > 0 {
+        println_log(format!("{} frames in the capture were not related to the game", conv.ignored_frames));
+    }
+}
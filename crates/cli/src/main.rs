@@ -48,8 +48,9 @@ pub(crate) struct CliOptions {
     log_level: LogLevel, 
 
     /// Enable verbose printing of packet contents.
-    #[clap(long, short='v', display_order=5)]
-    verbose: bool,
+    /// Use twice (-vv) to also print raw bytes of packets as an annotated hexdump.
+    #[clap(long, short='v', action=clap::ArgAction::Count, display_order=5)]
+    verbose: u8,
 }
 
 #[derive(Debug, ArgEnum, Clone, Copy, PartialEq, Eq)]
@@ -1,13 +1,35 @@
-use std::sync::mpsc::Sender;
+use std::sync::{mpsc::Sender, OnceLock};
 
 use colored::Colorize;
-use rw3d_net::{messages::{notifications::*, requests::*}, protocol::WitcherPacket};
+use rw3d_net::{messages::{notifications::*, requests::*, MessageDirection, MessageIdRegistry}, protocol::{SkippedData, WitcherPacket, WitcherPacketRef}};
 
-use crate::logging::println_output;
+use crate::logging::{println_log, println_output};
 
 
-pub fn print_raw_packet(packet: WitcherPacket) {
-    println_output(format!("{:?}", packet));
+/// With verbosity above 1 also prints raw bytes of the packet
+pub fn print_raw_packet(packet: WitcherPacket, frame: &[u8], verbosity: u8) {
+    static SERVER_MESSAGES: OnceLock<MessageIdRegistry> = OnceLock::new();
+    let registry = SERVER_MESSAGES.get_or_init(|| {
+        let mut registry = MessageIdRegistry::new();
+        registry.register_server_messages().expect("Server messages should not be ambiguous");
+        registry
+    });
+
+    let type_name = registry.probe_message_in(&packet, MessageDirection::ServerToClient)
+        .map(|meta| meta.type_name.as_ref())
+        .unwrap_or("Unknown");
+    println_output(format!("[{}] {:?}", type_name, packet));
+
+    if verbosity > 1 {
+        match WitcherPacketRef::new(frame) {
+            Ok(frame) => println_output(registry.hexdump(&packet, frame)),
+            Err(err) => println_log(format!("[Warning] Can't show the bytes of the packet. {}", err).yellow())
+        }
+    }
+}
+
+pub fn print_skipped_data(skipped: SkippedData) {
+    println_log(format!("[Warning] Skipped {} bytes of data received from the game. {}", skipped.bytes.len(), skipped.reason).yellow());
 }
 
 
@@ -30,7 +52,7 @@ impl ScriptsReloadPrinter {
 
     pub fn print_progress(&mut self, params: ScriptsReloadProgressParams) {
         match params {
-            ScriptsReloadProgressParams::Started => if !self.verbose_printing {
+            ScriptsReloadProgressParams::Started if !self.verbose_printing => {
                 println_output("Script compilation started...");
             }
             ScriptsReloadProgressParams::Log { message } if !self.verbose_printing => {
@@ -73,7 +95,7 @@ impl ScriptsReloadPrinter {
             println_output(e.red());
         }
 
-        if self.errors.len() > 0 {
+        if !self.errors.is_empty() {
             println_output(""); // empty line between errors and warnings
         }
 
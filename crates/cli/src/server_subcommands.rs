@@ -2,7 +2,7 @@ use std::{net::Ipv4Addr, str::FromStr, thread, time::Duration};
 
 use anyhow::{bail, Context};
 use clap::Subcommand;
-use rw3d_net::{connection::{WitcherConnection, WitcherPort}, messages::requests::*};
+use rw3d_net::{connection::{ConnectionResult, WitcherConnection, WitcherPort}, messages::requests::*};
 use rw3d_net_client::WitcherClient;
 
 use crate::{logging::println_log, response_handling::*, CliOptions, ConnectionTarget};
@@ -69,8 +69,11 @@ pub(crate) fn handle_server_subcommand( cmd: ServerSubcommands, options: CliOpti
     println_log("Successfully connected to the game and started the client!");
     
 
-    if options.verbose {
-        client.on_raw_packet(print_raw_packet);
+    client.on_skipped_data(print_skipped_data);
+
+    if options.verbose > 0 {
+        let verbosity = options.verbose;
+        client.on_raw_packet(move |packet, frame| print_raw_packet(packet, frame, verbosity));
     }
 
     println_log("Executing the command...\n");
@@ -79,7 +82,7 @@ pub(crate) fn handle_server_subcommand( cmd: ServerSubcommands, options: CliOpti
     match cmd {
         ServerSubcommands::Reload { max_compile_time } => {
             let (finished_token, did_finish) = std::sync::mpsc::channel();
-            let mut scripts_reload_printer = ScriptsReloadPrinter::new(finished_token, options.verbose);
+            let mut scripts_reload_printer = ScriptsReloadPrinter::new(finished_token, options.verbose > 0);
             client.on_scripts_reload_progress(move |params| {
                 scripts_reload_printer.print_progress(params);
             });
@@ -87,7 +90,7 @@ pub(crate) fn handle_server_subcommand( cmd: ServerSubcommands, options: CliOpti
             client.reload_scripts()?;
 
             if let Some(max_compile_time) = max_compile_time {
-                if let Err(_) = did_finish.recv_timeout(std::time::Duration::from_millis(max_compile_time)) {
+                if did_finish.recv_timeout(std::time::Duration::from_millis(max_compile_time)).is_err() {
                     println_log("Scripts didn't compile in the specified time. Exiting early...");
                 }
             } else {
@@ -100,21 +103,21 @@ pub(crate) fn handle_server_subcommand( cmd: ServerSubcommands, options: CliOpti
             })?;
 
             // If printing is verbose it is handled by a notification callback
-            if !options.verbose {
+            if options.verbose == 0 {
                 print_exec_result(result);
             }
         }
         ServerSubcommands::Rootpath => {
             let result = client.scripts_root_path()?;
 
-            if !options.verbose {
+            if options.verbose == 0 {
                 print_root_path_result(result);
             }
         }
         ServerSubcommands::Modlist => {
             let result = client.script_packages()?;
 
-            if !options.verbose {
+            if options.verbose == 0 {
                 print_mod_list_result(result);
             }
         }
@@ -124,7 +127,7 @@ pub(crate) fn handle_server_subcommand( cmd: ServerSubcommands, options: CliOpti
                 func_name
             })?;
 
-            if !options.verbose {
+            if options.verbose == 0 {
                 print_opcodes(result);
             }
         }
@@ -134,7 +137,7 @@ pub(crate) fn handle_server_subcommand( cmd: ServerSubcommands, options: CliOpti
                 name_filter: name
             })?;
 
-            if !options.verbose {
+            if options.verbose == 0 {
                 print_var_list(result);
             }
         }
@@ -152,7 +155,7 @@ fn connect_to_standalone(ip: Ipv4Addr) -> anyhow::Result<WitcherConnection> {
 
     WitcherConnection::connect_timeout(ip.into(), port.clone(), Duration::from_millis(CONNECT_TIMEOUT_MILLIS))
         .context(format!("Failed to connect to the game on address {}:{}.\n\
-                          Make sure the game is running and that it was launched with following debug flags: -net -debugscripts.", ip.to_string(), port.as_number()))
+                          Make sure the game is running and that it was launched with following debug flags: -net -debugscripts.", ip, port.as_number()))
 }
 
 fn connect_to_redkit(ip: Ipv4Addr) -> anyhow::Result<WitcherConnection> {
@@ -160,16 +163,16 @@ fn connect_to_redkit(ip: Ipv4Addr) -> anyhow::Result<WitcherConnection> {
 
     WitcherConnection::connect_timeout(ip.into(), port.clone(), Duration::from_millis(CONNECT_TIMEOUT_MILLIS))
         .context(format!("Failed to connect to the game in REDkit on address {}:{}.\n\
-                          Make sure REDkit is running.", ip.to_string(), port.as_number()))
+                          Make sure REDkit is running.", ip, port.as_number()))
 }
 
 fn connect_try_both(ip: Ipv4Addr) -> anyhow::Result<WitcherConnection> {
-    let (conns_send, conns_recv) = std::sync::mpsc::channel::<(anyhow::Result<WitcherConnection>, WitcherPort)>();
+    let (conns_send, conns_recv) = std::sync::mpsc::channel::<(ConnectionResult<WitcherConnection>, WitcherPort)>();
     
     let connect_on_port = |port: WitcherPort| {
         let sender = conns_send.clone();
         std::thread::spawn(move || {
-            let conn = WitcherConnection::connect_timeout(ip.clone().into(), port.clone(), Duration::from_millis(CONNECT_TIMEOUT_MILLIS));
+            let conn = WitcherConnection::connect_timeout(ip.into(), port.clone(), Duration::from_millis(CONNECT_TIMEOUT_MILLIS));
             let _ = sender.send((conn, port));
         })    
     };
@@ -190,7 +193,7 @@ fn connect_try_both(ip: Ipv4Addr) -> anyhow::Result<WitcherConnection> {
                     "editor"
                 };
 
-                port_errors.push_str(&format!("    [{}] {:?}\n", port_name, err));
+                port_errors.push_str(&format!("    [{}] {:#}\n", port_name, anyhow::Error::new(err)));
             }
         }
     }
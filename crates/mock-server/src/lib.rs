@@ -1,12 +1,18 @@
-use std::{collections::HashMap, net::{Ipv4Addr, TcpListener, TcpStream}, sync::{atomic::AtomicBool, Arc}};
+use std::{collections::HashMap, io::Write, net::{Ipv4Addr, TcpListener}, sync::{atomic::AtomicBool, Arc, Mutex}};
 
-use rw3d_net::{connection::WitcherPort, messages::{notifications::*, requests::*, Message, MessageId, MessageIdRegistry}, protocol::{Decode, Encode, WitcherPacket}};
+#[cfg(feature = "experimental")]
+use std::collections::BTreeMap;
+
+use rw3d_net::{connection::{MemoryTransport, WitcherPort, WitcherTransport}, messages::{notifications::*, requests::*, Message, MessageId, MessageIdRegistry}, protocol::{Decode, Encode, WitcherPacket}};
 
 
 pub struct MockWitcherServer {
-    listener: TcpListener,
     id_registry: MessageIdRegistry,
-    services: ServiceMap
+    services: ServiceMap,
+    #[cfg(feature = "experimental")]
+    breakpoints: Arc<BreakpointTable>,
+    #[cfg(feature = "experimental")]
+    debug_session: Arc<DebugSession>
 }
 
 type ServiceMap = HashMap<MessageId, Box<dyn Service + Send + Sync>>;
@@ -15,61 +21,100 @@ impl MockWitcherServer {
     const LISTEN_INTERVAL_MILLIS: u64 = 500;
     const READ_TIMEOUT_MILLIS: u64 = 100;
 
-    pub fn new() -> anyhow::Result<Arc<Self>> {
-        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, WitcherPort::Game.as_number()))?;
-        listener.set_nonblocking(true).unwrap();
-
+    pub fn new() -> Arc<Self> {
         let mut id_registry = MessageIdRegistry::new();
-        let mut services = ServiceMap::new();
-        
-        let id = id_registry.register_message::<ListenToNamespace>();
-        services.insert(id, Box::new(ListenToNamespaceService));
-
-        let id = id_registry.register_message::<ReloadScripts>();
-        services.insert(id, Box::new(ReloadScriptsService));
+        id_registry.register_client_messages().expect("Client messages should not be ambiguous");
 
-        let id = id_registry.register_message::<ScriptsRootPath>();
-        services.insert(id, Box::new(ScriptsRootPathService));
-
-        let id = id_registry.register_message::<ExecuteCommand>();
-        services.insert(id, Box::new(ExecuteCommandService));
+        let mut services = ServiceMap::new();
+        services.insert(ListenToNamespace::assemble_id(), Box::new(ListenToNamespaceService));
+        services.insert(ReloadScripts::assemble_id(), Box::new(ReloadScriptsService));
+        services.insert(ScriptsRootPath::assemble_id(), Box::new(ScriptsRootPathService));
+        services.insert(ScriptPackages::assemble_id(), Box::new(ScriptPackagesService));
+        services.insert(Opcodes::assemble_id(), Box::new(OpcodesService));
+        let config_vars = Arc::new(ConfigVarStore::default());
+        services.insert(ConfigVars::assemble_id(), Box::new(ConfigVarsService(config_vars.clone())));
+        #[cfg(feature = "experimental")]
+        services.insert(SetConfigVar::assemble_id(), Box::new(SetConfigVarService(config_vars.clone())));
+
+        #[cfg(feature = "experimental")]
+        let breakpoints = Arc::new(BreakpointTable::default());
+        #[cfg(feature = "experimental")]
+        let debug_session = Arc::new(DebugSession::default());
+        services.insert(ExecuteCommand::assemble_id(), Box::new(ExecuteCommandService {
+            #[cfg(feature = "experimental")]
+            breakpoints: breakpoints.clone(),
+            #[cfg(feature = "experimental")]
+            debug_session: debug_session.clone()
+        }));
+        #[cfg(feature = "experimental")]
+        {
+            services.insert(AddBreakpoint::assemble_id(), Box::new(AddBreakpointService(breakpoints.clone())));
+            services.insert(RemoveBreakpoint::assemble_id(), Box::new(RemoveBreakpointService(breakpoints.clone())));
+            services.insert(ToggleBreakpoint::assemble_id(), Box::new(ToggleBreakpointService(breakpoints.clone())));
+            services.insert(DebugContinue::assemble_id(), Box::new(DebugStepService { kind: DebugStep::Continue, session: debug_session.clone() }));
+            services.insert(DebugStepInto::assemble_id(), Box::new(DebugStepService { kind: DebugStep::Into, session: debug_session.clone() }));
+            services.insert(DebugStepOver::assemble_id(), Box::new(DebugStepService { kind: DebugStep::Over, session: debug_session.clone() }));
+            services.insert(DebugStepOut::assemble_id(), Box::new(DebugStepService { kind: DebugStep::Out, session: debug_session.clone() }));
+            services.insert(FrameLocals::assemble_id(), Box::new(FrameLocalsService(debug_session.clone())));
+            services.insert(ExpandProperty::assemble_id(), Box::new(ExpandPropertyService(debug_session.clone())));
+        }
 
-        let id = id_registry.register_message::<ScriptPackages>();
-        services.insert(id, Box::new(ScriptPackagesService));
+        #[cfg(feature = "experimental")]
+        {
+            let profiler = Arc::new(ProfilerSession::default());
+            services.insert(ProfilerStart::assemble_id(), Box::new(ProfilerStartService(profiler.clone())));
+            services.insert(ProfilerStop::assemble_id(), Box::new(ProfilerStopService(profiler.clone())));
+        }
 
-        let id = id_registry.register_message::<Opcodes>();
-        services.insert(id, Box::new(OpcodesService));
+        Arc::new(Self {
+            id_registry,
+            services,
+            #[cfg(feature = "experimental")]
+            breakpoints,
+            #[cfg(feature = "experimental")]
+            debug_session
+        })
+    }
 
-        let id = id_registry.register_message::<ConfigVars>();
-        services.insert(id, Box::new(ConfigVarsService));
+    /// Breakpoints set by all clients of the server
+    #[inline]
+    #[cfg(feature = "experimental")]
+    pub fn breakpoints(&self) -> &BreakpointTable {
+        &self.breakpoints
+    }
 
-        Ok(Arc::new(Self {
-            listener,
-            id_registry,
-            services
-        }))
+    /// Callstack of the script thread paused by the debugger, if there is any.
+    /// 
+    /// Scripts get paused when an exec command is run while there's an enabled breakpoint,
+    /// the thread is then stopped on the first of them.
+    #[cfg(feature = "experimental")]
+    pub fn paused_thread(&self) -> Option<BreakHitParams> {
+        self.debug_session.paused.lock().unwrap().clone()
     }
 
 
-    pub fn listen(self: Arc<Self>, cancel_token: Arc<AtomicBool>) {
+    /// Accepts TCP connections on the game's port until the token is cancelled.
+    pub fn listen(self: Arc<Self>, cancel_token: Arc<AtomicBool>) -> anyhow::Result<()> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, WitcherPort::Game.as_number()))?;
+        listener.set_nonblocking(true)?;
+
         println!("Server listening on port {}", WitcherPort::Game.as_number());
 
         loop {
             if cancel_token.load(std::sync::atomic::Ordering::Relaxed) {
                 println!("Shutting down the server...");
-                break;
+                break Ok(());
             }
 
-            match self.listener.accept() {
+            match listener.accept() {
                 Ok((socket, addr)) => {
                     println!("Client connected on address {}", addr);
     
                     socket.set_nonblocking(false).unwrap();
-                    socket.set_read_timeout(Some(std::time::Duration::from_millis(Self::READ_TIMEOUT_MILLIS))).unwrap();
                     let self_clone = self.clone();
                     std::thread::spawn(move || {
                         if let Err(err) = self_clone.serve_for(socket) {
-                            eprintln!("Server abruptly lost connection to the client: {}", err);
+                            eprintln!("Server abruptly lost connection to the client: {:#}", err);
                         }
                     });
                 }
@@ -83,14 +128,38 @@ impl MockWitcherServer {
         }
     }
 
-    pub fn serve_for(&self, mut client_socket: TcpStream) -> anyhow::Result<()> {
+    /// Creates an in-memory connection served on a separate thread.
+    /// Returns the client's end of it. Serving stops once the client closes its end.
+    pub fn serve_in_memory(self: &Arc<Self>) -> MemoryTransport {
+        let (client_end, server_end) = MemoryTransport::pair();
+        let self_clone = self.clone();
+        std::thread::spawn(move || {
+            if let Err(err) = self_clone.serve_for(server_end) {
+                eprintln!("Server abruptly lost connection to the client: {:#}", err);
+            }
+        });
+
+        client_end
+    }
+
+    /// Handles messages coming from the client until it closes the connection.
+    pub fn serve_for<T: WitcherTransport>(&self, mut client_transport: T) -> anyhow::Result<()> {
+        client_transport.set_read_timeout(Some(std::time::Duration::from_millis(Self::READ_TIMEOUT_MILLIS)))?;
+
         loop {
-            match client_socket.peek(&mut [0u8; 6]) {
+            match client_transport.peek(&mut [0u8; 6]) {
+                Ok(0) => {
+                    break Ok(());
+                }
                 Ok(_) => {
-                    let packet = WitcherPacket::decode_from(&mut client_socket)?;
+                    let packet = WitcherPacket::decode_from(&mut client_transport)?;
                     // println!("Received packet: \n{:?}", packet);
-                    if let Some(service) = self.id_registry.probe_message_id(&packet).and_then(|id| self.services.get(&id)) {
-                        service.accept_packet(packet, &mut client_socket);
+                    match self.id_registry.probe_message(&packet) {
+                        Some(meta) => match self.services.get(&meta.id) {
+                            Some(service) => service.accept_packet(packet, &mut client_transport),
+                            None => println!("No mock service for {} message", meta.type_name)
+                        },
+                        None => println!("Received an unknown message")
                     }
                 }
                 Err(err) => {
@@ -106,14 +175,14 @@ impl MockWitcherServer {
 
 
 trait Service {
-    fn accept_packet(&self, packet: WitcherPacket, socket: &mut TcpStream);
+    fn accept_packet(&self, packet: WitcherPacket, out: &mut dyn Write);
 }
 
 
 struct ListenToNamespaceService;
 
 impl Service for ListenToNamespaceService {
-    fn accept_packet(&self, _packet: WitcherPacket, _socket: &mut TcpStream) {
+    fn accept_packet(&self, _packet: WitcherPacket, _out: &mut dyn Write) {
         println!("Handling ListenToNamespace notification...");
         // notification, nothing is expected to be sent back
     }
@@ -123,27 +192,27 @@ impl Service for ListenToNamespaceService {
 struct ReloadScriptsService;
 
 impl Service for ReloadScriptsService {
-    fn accept_packet(&self, _packet: WitcherPacket, socket: &mut TcpStream) {
+    fn accept_packet(&self, _packet: WitcherPacket, mut out: &mut dyn Write) {
         println!("Handling ReloadScripts notification...");
 
-        ScriptsReloadProgress::assemble_packet(ScriptsReloadProgressParams::Started).encode_into(socket).unwrap();
+        ScriptsReloadProgress::assemble_packet(ScriptsReloadProgressParams::Started).encode_into(&mut out).unwrap();
         ScriptsReloadProgress::assemble_packet(ScriptsReloadProgressParams::Log { 
             message: "Compiling foo.ws".into() 
-        }).encode_into(socket).unwrap();
+        }).encode_into(&mut out).unwrap();
         ScriptsReloadProgress::assemble_packet(ScriptsReloadProgressParams::Log { 
             message: "Compiling bar.ws".into() 
-        }).encode_into(socket).unwrap();
+        }).encode_into(&mut out).unwrap();
         ScriptsReloadProgress::assemble_packet(ScriptsReloadProgressParams::Warn { 
             line: 12,
             local_script_path: "bar.ws".into(),
             message: "Variable declared, but unused".into() 
-        }).encode_into(socket).unwrap();
+        }).encode_into(&mut out).unwrap();
 
         std::thread::sleep(std::time::Duration::from_secs(1));
 
         ScriptsReloadProgress::assemble_packet(ScriptsReloadProgressParams::Finished { 
             success: true
-        }).encode_into(socket).unwrap();
+        }).encode_into(&mut out).unwrap();
     }
 }
 
@@ -151,25 +220,65 @@ impl Service for ReloadScriptsService {
 struct ScriptsRootPathService;
 
 impl Service for ScriptsRootPathService {
-    fn accept_packet(&self, _packet: WitcherPacket, socket: &mut TcpStream) {
+    fn accept_packet(&self, _packet: WitcherPacket, mut out: &mut dyn Write) {
         println!("Handling ScriptsRootPath request...");
 
         ScriptsRootPathResponse::assemble_packet(ScriptsRootPathResult {
             abs_path: r"C:\GOG\Witcher 3\content\content0\scripts".into()
-        }).encode_into(socket).unwrap();
+        }).encode_into(&mut out).unwrap();
     }
 }
 
 
-struct ExecuteCommandService;
+struct ExecuteCommandService {
+    #[cfg(feature = "experimental")]
+    breakpoints: Arc<BreakpointTable>,
+    #[cfg(feature = "experimental")]
+    debug_session: Arc<DebugSession>
+}
 
 impl Service for ExecuteCommandService {
-    fn accept_packet(&self, _packet: WitcherPacket, socket: &mut TcpStream) {
+    #[cfg_attr(not(feature = "experimental"), allow(unused_variables))]
+    fn accept_packet(&self, packet: WitcherPacket, mut out: &mut dyn Write) {
         println!("Handling ExecuteCommand request...");
 
+        #[cfg(feature = "experimental")]
+        self.pause_on_breakpoint(packet, out);
+
         ExecuteCommandResponse::assemble_packet(ExecuteCommandResult::Success { 
             log_output: None
-        }).encode_into(socket).unwrap();
+        }).encode_into(&mut out).unwrap();
+    }
+}
+
+impl ExecuteCommandService {
+    /// Pretends the function called by the command ran into a breakpoint
+    #[cfg(feature = "experimental")]
+    fn pause_on_breakpoint(&self, packet: WitcherPacket, mut out: &mut dyn Write) {
+        let breakpoint = self.breakpoints.list().into_iter().find(|(_, enabled)| *enabled);
+        if let (Some((location, _)), Ok(params)) = (breakpoint, ExecuteCommand::disassemble_packet(packet)) {
+            let exec_func = params.cmd.split('(').next().unwrap_or_default().trim().to_string();
+            let hit = BreakHitParams {
+                thread_id: DebugSession::THREAD_ID,
+                callstack: vec![
+                    CallstackFrame {
+                        func_name: "OnExec".into(),
+                        class_name: Some("CR4Player".into()),
+                        local_script_path: location.local_script_path,
+                        line: location.line
+                    },
+                    CallstackFrame {
+                        func_name: exec_func,
+                        class_name: None,
+                        local_script_path: "local/exec.ws".into(),
+                        line: 1
+                    }
+                ]
+            };
+
+            *self.debug_session.paused.lock().unwrap() = Some(hit.clone());
+            BreakHit::assemble_packet(hit).encode_into(&mut out).unwrap();
+        }
     }
 }
 
@@ -177,7 +286,7 @@ impl Service for ExecuteCommandService {
 struct ScriptPackagesService;
 
 impl Service for ScriptPackagesService {
-    fn accept_packet(&self, _packet: WitcherPacket, socket: &mut TcpStream) {
+    fn accept_packet(&self, _packet: WitcherPacket, mut out: &mut dyn Write) {
         println!("Handling ScriptPackages request...");
 
         ScriptPackagesResponse::assemble_packet(ScriptPackagesResult {
@@ -199,7 +308,7 @@ impl Service for ScriptPackagesService {
                     package_name: "modBrothersInArms".into()
                 }
             ]
-        }).encode_into(socket).unwrap();
+        }).encode_into(&mut out).unwrap();
     }
 }
 
@@ -207,7 +316,7 @@ impl Service for ScriptPackagesService {
 struct OpcodesService;
 
 impl Service for OpcodesService {
-    fn accept_packet(&self, _packet: WitcherPacket, socket: &mut TcpStream) {
+    fn accept_packet(&self, _packet: WitcherPacket, mut out: &mut dyn Write) {
         println!("Handling Opcodes request...");
 
         OpcodesResponse::assemble_packet(OpcodesResult {
@@ -229,19 +338,21 @@ impl Service for OpcodesService {
                     ]
                 }
             ]
-        }).encode_into(socket).unwrap();
+        }).encode_into(&mut out).unwrap();
     }
 }
 
 
-struct ConfigVarsService;
-
-impl Service for ConfigVarsService {
-    fn accept_packet(&self, _packet: WitcherPacket, socket: &mut TcpStream) {
-        println!("Handling ConfigVars request...");
+/// Config vars of the game, which clients can change
+#[derive(Debug)]
+struct ConfigVarStore {
+    vars: Mutex<Vec<ConfigVarInfo>>
+}
 
-        ConfigVarsResponse::assemble_packet(ConfigVarsResult {
-            vars: vec![
+impl Default for ConfigVarStore {
+    fn default() -> Self {
+        Self {
+            vars: Mutex::new(vec![
                 ConfigVarInfo {
                     section: "Visuals".into(),
                     name: "GammaValue".into(),
@@ -263,7 +374,445 @@ impl Service for ConfigVarsService {
                     data_type: 3,
                     _unknown0: 0
                 }
-            ]
-        }).encode_into(socket).unwrap();
+            ])
+        }
+    }
+}
+
+#[cfg(feature = "experimental")]
+impl ConfigVarStore {
+    /// Returns false if there's no such var or the value can't be parsed as var's type
+    fn set(&self, section: &str, name: &str, value: &str) -> bool {
+        let mut vars = self.vars.lock().unwrap();
+        let Some(var) = vars.iter_mut().find(|v| v.section == section && v.name == name) else {
+            return false;
+        };
+
+        let valid = match var.data_type {
+            1 => value == "true" || value == "false",
+            2 => value.parse::<i32>().is_ok(),
+            3 => value.parse::<f32>().is_ok(),
+            _ => true
+        };
+        if valid {
+            var.value = value.to_string();
+        }
+
+        valid
+    }
+}
+
+
+struct ConfigVarsService(Arc<ConfigVarStore>);
+
+impl Service for ConfigVarsService {
+    fn accept_packet(&self, packet: WitcherPacket, mut out: &mut dyn Write) {
+        println!("Handling ConfigVars request...");
+
+        let Ok(params) = ConfigVars::disassemble_packet(packet) else {
+            eprintln!("Malformed ConfigVars request");
+            return;
+        };
+
+        let contains = |s: &str, filter: &Option<String>| {
+            filter.as_ref().map(|f| s.to_lowercase().contains(&f.to_lowercase())).unwrap_or(true)
+        };
+        let vars = self.0.vars.lock().unwrap().iter()
+            .filter(|v| contains(&v.section, &params.section_filter) && contains(&v.name, &params.name_filter))
+            .cloned()
+            .collect();
+
+        ConfigVarsResponse::assemble_packet(ConfigVarsResult {
+            vars
+        }).encode_into(&mut out).unwrap();
+    }
+}
+
+
+#[cfg(feature = "experimental")]
+struct SetConfigVarService(Arc<ConfigVarStore>);
+
+#[cfg(feature = "experimental")]
+impl Service for SetConfigVarService {
+    fn accept_packet(&self, packet: WitcherPacket, mut out: &mut dyn Write) {
+        println!("Handling SetConfigVar request...");
+
+        let Ok(params) = SetConfigVar::disassemble_packet(packet) else {
+            eprintln!("Malformed SetConfigVar request");
+            return;
+        };
+
+        SetConfigVarResponse::assemble_packet(SetConfigVarResult {
+            success: self.0.set(&params.section, &params.name, &params.value)
+        }).encode_into(&mut out).unwrap();
+    }
+}
+
+
+
+/// Breakpoints kept the same way the game would, shared between all connected clients
+#[derive(Debug, Default)]
+#[cfg(feature = "experimental")]
+pub struct BreakpointTable {
+    // breakpoint location -> is enabled
+    entries: Mutex<BTreeMap<BreakpointParams, bool>>
+}
+
+#[cfg(feature = "experimental")]
+impl BreakpointTable {
+    /// Returns whether there's a breakpoint at given location and if so, whether it's enabled
+    pub fn get(&self, location: &BreakpointParams) -> Option<bool> {
+        self.entries.lock().unwrap().get(location).copied()
+    }
+
+    /// All breakpoints together with their enabled state, sorted by location
+    pub fn list(&self) -> Vec<(BreakpointParams, bool)> {
+        self.entries.lock().unwrap().iter()
+            .map(|(location, enabled)| (location.clone(), *enabled))
+            .collect()
+    }
+
+    fn add(&self, location: BreakpointParams) -> BreakpointState {
+        // adding a breakpoint that already exists enables it again
+        self.entries.lock().unwrap().insert(location, true);
+        BreakpointState::Enabled
+    }
+
+    fn remove(&self, location: &BreakpointParams) -> BreakpointState {
+        match self.entries.lock().unwrap().remove(location) {
+            Some(_) => BreakpointState::Removed,
+            None => BreakpointState::Rejected
+        }
+    }
+
+    fn toggle(&self, location: &BreakpointParams, enabled: bool) -> BreakpointState {
+        match self.entries.lock().unwrap().get_mut(location) {
+            Some(current) => {
+                *current = enabled;
+                if enabled { BreakpointState::Enabled } else { BreakpointState::Disabled }
+            },
+            None => BreakpointState::Rejected
+        }
+    }
+}
+
+
+#[cfg(feature = "experimental")]
+struct AddBreakpointService(Arc<BreakpointTable>);
+
+#[cfg(feature = "experimental")]
+impl Service for AddBreakpointService {
+    fn accept_packet(&self, packet: WitcherPacket, mut out: &mut dyn Write) {
+        println!("Handling AddBreakpoint request...");
+
+        let Ok(params) = AddBreakpoint::disassemble_packet(packet) else {
+            eprintln!("Malformed AddBreakpoint request");
+            return;
+        };
+
+        AddBreakpointResponse::assemble_packet(BreakpointConfirmation {
+            local_script_path: params.local_script_path.clone(),
+            line: params.line,
+            state: self.0.add(params)
+        }).encode_into(&mut out).unwrap();
+    }
+}
+
+
+#[cfg(feature = "experimental")]
+struct RemoveBreakpointService(Arc<BreakpointTable>);
+
+#[cfg(feature = "experimental")]
+impl Service for RemoveBreakpointService {
+    fn accept_packet(&self, packet: WitcherPacket, mut out: &mut dyn Write) {
+        println!("Handling RemoveBreakpoint request...");
+
+        let Ok(params) = RemoveBreakpoint::disassemble_packet(packet) else {
+            eprintln!("Malformed RemoveBreakpoint request");
+            return;
+        };
+
+        let state = self.0.remove(&params);
+        RemoveBreakpointResponse::assemble_packet(BreakpointConfirmation {
+            local_script_path: params.local_script_path,
+            line: params.line,
+            state
+        }).encode_into(&mut out).unwrap();
+    }
+}
+
+
+#[cfg(feature = "experimental")]
+struct ToggleBreakpointService(Arc<BreakpointTable>);
+
+#[cfg(feature = "experimental")]
+impl Service for ToggleBreakpointService {
+    fn accept_packet(&self, packet: WitcherPacket, mut out: &mut dyn Write) {
+        println!("Handling ToggleBreakpoint request...");
+
+        let Ok(params) = ToggleBreakpoint::disassemble_packet(packet) else {
+            eprintln!("Malformed ToggleBreakpoint request");
+            return;
+        };
+
+        let location = BreakpointParams {
+            local_script_path: params.local_script_path,
+            line: params.line
+        };
+        let state = self.0.toggle(&location, params.enabled);
+        ToggleBreakpointResponse::assemble_packet(BreakpointConfirmation {
+            local_script_path: location.local_script_path,
+            line: location.line,
+            state
+        }).encode_into(&mut out).unwrap();
+    }
+}
+
+
+
+/// State of the script debugger shared between all connected clients
+#[derive(Debug, Default)]
+#[cfg(feature = "experimental")]
+struct DebugSession {
+    paused: Mutex<Option<BreakHitParams>>
+}
+
+#[cfg(feature = "experimental")]
+impl DebugSession {
+    /// The mock runs all scripts on a single thread
+    const THREAD_ID: u32 = 1;
+
+    fn has_frame(&self, thread_id: u32, frame_index: u32) -> bool {
+        match self.paused.lock().unwrap().as_ref() {
+            Some(hit) => thread_id == hit.thread_id && (frame_index as usize) < hit.callstack.len(),
+            None => false
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+#[cfg(feature = "experimental")]
+enum DebugStep {
+    Continue,
+    Into,
+    Over,
+    Out
+}
+
+#[cfg(feature = "experimental")]
+struct DebugStepService {
+    kind: DebugStep,
+    session: Arc<DebugSession>
+}
+
+#[cfg(feature = "experimental")]
+impl Service for DebugStepService {
+    fn accept_packet(&self, _packet: WitcherPacket, mut out: &mut dyn Write) {
+        println!("Handling {:?} debug step notification...", self.kind);
+
+        let mut paused = self.session.paused.lock().unwrap();
+        let Some(hit) = paused.as_mut() else {
+            println!("Scripts are not paused, nothing to do");
+            return;
+        };
+
+        match self.kind {
+            DebugStep::Continue => {
+                *paused = None;
+                return;
+            },
+            DebugStep::Into => {
+                let caller = &hit.callstack[0];
+                let callee = CallstackFrame {
+                    func_name: "OnExecCallee".into(),
+                    class_name: caller.class_name.clone(),
+                    local_script_path: caller.local_script_path.clone(),
+                    line: caller.line + 10
+                };
+                hit.callstack.insert(0, callee);
+            },
+            DebugStep::Over => {
+                hit.callstack[0].line += 1;
+            },
+            DebugStep::Out => {
+                hit.callstack.remove(0);
+                if hit.callstack.is_empty() {
+                    *paused = None;
+                    return;
+                }
+            }
+        }
+
+        BreakHit::assemble_packet(hit.clone()).encode_into(&mut out).unwrap();
+    }
+}
+
+
+#[cfg(feature = "experimental")]
+struct FrameLocalsService(Arc<DebugSession>);
+
+#[cfg(feature = "experimental")]
+impl Service for FrameLocalsService {
+    fn accept_packet(&self, packet: WitcherPacket, mut out: &mut dyn Write) {
+        println!("Handling FrameLocals request...");
+
+        let Ok(params) = FrameLocals::disassemble_packet(packet) else {
+            eprintln!("Malformed FrameLocals request");
+            return;
+        };
+
+        // every frame has the same variables
+        let result = if self.0.has_frame(params.thread_id, params.frame_index) {
+            FrameLocalsResult {
+                parameters: vec![
+                    mock_value("amount", "int", "12", None)
+                ],
+                locals: vec![
+                    mock_value("i", "int", "0", None),
+                    mock_value("tags", "array<name>", "[2]", Some("tags".into()))
+                ],
+                this: Some(mock_value("this", "CR4Player", "CR4Player", Some("this".into())))
+            }
+        } else {
+            FrameLocalsResult {
+                parameters: vec![],
+                locals: vec![],
+                this: None
+            }
+        };
+
+        FrameLocalsResponse::assemble_packet(result).encode_into(&mut out).unwrap();
+    }
+}
+
+
+#[cfg(feature = "experimental")]
+struct ExpandPropertyService(Arc<DebugSession>);
+
+#[cfg(feature = "experimental")]
+impl Service for ExpandPropertyService {
+    fn accept_packet(&self, packet: WitcherPacket, mut out: &mut dyn Write) {
+        println!("Handling ExpandProperty request...");
+
+        let Ok(params) = ExpandProperty::disassemble_packet(packet) else {
+            eprintln!("Malformed ExpandProperty request");
+            return;
+        };
+
+        let children = if self.0.has_frame(params.thread_id, params.frame_index) {
+            let path = &params.path;
+            match path.as_str() {
+                "this" => vec![
+                    mock_value("inv", "CInventoryComponent", "CInventoryComponent", Some(path.child("inv"))),
+                    mock_value("level", "int", "35", None)
+                ],
+                "this.inv" => vec![
+                    mock_value("items", "array<SItemUniqueId>", "[1]", Some(path.child("items")))
+                ],
+                "this.inv.items" => vec![
+                    mock_value("[0]", "SItemUniqueId", "{ id = 1734 }", None)
+                ],
+                "tags" => vec![
+                    mock_value("[0]", "name", "monster", None),
+                    mock_value("[1]", "name", "hostile", None)
+                ],
+                _ => vec![]
+            }
+        } else {
+            vec![]
+        };
+
+        ExpandPropertyResponse::assemble_packet(ExpandPropertyResult {
+            path: params.path,
+            children
+        }).encode_into(&mut out).unwrap();
+    }
+}
+
+#[cfg(feature = "experimental")]
+fn mock_value(name: &str, type_name: &str, display_value: &str, child_handle: Option<PropertyPath>) -> ScriptValue {
+    ScriptValue {
+        name: name.into(),
+        type_name: type_name.into(),
+        display_value: display_value.into(),
+        child_handle
+    }
+}
+
+
+
+/// Time at which profiling was started, `None` if the profiler is not running
+#[cfg(feature = "experimental")]
+#[derive(Debug, Default)]
+struct ProfilerSession {
+    started: Mutex<Option<std::time::Instant>>
+}
+
+#[cfg(feature = "experimental")]
+struct ProfilerStartService(Arc<ProfilerSession>);
+
+#[cfg(feature = "experimental")]
+impl Service for ProfilerStartService {
+    fn accept_packet(&self, _packet: WitcherPacket, _out: &mut dyn Write) {
+        println!("Handling ProfilerStart notification...");
+
+        self.0.started.lock().unwrap().get_or_insert_with(std::time::Instant::now);
+    }
+}
+
+#[cfg(feature = "experimental")]
+struct ProfilerStopService(Arc<ProfilerSession>);
+
+#[cfg(feature = "experimental")]
+impl Service for ProfilerStopService {
+    fn accept_packet(&self, _packet: WitcherPacket, mut out: &mut dyn Write) {
+        println!("Handling ProfilerStop request...");
+
+        let report = match self.0.started.lock().unwrap().take() {
+            Some(started) => ProfilerReport {
+                duration_micros: started.elapsed().as_micros() as i64,
+                functions: vec![
+                    FunctionTiming {
+                        func_name: "CR4Player::OnTick".into(),
+                        call_count: 300,
+                        self_micros: 1500,
+                        total_micros: 5600
+                    },
+                    FunctionTiming {
+                        func_name: "CR4Player::UpdateHud".into(),
+                        call_count: 300,
+                        self_micros: 3600,
+                        total_micros: 3600
+                    },
+                    FunctionTiming {
+                        func_name: "GetWitcherPlayer".into(),
+                        call_count: 1200,
+                        self_micros: 500,
+                        total_micros: 500
+                    }
+                ],
+                stacks: vec![
+                    StackSample {
+                        frames: vec!["CR4Player::OnTick".into()],
+                        sample_count: 15,
+                        self_micros: 1500
+                    },
+                    StackSample {
+                        frames: vec!["CR4Player::OnTick".into(), "CR4Player::UpdateHud".into()],
+                        sample_count: 36,
+                        self_micros: 3600
+                    },
+                    StackSample {
+                        frames: vec!["CR4Player::OnTick".into(), "GetWitcherPlayer".into()],
+                        sample_count: 5,
+                        self_micros: 500
+                    }
+                ]
+            },
+            // the profiler was not running, so there's nothing to report
+            None => ProfilerReport::default()
+        };
+
+        ProfilerStopResponse::assemble_packet(report).encode_into(&mut out).unwrap();
     }
 }
@@ -1,16 +1,18 @@
-use std::sync::{atomic::AtomicBool, Arc, Mutex};
+use std::{sync::{atomic::AtomicBool, mpsc::Receiver, Arc, Mutex}, time::Duration};
 
-use anyhow::{bail, Context};
-use rw3d_net::{connection::WitcherConnection, messages::{notifications::*, requests::*, Message, WitcherNamespace}, protocol::WitcherPacket};
+use rw3d_net::{connection::{ConnectionResult, WitcherConnection}, messages::{notifications::*, requests::*, Message, WitcherNamespace}, protocol::{ProtocolResult, SkippedData, WitcherPacket}};
 
-use crate::Router;
+#[cfg(feature = "dynamic")]
+use rw3d_net::messages::dynamic::{DynamicStruct, MessageSchema};
+
+use crate::{ClientError, ClientResult, ResponseTicket, Router};
 
 
 #[derive(Debug)]
 pub struct WitcherClient {
     write_conn: Mutex<WitcherConnection>,
     router: Arc<Router>,
-    router_thread: Mutex<Option<std::thread::JoinHandle<anyhow::Result<()>>>>,
+    router_thread: Mutex<Option<std::thread::JoinHandle<ConnectionResult<()>>>>,
     router_cancel_token: Arc<AtomicBool>
 }
 
@@ -29,15 +31,15 @@ impl WitcherClient {
     /// This should be coupled with the call to [`Self::stop`] at the end of client's lifetime.
     /// 
     /// Will error if the client was already started before.
-    pub fn start(&self) -> anyhow::Result<()> {
+    pub fn start(&self) -> ClientResult<()> {
         let mut current_router_thread = self.router_thread.lock().unwrap();
         if current_router_thread.is_some() {
-            bail!("Client has been started before")
+            return Err(ClientError::AlreadyStarted);
         }
 
         let router = self.router.clone();
         let cancel_token = self.router_cancel_token.clone();
-        let read_conn = self.write_conn.lock().unwrap().try_clone().context("Failed to clone the connection")?;
+        let read_conn = self.write_conn.lock().unwrap().try_clone()?;
 
         let router_thread = std::thread::spawn(move || router.event_loop(read_conn, cancel_token));
         *current_router_thread = Some(router_thread);
@@ -54,32 +56,45 @@ impl WitcherClient {
     /// Stop communication with the server.
     /// 
     /// Will error if the client has not been started yet.
-    pub fn stop(&self) -> anyhow::Result<()> {
+    pub fn stop(&self) -> ClientResult<()> {
         let mut current_router_thread = self.router_thread.lock().unwrap();
         if current_router_thread.is_none() {
-            bail!("Client thread has not been started yet");
+            return Err(ClientError::NotStarted);
         }
 
         self.router_cancel_token.store(true, std::sync::atomic::Ordering::Relaxed);
         match current_router_thread.take().unwrap().join() {
-            Ok(result) => result,
-            Err(join_err) => {
-                bail!("Client thread panicked: {:?}", join_err)
+            Ok(result) => Ok(result?),
+            Err(panic) => {
+                let msg = panic.downcast_ref::<&str>().map(|s| s.to_string())
+                    .or_else(|| panic.downcast_ref::<String>().cloned())
+                    .unwrap_or_default();
+                Err(ClientError::RouterPanicked(msg))
             }
         }
     }
 
 
     /// Set a callback that will be ivoked on every raw packet received from the server.
+    /// Along with the packet it's given the bytes of the frame exactly as they were received.
     #[inline]
     pub fn on_raw_packet<F>(&self, callback: F)
-    where F: FnMut(WitcherPacket) + Send + Sync + 'static {
+    where F: FnMut(WitcherPacket, &[u8]) + Send + Sync + 'static {
         self.router.set_raw_packet_callback(callback)
     }
 
+    /// Set a callback that will be invoked whenever data received from the server has to be thrown away,
+    /// because it could not be decoded or interpreted as the message it was identified as.
+    /// The client continues working with the data that comes after.
+    #[inline]
+    pub fn on_skipped_data<F>(&self, callback: F)
+    where F: FnMut(SkippedData) + Send + Sync + 'static {
+        self.router.set_skipped_data_callback(callback)
+    }
+
     /// Send a notification to the server to recompile scripts.
     #[inline]
-    pub fn reload_scripts(&self) -> anyhow::Result<()> {
+    pub fn reload_scripts(&self) -> ClientResult<()> {
         self.send_notification::<ReloadScripts>(())
     }
 
@@ -94,7 +109,7 @@ impl WitcherClient {
     /// 
     /// Will block until the response is received or client waits for too long (based on connection's read_timeout).
     #[inline]
-    pub fn scripts_root_path(&self) -> anyhow::Result<ScriptsRootPathResult> {
+    pub fn scripts_root_path(&self) -> ClientResult<ScriptsRootPathResult> {
         self.send_request::<ScriptsRootPath>(())
     }
 
@@ -102,7 +117,7 @@ impl WitcherClient {
     /// 
     /// Will block until the response is received or client waits for too long (based on connection's read_timeout).
     #[inline]
-    pub fn execute_command(&self, params: ExecuteCommandParams) -> anyhow::Result<ExecuteCommandResult> {
+    pub fn execute_command(&self, params: ExecuteCommandParams) -> ClientResult<ExecuteCommandResult> {
         self.send_request::<ExecuteCommand>(params)
     }
 
@@ -110,7 +125,7 @@ impl WitcherClient {
     /// 
     /// Will block until the response is received or client waits for too long (based on connection's read_timeout).
     #[inline]
-    pub fn script_packages(&self) -> anyhow::Result<ScriptPackagesResult> {
+    pub fn script_packages(&self) -> ClientResult<ScriptPackagesResult> {
         self.send_request::<ScriptPackages>(())
     }
 
@@ -118,7 +133,7 @@ impl WitcherClient {
     /// 
     /// Will block until the response is received or client waits for too long (based on connection's read_timeout).
     #[inline]
-    pub fn opcodes(&self, params: OpcodesParams) -> anyhow::Result<OpcodesResult> {
+    pub fn opcodes(&self, params: OpcodesParams) -> ClientResult<OpcodesResult> {
         self.send_request::<Opcodes>(params)
     }
 
@@ -126,82 +141,269 @@ impl WitcherClient {
     /// 
     /// Will block until the response is received or client waits for too long (based on connection's read_timeout).
     #[inline]
-    pub fn config_vars(&self, params: ConfigVarsParams) -> anyhow::Result<ConfigVarsResult> {
+    pub fn config_vars(&self, params: ConfigVarsParams) -> ClientResult<ConfigVarsResult> {
         self.send_request::<ConfigVars>(params)
     }
 
+    /// Send a request to change the value of an internal configuration var.
+    /// 
+    /// Requires the `experimental` feature, the messages used for this are a guess, see [`rw3d_net::messages::Message::EXPERIMENTAL`].
+    /// 
+    /// Will block until the response is received or client waits for too long (based on connection's read_timeout).
+    #[inline]
+    #[cfg(feature = "experimental")]
+    pub fn set_config_var(&self, params: SetConfigVarParams) -> ClientResult<SetConfigVarResult> {
+        self.send_request::<SetConfigVar>(params)
+    }
+
+    /// Send a request to set a breakpoint at given script line.
+    /// 
+    /// Requires the `experimental` feature, the breakpoint messages are a guess, see [`rw3d_net::messages::Message::EXPERIMENTAL`].
+    /// 
+    /// Will block until the response is received or client waits for too long (based on connection's read_timeout).
+    #[inline]
+    #[cfg(feature = "experimental")]
+    pub fn add_breakpoint(&self, params: BreakpointParams) -> ClientResult<BreakpointConfirmation> {
+        self.send_request::<AddBreakpoint>(params)
+    }
+
+    /// Send a request to clear the breakpoint at given script line.
+    /// 
+    /// Requires the `experimental` feature, the breakpoint messages are a guess, see [`rw3d_net::messages::Message::EXPERIMENTAL`].
+    /// 
+    /// Will block until the response is received or client waits for too long (based on connection's read_timeout).
+    #[inline]
+    #[cfg(feature = "experimental")]
+    pub fn remove_breakpoint(&self, params: BreakpointParams) -> ClientResult<BreakpointConfirmation> {
+        self.send_request::<RemoveBreakpoint>(params)
+    }
+
+    /// Send a request to enable or disable an existing breakpoint without removing it.
+    /// 
+    /// Requires the `experimental` feature, the breakpoint messages are a guess, see [`rw3d_net::messages::Message::EXPERIMENTAL`].
+    /// 
+    /// Will block until the response is received or client waits for too long (based on connection's read_timeout).
+    #[inline]
+    #[cfg(feature = "experimental")]
+    pub fn toggle_breakpoint(&self, params: ToggleBreakpointParams) -> ClientResult<BreakpointConfirmation> {
+        self.send_request::<ToggleBreakpoint>(params)
+    }
+
+    /// Send a request for parameters, local variables and `this` of a frame in the callstack of a paused thread.
+    /// 
+    /// Requires the `experimental` feature, the debugger messages are a guess, see [`rw3d_net::messages::Message::EXPERIMENTAL`].
+    /// 
+    /// Will block until the response is received or client waits for too long (based on connection's read_timeout).
+    #[inline]
+    #[cfg(feature = "experimental")]
+    pub fn frame_locals(&self, params: FrameLocalsParams) -> ClientResult<FrameLocalsResult> {
+        self.send_request::<FrameLocals>(params)
+    }
+
+    /// Send a request for members or elements of the value at given path in a frame of a paused thread.
+    /// 
+    /// Requires the `experimental` feature, the debugger messages are a guess, see [`rw3d_net::messages::Message::EXPERIMENTAL`].
+    /// 
+    /// Will block until the response is received or client waits for too long (based on connection's read_timeout).
+    #[inline]
+    #[cfg(feature = "experimental")]
+    pub fn expand_property(&self, params: ExpandPropertyParams) -> ClientResult<ExpandPropertyResult> {
+        self.send_request::<ExpandProperty>(params)
+    }
+
+    /// Retrieves children of a value returned by [`Self::frame_locals`] or [`Self::expand_property`].
+    /// No request is sent if the value can't be expanded.
+    /// 
+    /// Will block until the response is received or client waits for too long (based on connection's read_timeout).
+    #[cfg(feature = "experimental")]
+    pub fn expand_value(&self, frame: FrameLocalsParams, value: &ScriptValue) -> ClientResult<Vec<ScriptValue>> {
+        let Some(path) = value.child_handle.clone() else {
+            return Ok(Vec::new());
+        };
+
+        let result = self.expand_property(ExpandPropertyParams {
+            thread_id: frame.thread_id,
+            frame_index: frame.frame_index,
+            path
+        })?;
+
+        Ok(result.children)
+    }
+
+    /// Send a notification to the server to start profiling scripts.
+    /// 
+    /// Requires the `experimental` feature, the profiler messages are a guess, see [`rw3d_net::messages::Message::EXPERIMENTAL`].
+    #[inline]
+    #[cfg(feature = "experimental")]
+    pub fn start_profiler(&self) -> ClientResult<()> {
+        self.send_notification::<ProfilerStart>(())
+    }
+
+    /// Send a request to stop profiling scripts and retrieve the data gathered since the profiler was started.
+    /// 
+    /// Requires the `experimental` feature, the profiler messages are a guess, see [`rw3d_net::messages::Message::EXPERIMENTAL`].
+    /// 
+    /// Will block until the response is received or client waits for too long (based on connection's read_timeout).
+    #[inline]
+    #[cfg(feature = "experimental")]
+    pub fn stop_profiler(&self) -> ClientResult<ProfilerReport> {
+        self.send_request::<ProfilerStop>(())
+    }
+
+    /// Set a callback that will be invoked whenever scripts get paused on a breakpoint or after a step.
+    /// 
+    /// Requires the `experimental` feature, the debugger messages are a guess, see [`rw3d_net::messages::Message::EXPERIMENTAL`].
+    #[inline]
+    #[cfg(feature = "experimental")]
+    pub fn on_break<F>(&self, callback: F)
+    where F: FnMut(BreakHitParams) + Send + Sync + 'static {
+        self.on_notification::<BreakHit, F>(callback)
+    }
+
+    /// Send a notification to the server to resume paused scripts until the next breakpoint is hit.
+    /// 
+    /// Requires the `experimental` feature, the debugger messages are a guess, see [`rw3d_net::messages::Message::EXPERIMENTAL`].
+    #[inline]
+    #[cfg(feature = "experimental")]
+    pub fn resume(&self) -> ClientResult<()> {
+        self.send_notification::<DebugContinue>(())
+    }
+
+    /// Send a notification to the server to execute the current line and pause inside the function it calls.
+    /// 
+    /// Requires the `experimental` feature, the debugger messages are a guess, see [`rw3d_net::messages::Message::EXPERIMENTAL`].
+    #[inline]
+    #[cfg(feature = "experimental")]
+    pub fn step_into(&self) -> ClientResult<()> {
+        self.send_notification::<DebugStepInto>(())
+    }
+
+    /// Send a notification to the server to execute the current line and pause on the next one.
+    /// 
+    /// Requires the `experimental` feature, the debugger messages are a guess, see [`rw3d_net::messages::Message::EXPERIMENTAL`].
+    #[inline]
+    #[cfg(feature = "experimental")]
+    pub fn step_over(&self) -> ClientResult<()> {
+        self.send_notification::<DebugStepOver>(())
+    }
+
+    /// Send a notification to the server to finish the current function and pause in its caller.
+    /// 
+    /// Requires the `experimental` feature, the debugger messages are a guess, see [`rw3d_net::messages::Message::EXPERIMENTAL`].
+    #[inline]
+    #[cfg(feature = "experimental")]
+    pub fn step_out(&self) -> ClientResult<()> {
+        self.send_notification::<DebugStepOut>(())
+    }
+
+
+    /// Send a notification described by a schema instead of a compile-time message type.
+    #[cfg(feature = "dynamic")]
+    pub fn send_dynamic_notification(&self, schema: &MessageSchema, body: &DynamicStruct) -> ClientResult<()> {
+        let packet = schema.assemble_packet(body)?;
+        self.write_conn.lock().unwrap().send(packet)?;
+        Ok(())
+    }
+
+    /// Send a request described by a schema and wait for the response described by another one.
+    /// 
+    /// Will block until the response is received or client waits for too long (based on connection's read_timeout).
+    #[cfg(feature = "dynamic")]
+    pub fn send_dynamic_request(&self, request: &MessageSchema, response: &MessageSchema, body: &DynamicStruct) -> ClientResult<DynamicStruct> {
+        let read_timeout = self.write_conn.lock().unwrap().get_read_timeout()?;
+        let (send, recv) = std::sync::mpsc::channel();
+        let result_sender = move |result: ProtocolResult<DynamicStruct>| {
+            // requester may have already given up waiting
+            let _ = send.send(result);
+        };
+
+        let packet = request.assemble_packet(body)?;
+        let ticket = self.router.add_dynamic_response_callback(response.clone(), result_sender)?;
+        self.await_response(ticket, packet, recv, read_timeout)
+    }
+
+    /// Set a callback that will be invoked for notifications described by a schema sent from the server.
+    /// 
+    /// Will error if the schema's id can't be told apart from ids of messages already known to the client.
+    #[cfg(feature = "dynamic")]
+    pub fn on_dynamic_notification<F>(&self, schema: &MessageSchema, callback: F) -> ClientResult<()>
+    where F: FnMut(DynamicStruct) + Send + Sync + 'static {
+        self.router.set_dynamic_notification_callback(schema.clone(), callback)?;
+        Ok(())
+    }
+
 
 
-    /// Notify the server to send back messages concerning given namespaces.
+    /// Notify the server to send back messages concerning given namespace.
     /// This is necessary for responses to be received by the client.
     /// 
-    /// To listen to all namespaces use [`Self::listen_to_all_namespaces`].
+    /// All namespaces known to the library are listened to when the client starts.
+    /// Use this to receive messages from other namespaces, which can be given by their name.
     #[inline]
-    fn listen_to_namespace(&self, params: ListenToNamespaceParams) -> anyhow::Result<()> {
-        self.send_notification::<ListenToNamespace>(params)
+    pub fn listen_to_namespace<N: Into<WitcherNamespace>>(&self, namesp: N) -> ClientResult<()> {
+        self.send_notification::<ListenToNamespace>(ListenToNamespaceParams {
+            namesp: namesp.into()
+        })
     }
 
     /// Notify the server to send back messages.
     /// This is necessary for responses to be received by the client and should be called after creating it.
-    fn listen_to_all_namespaces(&self) -> anyhow::Result<()> {
-        self.listen_to_namespace(ListenToNamespaceParams {
-            namesp: WitcherNamespace::Config
-        })?;
-        self.listen_to_namespace(ListenToNamespaceParams {
-            namesp: WitcherNamespace::Remote
-        })?;
-        self.listen_to_namespace(ListenToNamespaceParams {
-            namesp: WitcherNamespace::ScriptCompiler
-        })?;
-        self.listen_to_namespace(ListenToNamespaceParams {
-            namesp: WitcherNamespace::ScriptDebugger
-        })?;
-        self.listen_to_namespace(ListenToNamespaceParams {
-            namesp: WitcherNamespace::ScriptProfiler
-        })?;
-        self.listen_to_namespace(ListenToNamespaceParams {
-            namesp: WitcherNamespace::Scripts
-        })?;
-        self.listen_to_namespace(ListenToNamespaceParams {
-            namesp: WitcherNamespace::Utility
-        })?;
+    fn listen_to_all_namespaces(&self) -> ClientResult<()> {
+        for namesp in WitcherNamespace::KNOWN {
+            self.listen_to_namespace(namesp)?;
+        }
 
         Ok(())
     }
 
 
-    fn send_notification<N>(&self, params: N::Body) -> anyhow::Result<()> 
+    fn send_notification<N>(&self, params: N::Body) -> ClientResult<()> 
     where N: Notification + Send + Sync + 'static {
         let packet = N::assemble_packet(params);
         self.write_conn.lock().unwrap().send(packet)?;
         Ok(())
     }
 
-    fn send_request<R>(&self, params: R::Body) -> anyhow::Result<<R::Response as Message>::Body>
+    fn send_request<R>(&self, params: R::Body) -> ClientResult<<R::Response as Message>::Body>
     where R: Request + Send + Sync + 'static,
           R::Response: Send + Sync + 'static,
           <R::Response as Message>::Body: Send {
 
         let read_timeout = self.write_conn.lock().unwrap().get_read_timeout()?;          
         let (send, recv) = std::sync::mpsc::channel();
-        let result_sender = move |result: <R::Response as Message>::Body| {
-            send.send(result).unwrap()
+        let result_sender = move |result: ProtocolResult<<R::Response as Message>::Body>| {
+            // requester may have already given up waiting
+            let _ = send.send(result);
         };
                 
         let packet = R::assemble_packet(params);
-        self.router.add_response_callback::<R::Response, _>(result_sender);
-        self.write_conn.lock().unwrap().send(packet)?;
-        
-        let result = recv.recv_timeout(read_timeout).context("Waited too long for the response")?;
+        let ticket = self.router.add_response_callback::<R::Response, _>(result_sender)?;
+        self.await_response(ticket, packet, recv, read_timeout)
+    }
+
+    /// Sends the request and waits for the callback behind the ticket to pass on the response.
+    /// The callback is removed if the response doesn't come, so it won't take a response meant for a later request.
+    fn await_response<T>(&self, ticket: ResponseTicket, packet: WitcherPacket, recv: Receiver<ProtocolResult<T>>, read_timeout: Duration) -> ClientResult<T> {
+        if let Err(err) = self.write_conn.lock().unwrap().send(packet) {
+            self.router.remove_response_callback(ticket);
+            return Err(err.into());
+        }
 
-        Ok(result)
+        match recv.recv_timeout(read_timeout) {
+            Ok(result) => Ok(result?),
+            Err(_) => {
+                self.router.remove_response_callback(ticket);
+                Err(ClientError::ResponseTimeout { timeout: read_timeout })
+            }
+        }
     }
 
     fn on_notification<N, F>(&self, callback: F) 
     where N: Notification + Send + Sync + 'static,
           F: FnMut(N::Body) + Send + Sync + 'static {
 
-        self.router.set_notification_callback::<N, F>(callback);
+        self.router.set_notification_callback::<N, F>(callback)
+            .expect("Server messages are registered up front");
     }
 }
 
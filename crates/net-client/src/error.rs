@@ -0,0 +1,29 @@
+use std::time::Duration;
+
+use thiserror::Error;
+use rw3d_net::{connection::ConnectionError, messages::AmbiguousMessageId, protocol::ProtocolError};
+
+
+/// Errors that can be returned by [`crate::WitcherClient`].
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("Client has been started before")]
+    AlreadyStarted,
+    #[error("Client thread has not been started yet")]
+    NotStarted,
+    #[error("Waited too long for the response, timeout was {timeout:?}")]
+    ResponseTimeout {
+        timeout: Duration
+    },
+    #[error("Client thread panicked: {0}")]
+    RouterPanicked(String),
+
+    #[error(transparent)]
+    Connection(#[from] ConnectionError),
+    #[error(transparent)]
+    Protocol(#[from] ProtocolError),
+    #[error(transparent)]
+    AmbiguousMessage(#[from] AmbiguousMessageId)
+}
+
+pub type ClientResult<T> = Result<T, ClientError>;
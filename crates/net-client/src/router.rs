@@ -1,81 +1,176 @@
-use std::{collections::VecDeque, marker::PhantomData, sync::{atomic::AtomicBool, Arc, Mutex}};
+use std::{borrow::Cow, collections::VecDeque, marker::PhantomData, sync::{atomic::{AtomicBool, AtomicU64, Ordering}, Arc, Mutex}};
 
-use anyhow::Context;
 use dashmap::DashMap;
-use rw3d_net::{connection::WitcherConnection, messages::*, protocol::WitcherPacket};
+use rw3d_net::{connection::{ConnectionResult, WitcherConnection}, messages::*, protocol::{ProtocolResult, SkipReason, SkippedData, WitcherPacket}};
+#[cfg(feature = "dynamic")]
+use rw3d_net::messages::dynamic::{DynamicStruct, MessageSchema};
 
 
+/// Handlers are keyed by names of message types rather than by their ids,
+/// so that routing can use metadata borrowed from the registry without cloning the id of every packet.
 pub(crate) struct Router {
     id_registry: Mutex<MessageIdRegistry>,
-    raw_packet_handler: Mutex<Option<Box<dyn RouteHandler + Send + Sync>>>,
-    response_handlers: DashMap<MessageId, VecDeque<Box<dyn RouteHandler + Send + Sync>>>,
-    notif_handlers: DashMap<MessageId, Box<dyn RouteHandler + Send + Sync>>,
+    raw_packet_handler: Mutex<Option<RawPacketCallback>>,
+    response_handlers: DashMap<Cow<'static, str>, VecDeque<PendingResponse>>,
+    next_response_ticket: AtomicU64,
+    notif_handlers: DashMap<Cow<'static, str>, Box<dyn RouteHandler + Send + Sync>>,
+    skipped_data_handler: Mutex<Option<SkippedDataCallback>>,
+}
+
+type RawPacketCallback = Box<dyn FnMut(WitcherPacket, &[u8]) + Send + Sync>;
+type SkippedDataCallback = Box<dyn FnMut(SkippedData) + Send + Sync>;
+/// Response handler together with the number of its [`ResponseTicket`]
+type PendingResponse = (u64, Box<dyn RouteHandler + Send + Sync>);
+
+/// Identifies a response callback that is still waiting, so that it can be removed if the response never comes
+#[derive(Debug)]
+pub(crate) struct ResponseTicket {
+    type_name: Cow<'static, str>,
+    number: u64
 }
 
 impl Router {
     pub fn new() -> Self {
+        let mut id_registry = MessageIdRegistry::new();
+        // some ids overlap, so registering them one by one only when needed could fail depending on the order
+        id_registry.register_server_messages().expect("Server messages should not be ambiguous");
+
         Self {
-            id_registry: Mutex::new(MessageIdRegistry::new()),
+            id_registry: Mutex::new(id_registry),
             raw_packet_handler: Mutex::new(None),
             response_handlers: DashMap::new(),
-            notif_handlers: DashMap::new()
+            next_response_ticket: AtomicU64::new(0),
+            notif_handlers: DashMap::new(),
+            skipped_data_handler: Mutex::new(None)
         }
     }   
 
-    pub fn add_response_callback<R, F>(&self, callback: F) 
+    pub fn add_response_callback<R, F>(&self, callback: F) -> Result<ResponseTicket, AmbiguousMessageId>
     where R: Response + Send + Sync + 'static,
-          F: FnOnce(R::Body) + Send + Sync + 'static {
+          F: FnOnce(ProtocolResult<R::Body>) + Send + Sync + 'static {
         
-        let id = self.id_registry.lock().unwrap().register_message::<R>();
-        self.response_handlers.entry(id)
-            .or_default()
-            .push_back(Box::new(ResponseRouteHandler::<R, F>::new(callback)));
+        let type_name = self.register(MessageMetadata::of::<R>())?;
+        Ok(self.push_response_handler(type_name, Box::new(ResponseRouteHandler::<R, F>::new(callback))))
     }
 
-    pub fn set_notification_callback<N, F>(&self, callback: F) 
+    pub fn set_notification_callback<N, F>(&self, callback: F) -> Result<(), AmbiguousMessageId>
     where N: Notification + Send + Sync + 'static,
           F: FnMut(N::Body) + Send + Sync + 'static {
         
-        let id = self.id_registry.lock().unwrap().register_message::<N>();
-        self.notif_handlers.entry(id)
+        let type_name = self.register(MessageMetadata::of::<N>())?;
+        self.notif_handlers.entry(type_name)
             .insert(Box::new(NotificationRouteHandler::<N, F>::new(callback)));
+        Ok(())
+    }
+
+    #[cfg(feature = "dynamic")]
+    pub fn add_dynamic_response_callback<F>(&self, schema: MessageSchema, callback: F) -> Result<ResponseTicket, AmbiguousMessageId>
+    where F: FnOnce(ProtocolResult<DynamicStruct>) + Send + Sync + 'static {
+
+        let type_name = self.register(schema.metadata())?;
+        Ok(self.push_response_handler(type_name, Box::new(DynamicResponseRouteHandler { schema, resp_callback: Some(callback) })))
+    }
+
+    /// Registers the message and returns the name under which its handlers are kept
+    fn register(&self, meta: MessageMetadata) -> Result<Cow<'static, str>, AmbiguousMessageId> {
+        let type_name = meta.type_name.clone();
+        self.id_registry.lock().unwrap().register(meta)?;
+        Ok(type_name)
+    }
+
+    fn push_response_handler(&self, type_name: Cow<'static, str>, handler: Box<dyn RouteHandler + Send + Sync>) -> ResponseTicket {
+        let number = self.next_response_ticket.fetch_add(1, Ordering::Relaxed);
+        self.response_handlers.entry(type_name.clone())
+            .or_default()
+            .push_back((number, handler));
+        ResponseTicket { type_name, number }
+    }
+
+    /// Removes the callback if it's still waiting for its response.
+    /// Otherwise a late response would be handed to it instead of to the next request of the same type.
+    pub fn remove_response_callback(&self, ticket: ResponseTicket) {
+        if let Some(mut rhs) = self.response_handlers.get_mut(&ticket.type_name) {
+            rhs.retain(|(number, _)| *number != ticket.number);
+        }
+    }
+
+    #[cfg(feature = "dynamic")]
+    pub fn set_dynamic_notification_callback<F>(&self, schema: MessageSchema, callback: F) -> Result<(), AmbiguousMessageId>
+    where F: FnMut(DynamicStruct) + Send + Sync + 'static {
+
+        let type_name = self.register(schema.metadata())?;
+        self.notif_handlers.entry(type_name)
+            .insert(Box::new(DynamicNotificationRouteHandler { schema, notif_callback: callback }));
+        Ok(())
     }
 
     pub fn set_raw_packet_callback<F>(&self, callback: F)
-    where F: FnMut(WitcherPacket) + Send + Sync + 'static {
+    where F: FnMut(WitcherPacket, &[u8]) + Send + Sync + 'static {
         let mut raw_handler = self.raw_packet_handler.lock().unwrap();
-        *raw_handler = Some(Box::new(RawRouteHandler::new(callback)));
+        *raw_handler = Some(Box::new(callback));
     }
 
-    pub fn event_loop(&self, mut read_conn: WitcherConnection, cancel_token: Arc<AtomicBool>) -> anyhow::Result<()> {
+    pub fn set_skipped_data_callback<F>(&self, callback: F)
+    where F: FnMut(SkippedData) + Send + Sync + 'static {
+        let mut skipped_handler = self.skipped_data_handler.lock().unwrap();
+        *skipped_handler = Some(Box::new(callback));
+    }
+
+    pub fn event_loop(&self, mut read_conn: WitcherConnection, cancel_token: Arc<AtomicBool>) -> ConnectionResult<()> {
         loop {
-            if cancel_token.load(std::sync::atomic::Ordering::Relaxed) {
+            if cancel_token.load(Ordering::Relaxed) {
                 break;
             }
 
-            if read_conn.peek()? {
-                let packet = read_conn.receive()?;
+            // waits at most for the duration of connection's read timeout
+            if let Some((packet, frame)) = read_conn.try_receive_frame_recovering(|skipped| self.report_skipped_data(skipped))? {
                 {
                     let mut raw_handler = self.raw_packet_handler.lock().unwrap();
                     if let Some(raw_handler) = &mut *raw_handler {
-                        raw_handler.accept_packet(packet.clone())?;
+                        (raw_handler)(packet.clone(), &frame);
                     }
                 }
-                if let Some(id) = self.id_registry.lock().unwrap().probe_message_id(&packet) {
-                    if let Some(mut nh) = self.notif_handlers.get_mut(&id) {
-                        nh.accept_packet(packet)?;
-                    }
-                    else if let Some(mut rhs) = self.response_handlers.get_mut(&id) {
-                        if let Some(mut rh) = rhs.pop_front() {
-                            rh.accept_packet(packet)?;
-                        }
-                    }
+                // a bad packet only gets reported, it's not a reason to stop listening
+                if let Err(err) = self.route_packet(packet) {
+                    self.report_skipped_data(SkippedData {
+                        bytes: frame,
+                        reason: SkipReason::MalformedMessage(err.chain_message())
+                    });
                 }
             }
         }
 
         Ok(())
     }
+
+    fn route_packet(&self, packet: WitcherPacket) -> ProtocolResult<()> {
+        let id_registry = self.id_registry.lock().unwrap();
+        let Some(meta) = id_registry.probe_message(&packet) else {
+            return Ok(());
+        };
+
+        // registry is not kept locked while handlers run, they may want to register something themselves
+        if let Some(mut nh) = self.notif_handlers.get_mut(meta.type_name.as_ref()) {
+            drop(id_registry);
+            nh.accept_packet(packet)?;
+        }
+        else if let Some(mut rhs) = self.response_handlers.get_mut(meta.type_name.as_ref()) {
+            drop(id_registry);
+            if let Some((_, mut rh)) = rhs.pop_front() {
+                drop(rhs);
+                rh.accept_packet(packet)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn report_skipped_data(&self, skipped: SkippedData) {
+        let mut skipped_handler = self.skipped_data_handler.lock().unwrap();
+        if let Some(skipped_handler) = &mut *skipped_handler {
+            (skipped_handler)(skipped);
+        }
+    }
 }
 
 impl std::fmt::Debug for Router {
@@ -83,8 +178,9 @@ impl std::fmt::Debug for Router {
         f.debug_struct("Router")
             .field("id_registry", &self.id_registry)
             .field("raw_handler", &self.raw_packet_handler.lock().unwrap().is_some())
-            .field("response_handlers", &self.response_handlers.iter().map(|h| h.key().to_owned()).collect::<Vec<_>>())
-            .field("notif_handlers", &self.notif_handlers.iter().map(|h| h.key().to_owned()).collect::<Vec<_>>())
+            .field("response_handlers", &self.response_handlers.iter().map(|h| h.key().clone()).collect::<Vec<_>>())
+            .field("notif_handlers", &self.notif_handlers.iter().map(|h| h.key().clone()).collect::<Vec<_>>())
+            .field("skipped_data_handler", &self.skipped_data_handler.lock().unwrap().is_some())
             .finish()
     }
 }
@@ -92,7 +188,7 @@ impl std::fmt::Debug for Router {
 
 
 trait RouteHandler {
-    fn accept_packet(&mut self, packet: WitcherPacket) -> anyhow::Result<()>;
+    fn accept_packet(&mut self, packet: WitcherPacket) -> ProtocolResult<()>;
 }
 
 
@@ -113,8 +209,8 @@ where N: Notification, F: FnMut(N::Body) {
 
 impl<N, F> RouteHandler for NotificationRouteHandler<N, F> 
 where N: Notification, F: FnMut(N::Body) {
-    fn accept_packet(&mut self, packet: WitcherPacket) -> anyhow::Result<()> {
-        let notif = N::disassemble_packet(packet).context("Notification deserialization error")?;
+    fn accept_packet(&mut self, packet: WitcherPacket) -> ProtocolResult<()> {
+        let notif = N::disassemble_packet(packet)?;
         (self.notif_callback)(notif);
         Ok(())
     }
@@ -127,7 +223,7 @@ struct ResponseRouteHandler<R, F> {
 }
 
 impl<R, F> ResponseRouteHandler<R, F>
-where R: Response, F: FnOnce(R::Body) {
+where R: Response, F: FnOnce(ProtocolResult<R::Body>) {
     fn new(resp_callback: F) -> Self {
         Self {
             resp_callback: Some(resp_callback),
@@ -136,10 +232,11 @@ where R: Response, F: FnOnce(R::Body) {
     }
 }
 
+/// The requester is waiting for this response, so failing to decode it is passed on to the callback
 impl<R, F> RouteHandler for ResponseRouteHandler<R, F> 
-where R: Response, F: FnOnce(R::Body) {
-    fn accept_packet(&mut self, packet: WitcherPacket) -> anyhow::Result<()> {
-        let resp = R::disassemble_packet(packet).context("Response deserialization error")?;
+where R: Response, F: FnOnce(ProtocolResult<R::Body>) {
+    fn accept_packet(&mut self, packet: WitcherPacket) -> ProtocolResult<()> {
+        let resp = R::disassemble_packet(packet);
         if let Some(resp_handler) = self.resp_callback.take() {
             (resp_handler)(resp);
         }
@@ -148,23 +245,37 @@ where R: Response, F: FnOnce(R::Body) {
 }
 
 
-struct RawRouteHandler<F> {
-    raw_packet_callback: F,
+#[cfg(feature = "dynamic")]
+struct DynamicNotificationRouteHandler<F> {
+    schema: MessageSchema,
+    notif_callback: F
 }
 
-impl<F> RawRouteHandler<F>
-where F: FnMut(WitcherPacket) {
-    fn new(raw_packet_callback: F) -> Self {
-        Self {
-            raw_packet_callback
-        }
+#[cfg(feature = "dynamic")]
+impl<F> RouteHandler for DynamicNotificationRouteHandler<F>
+where F: FnMut(DynamicStruct) {
+    fn accept_packet(&mut self, packet: WitcherPacket) -> ProtocolResult<()> {
+        let notif = self.schema.disassemble_packet(packet)?;
+        (self.notif_callback)(notif);
+        Ok(())
     }
 }
 
-impl<F> RouteHandler for RawRouteHandler<F>
-where F: FnMut(WitcherPacket) {
-    fn accept_packet(&mut self, packet: WitcherPacket) -> anyhow::Result<()> {
-        (self.raw_packet_callback)(packet);
+
+#[cfg(feature = "dynamic")]
+struct DynamicResponseRouteHandler<F> {
+    schema: MessageSchema,
+    resp_callback: Option<F>
+}
+
+#[cfg(feature = "dynamic")]
+impl<F> RouteHandler for DynamicResponseRouteHandler<F>
+where F: FnOnce(ProtocolResult<DynamicStruct>) {
+    fn accept_packet(&mut self, packet: WitcherPacket) -> ProtocolResult<()> {
+        let resp = self.schema.disassemble_packet(packet);
+        if let Some(resp_handler) = self.resp_callback.take() {
+            (resp_handler)(resp);
+        }
         Ok(())
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,50 @@
+use rw3d_mock_server::MockWitcherServer;
+use rw3d_net::{connection::{WitcherConnection, WitcherPort}, messages::dynamic::*, protocol::WireType};
+use rw3d_net_client::WitcherClient;
+
+
+
+#[test]
+fn dynamic_message_test() -> anyhow::Result<()> {
+    let schemas = MessageSchemaSet::from_toml_str(r#"
+        [[message]]
+        name = "ScriptsRootPath"
+        direction = "ClientToServer"
+        namespace = "ScriptCompiler"
+        response = "ScriptsRootPathResponse"
+        id = [
+            { type = "StringUTF8", value = "ScriptCompiler" },
+            { type = "StringUTF8", value = "RootPath" }
+        ]
+
+        [[message]]
+        name = "ScriptsRootPathResponse"
+        direction = "ServerToClient"
+        namespace = "ScriptCompiler"
+        id = [
+            { type = "StringUTF8", value = "ScriptCompiler" },
+            { type = "StringUTF8", value = "RootPathConfirm" }
+        ]
+        body = [
+            { name = "abs_path", type = "StringUTF16" }
+        ]
+    "#)?;
+
+    let server = MockWitcherServer::new();
+    let transport = server.serve_in_memory();
+
+    let conn = WitcherConnection::from_transport(transport, WitcherPort::Game)?;
+    let client = WitcherClient::new(conn);
+    client.start()?;
+
+    let request = schemas.get("ScriptsRootPath").unwrap();
+    let response = schemas.response_of(request).unwrap();
+    let result = client.send_dynamic_request(request, response, &DynamicStruct::new())?;
+
+    let abs_path = result.get_data("abs_path").unwrap();
+    assert_eq!(abs_path.wire_type(), Some(WireType::StringUTF16));
+
+    client.stop()?;
+
+    Ok(())
+}
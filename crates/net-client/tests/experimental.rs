@@ -0,0 +1,241 @@
+use std::time::Duration;
+
+use rw3d_mock_server::MockWitcherServer;
+use rw3d_net::{connection::{WitcherConnection, WitcherPort}, messages::requests::*};
+use rw3d_net_client::WitcherClient;
+
+
+// The mock server answers with the same guessed layouts the library uses,
+// so these tests only check that the client and the mock agree, not that the game would understand any of it.
+
+#[test]
+fn breakpoints_test() -> anyhow::Result<()> {
+    let server = MockWitcherServer::new();
+    let transport = server.serve_in_memory();
+
+    let conn = WitcherConnection::from_transport(transport, WitcherPort::Game)?;
+    let client = WitcherClient::new(conn);
+    client.start()?;
+
+    let location = BreakpointParams {
+        local_script_path: r"game\player\playerWitcher.ws".into(),
+        line: 1364
+    };
+
+    let confirm = client.add_breakpoint(location.clone())?;
+    assert_eq!(confirm.local_script_path, location.local_script_path);
+    assert_eq!(confirm.line, location.line);
+    assert_eq!(confirm.state, BreakpointState::Enabled);
+    assert_eq!(server.breakpoints().get(&location), Some(true));
+
+    let confirm = client.toggle_breakpoint(ToggleBreakpointParams {
+        local_script_path: location.local_script_path.clone(),
+        line: location.line,
+        enabled: false
+    })?;
+    assert_eq!(confirm.state, BreakpointState::Disabled);
+    assert_eq!(server.breakpoints().list(), vec![(location.clone(), false)]);
+
+    let confirm = client.remove_breakpoint(location.clone())?;
+    assert_eq!(confirm.state, BreakpointState::Removed);
+    assert_eq!(server.breakpoints().get(&location), None);
+
+    let confirm = client.remove_breakpoint(location.clone())?;
+    assert_eq!(confirm.state, BreakpointState::Rejected);
+
+    client.stop()?;
+
+    Ok(())
+}
+
+
+#[test]
+fn break_hit_test() -> anyhow::Result<()> {
+    let server = MockWitcherServer::new();
+    let transport = server.serve_in_memory();
+
+    let conn = WitcherConnection::from_transport(transport, WitcherPort::Game)?;
+    let client = WitcherClient::new(conn);
+    client.start()?;
+
+    let (send_hit, recv_hit) = std::sync::mpsc::channel();
+    client.on_break(move |params| {
+        send_hit.send(params).unwrap();
+    });
+
+    let location = BreakpointParams {
+        local_script_path: r"game\player\playerWitcher.ws".into(),
+        line: 1364
+    };
+    client.add_breakpoint(location.clone())?;
+
+    client.execute_command(ExecuteCommandParams { 
+        cmd: "spawnt(12)".into() 
+    })?;
+    let hit = recv_hit.recv_timeout(Duration::from_secs(5))?;
+    assert_eq!(hit.callstack.len(), 2);
+    assert_eq!(hit.callstack[0].local_script_path, location.local_script_path);
+    assert_eq!(hit.callstack[0].line, location.line);
+    assert_eq!(hit.callstack[1].func_name, "spawnt");
+    assert_eq!(server.paused_thread(), Some(hit.clone()));
+
+    client.step_over()?;
+    let hit = recv_hit.recv_timeout(Duration::from_secs(5))?;
+    assert_eq!(hit.callstack[0].line, location.line + 1);
+
+    client.step_into()?;
+    let hit = recv_hit.recv_timeout(Duration::from_secs(5))?;
+    assert_eq!(hit.callstack.len(), 3);
+
+    client.step_out()?;
+    let hit = recv_hit.recv_timeout(Duration::from_secs(5))?;
+    assert_eq!(hit.callstack.len(), 2);
+    assert_eq!(hit.callstack[0].line, location.line + 1);
+
+    client.resume()?;
+    // requests are handled in order, so once this one is answered scripts should be running again
+    client.scripts_root_path()?;
+    assert_eq!(server.paused_thread(), None);
+    assert!(recv_hit.try_recv().is_err());
+
+    client.stop()?;
+
+    Ok(())
+}
+
+
+#[test]
+fn frame_locals_test() -> anyhow::Result<()> {
+    let server = MockWitcherServer::new();
+    let transport = server.serve_in_memory();
+
+    let conn = WitcherConnection::from_transport(transport, WitcherPort::Game)?;
+    let client = WitcherClient::new(conn);
+    client.start()?;
+
+    let (send_hit, recv_hit) = std::sync::mpsc::channel();
+    client.on_break(move |params| {
+        send_hit.send(params).unwrap();
+    });
+
+    let frame = FrameLocalsParams {
+        thread_id: 1,
+        frame_index: 0
+    };
+
+    // nothing to inspect when scripts are running
+    let locals = client.frame_locals(frame)?;
+    assert!(locals.parameters.is_empty() && locals.locals.is_empty() && locals.this.is_none());
+
+    client.add_breakpoint(BreakpointParams {
+        local_script_path: r"game\player\playerWitcher.ws".into(),
+        line: 1364
+    })?;
+    client.execute_command(ExecuteCommandParams { 
+        cmd: "spawnt(12)".into() 
+    })?;
+    let hit = recv_hit.recv_timeout(Duration::from_secs(5))?;
+    let frame = FrameLocalsParams {
+        thread_id: hit.thread_id,
+        frame_index: 0
+    };
+
+    let locals = client.frame_locals(frame)?;
+    assert_eq!(locals.parameters[0].name, "amount");
+    assert!(!locals.parameters[0].is_expandable());
+    assert!(client.expand_value(frame, &locals.parameters[0])?.is_empty());
+
+    let this = locals.this.unwrap();
+    assert_eq!(this.type_name, "CR4Player");
+    let members = client.expand_value(frame, &this)?;
+    let inv = members.iter().find(|m| m.name == "inv").unwrap();
+    let inv_members = client.expand_value(frame, inv)?;
+    let items = client.expand_value(frame, &inv_members[0])?;
+    assert_eq!(items[0].name, "[0]");
+    assert_eq!(items[0].type_name, "SItemUniqueId");
+
+    let result = client.expand_property(ExpandPropertyParams {
+        thread_id: hit.thread_id,
+        frame_index: 0,
+        path: "this.nonexistent".into()
+    })?;
+    assert!(result.children.is_empty());
+
+    client.stop()?;
+
+    Ok(())
+}
+
+#[test]
+fn profiler_test() -> anyhow::Result<()> {
+    let server = MockWitcherServer::new();
+    let transport = server.serve_in_memory();
+
+    let conn = WitcherConnection::from_transport(transport, WitcherPort::Game)?;
+    let client = WitcherClient::new(conn);
+    client.start()?;
+
+    // not started yet
+    let report = client.stop_profiler()?;
+    assert!(report.functions.is_empty() && report.stacks.is_empty());
+
+    client.start_profiler()?;
+    std::thread::sleep(Duration::from_millis(50));
+    let report = client.stop_profiler()?;
+    assert!(report.duration_micros >= 50_000);
+    assert!(!report.functions.is_empty());
+    assert!(report.stacks.iter().all(|s| !s.frames.is_empty()));
+
+    client.stop()?;
+
+    Ok(())
+}
+
+#[test]
+fn set_config_var_test() -> anyhow::Result<()> {
+    let server = MockWitcherServer::new();
+    let transport = server.serve_in_memory();
+
+    let conn = WitcherConnection::from_transport(transport, WitcherPort::Game)?;
+    let client = WitcherClient::new(conn);
+    client.start()?;
+
+    let read_gamma = || -> anyhow::Result<String> {
+        let result = client.config_vars(ConfigVarsParams {
+            section_filter: Some("Visuals".into()),
+            name_filter: Some("GammaValue".into())
+        })?;
+        assert_eq!(result.vars.len(), 1);
+        Ok(result.vars[0].value.clone())
+    };
+
+    assert_eq!(read_gamma()?, "1");
+
+    let result = client.set_config_var(SetConfigVarParams {
+        section: "Visuals".into(),
+        name: "GammaValue".into(),
+        value: "2".into()
+    })?;
+    assert!(result.success);
+    assert_eq!(read_gamma()?, "2");
+
+    // not an int
+    let result = client.set_config_var(SetConfigVarParams {
+        section: "Visuals".into(),
+        name: "GammaValue".into(),
+        value: "bright".into()
+    })?;
+    assert!(!result.success);
+    assert_eq!(read_gamma()?, "2");
+
+    let result = client.set_config_var(SetConfigVarParams {
+        section: "Visuals".into(),
+        name: "NoSuchVar".into(),
+        value: "1".into()
+    })?;
+    assert!(!result.success);
+
+    client.stop()?;
+
+    Ok(())
+}
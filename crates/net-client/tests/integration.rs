@@ -1,8 +1,8 @@
 use std::{net::Ipv4Addr, sync::{atomic::{AtomicBool, AtomicUsize, Ordering}, Arc}, time::Duration};
 
 use rw3d_mock_server::MockWitcherServer;
-use rw3d_net::{connection::{WitcherConnection, WitcherPort}, messages::{notifications::*, requests::*}};
-use rw3d_net_client::WitcherClient;
+use rw3d_net::{connection::{MemoryTransport, WitcherConnection, WitcherPort}, messages::{notifications::*, requests::*, Message}, protocol::{ProtocolError, WitcherPacketAssembler}};
+use rw3d_net_client::{ClientError, WitcherClient};
 
 
 
@@ -11,9 +11,8 @@ fn integration_test() -> anyhow::Result<()> {
     let server_cancel_token = Arc::new(AtomicBool::new(false));
     let cancel_token_cloned = server_cancel_token.clone();
     let server_handle = std::thread::spawn(move || -> anyhow::Result<()> {
-        let server = MockWitcherServer::new()?;
-        server.listen(cancel_token_cloned);
-        Ok(())
+        let server = MockWitcherServer::new();
+        server.listen(cancel_token_cloned)
     });
 
     // wait for the server to set up
@@ -25,7 +24,7 @@ fn integration_test() -> anyhow::Result<()> {
 
     let packets_received = Arc::new(AtomicUsize::new(0));
     let packets_received_cl = packets_received.clone();
-    client.on_raw_packet(move |_| {
+    client.on_raw_packet(move |_, _| {
         packets_received_cl.fetch_add(1, Ordering::Relaxed);
     });
 
@@ -71,4 +70,133 @@ fn integration_test() -> anyhow::Result<()> {
     server_handle.join().unwrap()?;
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[test]
+fn integration_in_memory_test() -> anyhow::Result<()> {
+    let server = MockWitcherServer::new();
+    let transport = server.serve_in_memory();
+
+    let conn = WitcherConnection::from_transport(transport, WitcherPort::Game)?;
+    let client = WitcherClient::new(conn);
+    client.start()?;
+
+    let packets_received = Arc::new(AtomicUsize::new(0));
+    let packets_received_cl = packets_received.clone();
+    client.on_raw_packet(move |_, _| {
+        packets_received_cl.fetch_add(1, Ordering::Relaxed);
+    });
+
+    let (finish_reload, did_finish_reload) = std::sync::mpsc::channel();
+    client.on_scripts_reload_progress(move |params| {
+        if let ScriptsReloadProgressParams::Finished { .. } = params {
+            finish_reload.send(()).unwrap();
+        }
+    });
+
+    client.reload_scripts()?;
+    assert!(did_finish_reload.recv_timeout(Duration::from_secs(5)).is_ok());
+
+    
+    client.scripts_root_path()?;
+
+
+    client.execute_command(ExecuteCommandParams { 
+        cmd: "spawnt(12)".into() 
+    })?;
+
+
+    client.script_packages()?;
+
+
+    client.opcodes(OpcodesParams {
+        func_name: "additem".into(),
+        class_name: None
+    })?;
+
+
+    client.config_vars(ConfigVarsParams {
+        section_filter: Some("boat".into()),
+        name_filter: Some("yaw".into())
+    })?;
+
+    assert!(packets_received.load(Ordering::Relaxed) >= 7);
+
+
+    client.stop()?;
+
+    Ok(())
+}
+
+#[test]
+fn late_response_test() -> anyhow::Result<()> {
+    let (client_transport, server_transport) = MemoryTransport::pair();
+    let mut conn = WitcherConnection::from_transport(client_transport, WitcherPort::Game)?;
+    conn.set_read_timeout(Duration::from_millis(200))?;
+    let client = WitcherClient::new(conn);
+    client.start()?;
+
+    let mut server_conn = WitcherConnection::from_transport(server_transport, WitcherPort::Game)?;
+    let respond = |server_conn: &mut WitcherConnection, path: &str| {
+        server_conn.send(ScriptsRootPathResponse::assemble_packet(ScriptsRootPathResult { abs_path: path.into() }))
+    };
+
+    // server doesn't answer in time
+    assert!(matches!(client.scripts_root_path(), Err(ClientError::ResponseTimeout { .. })));
+    respond(&mut server_conn, "late")?;
+    std::thread::sleep(Duration::from_millis(100));
+
+    // the late response must neither bring down the client nor be taken for the answer to the next request
+    let (send_path, recv_path) = std::sync::mpsc::channel();
+    let handle = std::thread::spawn(move || {
+        let _ = send_path.send(client.scripts_root_path().map(|r| r.abs_path));
+        client
+    });
+    std::thread::sleep(Duration::from_millis(50));
+    respond(&mut server_conn, "on time")?;
+
+    let path = recv_path.recv_timeout(Duration::from_secs(5))??;
+    assert_eq!(path, std::path::PathBuf::from("on time"));
+
+    let client = handle.join().unwrap();
+    client.stop()?;
+
+    Ok(())
+}
+
+#[test]
+fn malformed_response_test() -> anyhow::Result<()> {
+    let (client_transport, server_transport) = MemoryTransport::pair();
+    let mut conn = WitcherConnection::from_transport(client_transport, WitcherPort::Game)?;
+    conn.set_read_timeout(Duration::from_millis(500))?;
+    let client = Arc::new(WitcherClient::new(conn));
+    client.start()?;
+    let mut server_conn = WitcherConnection::from_transport(server_transport, WitcherPort::Game)?;
+
+    let request_root_path = || {
+        let client = client.clone();
+        let handle = std::thread::spawn(move || client.scripts_root_path());
+        // give the client time to register the response callback
+        std::thread::sleep(Duration::from_millis(100));
+        handle
+    };
+
+    // path is sent as an int instead of a string
+    let handle = request_root_path();
+    server_conn.send(WitcherPacketAssembler::new()
+        .string_utf8("ScriptCompiler")
+        .string_utf8("RootPathConfirm")
+        .int32(0)
+        .finish())?;
+    let err = handle.join().unwrap().unwrap_err();
+    assert!(matches!(err, ClientError::Protocol(ProtocolError::InvalidMessageBody(_))), "{:?}", err);
+
+    // the client keeps working afterwards
+    let handle = request_root_path();
+    server_conn.send(ScriptsRootPathResponse::assemble_packet(ScriptsRootPathResult { abs_path: "scripts".into() }))?;
+    assert_eq!(handle.join().unwrap()?.abs_path, std::path::PathBuf::from("scripts"));
+
+    client.stop()?;
+
+    Ok(())
+}
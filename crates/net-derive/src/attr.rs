@@ -0,0 +1,206 @@
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::{bracketed, parse::ParseStream, punctuated::Punctuated, Attribute, Lit, LitInt, Path, Token};
+
+
+/// Kind of data as it is sent over the wire
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WireKind {
+    Int8,
+    Int16,
+    Int32,
+    Uint32,
+    Int64,
+    StringUtf8,
+    StringUtf16
+}
+
+impl WireKind {
+    fn from_int_suffix(suffix: &str) -> Option<Self> {
+        match suffix {
+            "i8" => Some(Self::Int8),
+            "i16" => Some(Self::Int16),
+            "" | "i32" => Some(Self::Int32),
+            "u32" => Some(Self::Uint32),
+            "i64" => Some(Self::Int64),
+            _ => None
+        }
+    }
+
+    pub fn is_int(&self) -> bool {
+        !matches!(self, Self::StringUtf8 | Self::StringUtf16)
+    }
+
+    fn is_signed(&self) -> bool {
+        !matches!(self, Self::Uint32 | Self::StringUtf8 | Self::StringUtf16)
+    }
+
+    /// Name of the method in `WitcherPacketAssembler` and `WitcherPacketDisassembler` 
+    pub fn method(&self) -> Ident {
+        let name = match self {
+            Self::Int8 => "int8",
+            Self::Int16 => "int16",
+            Self::Int32 => "int32",
+            Self::Uint32 => "uint32",
+            Self::Int64 => "int64",
+            Self::StringUtf8 => "string_utf8",
+            Self::StringUtf16 => "string_utf16",
+        };
+
+        Ident::new(name, Span::call_site())
+    }
+
+    /// Name of the method in `WitcherPacketDisassembler` that checks for a constant
+    pub fn fixed_method(&self) -> Ident {
+        Ident::new(&format!("fixed_{}", self.method()), Span::call_site())
+    }
+}
+
+
+/// Constant data that is always present in the payload
+pub(crate) struct FixedValue {
+    pub kind: WireKind,
+    pub value: TokenStream
+}
+
+impl FixedValue {
+    fn parse(input: ParseStream, expected_kind: Option<WireKind>) -> syn::Result<Self> {
+        let neg: Option<Token![-]> = input.parse()?;
+        let lit: Lit = input.parse()?;
+
+        match lit {
+            Lit::Str(s) if neg.is_none() => {
+                let kind = expected_kind.unwrap_or(WireKind::StringUtf8);
+                if kind.is_int() {
+                    return Err(syn::Error::new(s.span(), "expected an integer literal"));
+                }
+
+                Ok(Self { 
+                    kind, 
+                    value: s.into_token_stream() 
+                })
+            },
+            Lit::Int(i) => {
+                let suffix_kind = WireKind::from_int_suffix(i.suffix())
+                    .ok_or_else(|| syn::Error::new(i.span(), "unsupported integer type, expected one of: i8, i16, i32, u32, i64"))?;
+
+                let kind = match expected_kind {
+                    Some(kind) if !kind.is_int() => {
+                        return Err(syn::Error::new(i.span(), "expected a string literal"));
+                    }
+                    Some(kind) if i.suffix().is_empty() || kind == suffix_kind => kind,
+                    Some(_) => {
+                        return Err(syn::Error::new(i.span(), "integer suffix does not match the attribute"));
+                    }
+                    None => suffix_kind
+                };
+
+                if neg.is_some() && !kind.is_signed() {
+                    return Err(syn::Error::new(i.span(), "unsigned value can't be negative"));
+                }
+
+                // suffix is dropped, the type is inferred from the method this value is passed to
+                let digits = LitInt::new(i.base10_digits(), i.span());
+                Ok(Self {
+                    kind,
+                    value: quote!(#neg #digits)
+                })
+            },
+            _ => Err(syn::Error::new(lit.span(), "expected a string or an integer literal"))
+        }
+    }
+}
+
+
+/// Attributes put on the struct itself
+#[derive(Default)]
+pub(crate) struct ContainerAttrs {
+    /// Constants that precede the fields of the struct, e.g. `#[witcher(id = ["Remote", 0x12345678])]`
+    pub id: Vec<FixedValue>
+}
+
+impl ContainerAttrs {
+    pub fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut container = Self::default();
+
+        for attr in attrs.iter().filter(|a| a.path().is_ident("witcher")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("id") {
+                    let value = meta.value()?;
+                    let content;
+                    bracketed!(content in value);
+                    let values = Punctuated::<FixedValue, Token![,]>::parse_terminated_with(&content, |input| FixedValue::parse(input, None))?;
+                    container.id.extend(values);
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported witcher attribute, expected `id`"))
+                }
+            })?;
+        }
+
+        Ok(container)
+    }
+}
+
+
+/// How a single field gets assembled and disassembled
+pub(crate) enum FieldEncoding {
+    /// Field's type implements `AssemblePayload` and `DisassemblePayload`
+    Payload,
+    /// Field is converted to and from a string of given encoding, e.g. `#[witcher(utf16)]`
+    String(WireKind),
+    /// Field always holds the same value, e.g. `#[witcher(fixed_i32 = 0x12345678)]`
+    Fixed(FixedValue),
+    /// Field is converted into a wrapper type that implements the traits instead, e.g. `#[witcher(with = Flagged)]`
+    With(Path)
+}
+
+impl FieldEncoding {
+    pub fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut encoding = None;
+
+        for attr in attrs.iter().filter(|a| a.path().is_ident("witcher")) {
+            attr.parse_nested_meta(|meta| {
+                if encoding.is_some() {
+                    return Err(meta.error("only one encoding attribute is allowed per field"));
+                }
+
+                let ident = meta.path.get_ident()
+                    .map(|i| i.to_string())
+                    .unwrap_or_default();
+
+                let fixed_kind = match ident.as_str() {
+                    "utf8" => {
+                        encoding = Some(Self::String(WireKind::StringUtf8));
+                        return Ok(());
+                    }
+                    "utf16" => {
+                        encoding = Some(Self::String(WireKind::StringUtf16));
+                        return Ok(());
+                    }
+                    "with" => {
+                        encoding = Some(Self::With(meta.value()?.call(Path::parse_mod_style)?));
+                        return Ok(());
+                    }
+                    "fixed_i8" => WireKind::Int8,
+                    "fixed_i16" => WireKind::Int16,
+                    "fixed_i32" => WireKind::Int32,
+                    "fixed_u32" => WireKind::Uint32,
+                    "fixed_i64" => WireKind::Int64,
+                    "fixed_utf8" => WireKind::StringUtf8,
+                    "fixed_utf16" => WireKind::StringUtf16,
+                    _ => {
+                        return Err(meta.error("unsupported witcher attribute, expected one of: utf8, utf16, with, fixed_i8, fixed_i16, fixed_i32, fixed_u32, fixed_i64, fixed_utf8, fixed_utf16"))
+                    }
+                };
+
+                let value = FixedValue::parse(meta.value()?, Some(fixed_kind))?;
+                encoding = Some(Self::Fixed(value));
+                Ok(())
+            })?;
+        }
+
+        Ok(encoding.unwrap_or(Self::Payload))
+    }
+}
+
@@ -0,0 +1,336 @@
+//! Derive macros for `AssemblePayload` and `DisassemblePayload` traits from `rw3d_net`.
+//! 
+//! Fields are assembled in the order of declaration.
+//! `DisassemblePayload` derive also implements `DisassemblePartial` using a generated `<Name>Partial` struct,
+//! which has the same fields, but wrapped in `Option`.
+//! By default every field is assembled using its own `AssemblePayload` and `DisassemblePayload` implementation.
+//! This can be changed with `#[witcher(...)]` attributes:
+//! 
+//! On the struct:
+//! - `id = ["Remote", 0x12345678, 1i8]` - constants put before all fields. 
+//!   String literals are sent as UTF8 strings, integers as int32 unless they have a suffix (i8, i16, i32, u32 or i64).
+//! 
+//! On a field:
+//! - `utf8`, `utf16` - field is sent as a string of given encoding. 
+//!   Its type has to be convertible to the string type and constructible from `String`.
+//! - `with = Wrapper` - field is converted into `Wrapper` (given without generic arguments) using `From` and that wrapper gets assembled instead.
+//!   When disassembling the wrapper is turned back into the field using its `into_inner` method.
+//! - `fixed_i8 = 1`, `fixed_i16`, `fixed_i32`, `fixed_u32`, `fixed_i64`, `fixed_utf8 = "list"`, `fixed_utf16` - 
+//!   the field is always sent with this value and disassembly fails if a different value is received.
+//! 
+//! ```ignore
+//! #[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+//! #[witcher(id = ["ScriptDebugger", "OpcodeBreakdownRequest"])]
+//! pub struct OpcodesId;
+//! ```
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use proc_macro2::Ident;
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Fields, Generics, Index, Type};
+
+mod attr;
+use attr::{ContainerAttrs, FieldEncoding};
+
+
+#[proc_macro_derive(AssemblePayload, attributes(witcher))]
+pub fn derive_assemble_payload(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_assemble(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(DisassemblePayload, attributes(witcher))]
+pub fn derive_disassemble_payload(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_disassemble(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+
+
+fn protocol_path() -> TokenStream2 {
+    quote!(::rw3d_net::protocol)
+}
+
+fn struct_fields(input: &DeriveInput) -> syn::Result<&Fields> {
+    match &input.data {
+        Data::Struct(data) => Ok(&data.fields),
+        _ => Err(syn::Error::new_spanned(&input.ident, "only structs are supported"))
+    }
+}
+
+fn add_trait_bounds(generics: &Generics, bound: TokenStream2) -> Generics {
+    let mut generics = generics.clone();
+    let type_params: Vec<_> = generics.type_params().map(|p| p.ident.clone()).collect();
+    let where_clause = generics.make_where_clause();
+    for ident in type_params {
+        where_clause.predicates.push(parse_quote!(#ident: #bound));
+    }
+    generics
+}
+
+
+fn expand_assemble(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let proto = protocol_path();
+    let container = ContainerAttrs::from_attrs(&input.attrs)?;
+    let fields = struct_fields(&input)?;
+
+    let mut steps = Vec::new();
+    for fixed in &container.id {
+        let method = fixed.kind.method();
+        let value = &fixed.value;
+        steps.push(quote!(let asm = asm.#method(#value);));
+    }
+
+    for (i, field) in fields.iter().enumerate() {
+        let access = match &field.ident {
+            Some(ident) => quote!(self.#ident),
+            None => {
+                let index = Index::from(i);
+                quote!(self.#index)
+            }
+        };
+
+        let step = match FieldEncoding::from_attrs(&field.attrs)? {
+            FieldEncoding::Payload => {
+                quote!(let asm = #proto::AssemblePayload::assemble_payload(#access, asm);)
+            }
+            FieldEncoding::String(kind) => {
+                let method = kind.method();
+                quote!(let asm = asm.#method(#access);)
+            }
+            FieldEncoding::Fixed(fixed) => {
+                let method = fixed.kind.method();
+                let value = &fixed.value;
+                quote!(let asm = asm.#method(#value);)
+            }
+            FieldEncoding::With(wrapper) => {
+                quote!(let asm = #proto::AssemblePayload::assemble_payload(#wrapper::from(#access), asm);)
+            }
+        };
+        steps.push(step);
+    }
+
+    let name = &input.ident;
+    let generics = add_trait_bounds(&input.generics, quote!(#proto::AssemblePayload));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #proto::AssemblePayload for #name #ty_generics #where_clause {
+            #[allow(overflowing_literals, unused_mut)]
+            fn assemble_payload(self, asm: #proto::WitcherPacketAssembler) -> #proto::WitcherPacketAssembler {
+                #(#steps)*
+                asm
+            }
+        }
+    })
+}
+
+
+fn expand_disassemble(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let proto = protocol_path();
+    let container = ContainerAttrs::from_attrs(&input.attrs)?;
+    let fields = struct_fields(&input)?;
+
+    let mut steps = Vec::new();
+    for fixed in &container.id {
+        let method = fixed.kind.fixed_method();
+        let value = &fixed.value;
+        steps.push(quote!(dasm.#method(#value)));
+    }
+
+    // each field is disassembled by an expression that evaluates to `ProtocolResult<FieldType>`
+    let mut vars = Vec::new();
+    let mut values = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let (var, field_name) = match &field.ident {
+            Some(ident) => (ident.clone(), ident.to_string()),
+            None => (format_ident!("__field{}", i), i.to_string())
+        };
+        let ty = &field.ty;
+
+        let value = match FieldEncoding::from_attrs(&field.attrs)? {
+            FieldEncoding::Payload => {
+                quote! {
+                    #proto::FieldContext::field(
+                        <#ty as #proto::DisassemblePayload>::disassemble_payload(dasm), #field_name
+                    )
+                }
+            }
+            FieldEncoding::String(kind) => {
+                let method = kind.method();
+                quote! {
+                    #proto::FieldContext::field(dasm.#method(), #field_name)
+                        .map(|s| <#ty as ::core::convert::From<::std::string::String>>::from(s.0))
+                }
+            }
+            FieldEncoding::Fixed(fixed) => {
+                let method = fixed.kind.fixed_method();
+                let value = &fixed.value;
+                let data = if !fixed.kind.is_int() {
+                    quote!(data.0)
+                } else {
+                    quote!(data)
+                };
+
+                quote! {
+                    #proto::FieldContext::field(dasm.#method(#value), #field_name)
+                        .map(|data| -> #ty { ::core::convert::Into::into(#data) })
+                }
+            }
+            FieldEncoding::With(wrapper) => {
+                quote! {
+                    #proto::FieldContext::field(
+                        #proto::DisassemblePayload::disassemble_payload(dasm).map(#wrapper::into_inner), #field_name
+                    )
+                }
+            }
+        };
+        vars.push(var);
+        values.push((ty, value));
+    }
+
+    let construct = match fields {
+        Fields::Named(_) => quote!(Self { #(#vars),* }),
+        Fields::Unnamed(_) => quote!(Self ( #(#vars),* )),
+        Fields::Unit => quote!(Self)
+    };
+
+    let name = &input.ident;
+    let generics = add_trait_bounds(&input.generics, quote!(#proto::DisassemblePayload));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let var_steps = vars.iter().zip(&values).map(|(var, (ty, value))| quote!(let #var: #ty = (#value)?;));
+    let disassemble = quote! {
+        impl #impl_generics #proto::DisassemblePayload for #name #ty_generics #where_clause {
+            #[allow(overflowing_literals, unused_variables)]
+            fn disassemble_payload(dasm: &mut #proto::WitcherPacketDisassembler) -> #proto::ProtocolResult<Self> {
+                #(#steps?;)*
+                #(#var_steps)*
+                Ok(#construct)
+            }
+        }
+    };
+
+    let partial = expand_partial(&input, fields, &vars, &values)?;
+
+    Ok(quote! {
+        #disassemble
+        #partial
+    })
+}
+
+/// Generates `<Name>Partial` struct with every field being an `Option` and implements `DisassemblePartial` with it.
+/// Structs without fields use `()` instead.
+fn expand_partial(input: &DeriveInput, fields: &Fields, vars: &[Ident], values: &[(&Type, TokenStream2)]) -> syn::Result<TokenStream2> {
+    let proto = protocol_path();
+    let container = ContainerAttrs::from_attrs(&input.attrs)?;
+
+    let name = &input.ident;
+    let vis = &input.vis;
+    let partial_name = format_ident!("{}Partial", name);
+    let generics = add_trait_bounds(&input.generics, quote!(#proto::DisassemblePayload));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // partial struct built from fields disassembled before the one at given index
+    let partial_with = |done: usize| -> TokenStream2 {
+        if fields.is_empty() {
+            return quote!(());
+        }
+
+        let values = vars.iter().enumerate().map(|(i, var)| {
+            if i < done { quote!(::core::option::Option::Some(#var)) } else { quote!(::core::option::Option::None) }
+        });
+        match fields {
+            Fields::Named(_) => quote!(#partial_name { #(#vars: #values),* }),
+            _ => quote!(#partial_name ( #(#values),* ))
+        }
+    };
+
+    let empty = partial_with(0);
+    let mut steps = Vec::new();
+    if !container.id.is_empty() {
+        steps.push(quote!(let start = dasm.clone();));
+    }
+    for fixed in &container.id {
+        let method = fixed.kind.fixed_method();
+        let value = &fixed.value;
+        steps.push(quote! {
+            if let Err(err) = dasm.#method(#value) {
+                let err = dasm.locate(err);
+                *dasm = start;
+                return Err((#empty, err));
+            }
+        });
+    }
+
+    for (i, (var, (ty, value))) in vars.iter().zip(values).enumerate() {
+        let partial = partial_with(i);
+        steps.push(quote! {
+            let start = dasm.clone();
+            let #var: #ty = match #value {
+                Ok(value) => value,
+                Err(err) => {
+                    let err = dasm.locate(err);
+                    *dasm = start;
+                    return Err((#partial, err));
+                }
+            };
+        });
+    }
+
+    let construct = match fields {
+        Fields::Named(_) => quote!(Self { #(#vars),* }),
+        Fields::Unnamed(_) => quote!(Self ( #(#vars),* )),
+        Fields::Unit => quote!(Self)
+    };
+
+    let (partial_ty, partial_def) = if fields.is_empty() {
+        (quote!(()), quote!())
+    } else {
+        let doc = format!("Fields of [`{}`] that were disassembled before an error, see `DisassemblePartial`", name);
+        let field_defs = fields.iter().map(|field| {
+            let field_vis = &field.vis;
+            let ty = &field.ty;
+            match &field.ident {
+                Some(ident) => quote!(#field_vis #ident: ::core::option::Option<#ty>),
+                None => quote!(#field_vis ::core::option::Option<#ty>)
+            }
+        });
+        let params = &input.generics.params;
+        let def_where_clause = &input.generics.where_clause;
+        let def = match fields {
+            Fields::Named(_) => quote! {
+                #[doc = #doc]
+                #[derive(Debug)]
+                #vis struct #partial_name <#params> #def_where_clause {
+                    #(#field_defs),*
+                }
+            },
+            _ => quote! {
+                #[doc = #doc]
+                #[derive(Debug)]
+                #vis struct #partial_name <#params> ( #(#field_defs),* ) #def_where_clause;
+            }
+        };
+        (quote!(#partial_name #ty_generics), def)
+    };
+
+    Ok(quote! {
+        #partial_def
+
+        impl #impl_generics #proto::DisassemblePartial for #name #ty_generics #where_clause {
+            type Partial = #partial_ty;
+
+            #[allow(overflowing_literals, unused_variables)]
+            fn disassemble_partial(dasm: &mut #proto::WitcherPacketDisassembler) -> ::core::result::Result<Self, (Self::Partial, #proto::ProtocolError)> {
+                #(#steps)*
+                Ok(#construct)
+            }
+        }
+    })
+}
@@ -0,0 +1,72 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rw3d_net::{messages::{notifications::*, requests::*, Message}, protocol::*};
+
+
+fn reload_log_frame() -> Vec<u8> {
+    let packet = ScriptsReloadProgress::assemble_packet(ScriptsReloadProgressParams::Log { 
+        message: r"Compiling C:\GOG\Witcher 3\content\content0\scripts\game\player\r4Player.ws".into() 
+    });
+
+    let mut bytes = Vec::new();
+    packet.encode_into(&mut bytes).unwrap();
+    bytes
+}
+
+fn config_vars_frame() -> Vec<u8> {
+    let packet = ConfigVarsResponse::assemble_packet(ConfigVarsResult {
+        vars: (0..300)
+            .map(|i| ConfigVarInfo {
+                section: "Visuals".into(),
+                name: format!("SomeVisualSetting{}", i),
+                value: "1.1".into(),
+                data_type: 3,
+                _unknown0: 0
+            })
+            .collect()
+    });
+
+    let mut bytes = Vec::new();
+    packet.encode_into(&mut bytes).unwrap();
+    bytes
+}
+
+
+/// Owned decoding, which is what the connection does for every received packet
+fn decode_owned(frame: &[u8]) -> usize {
+    let packet = WitcherPacket::decode_from(&mut &frame[..]).unwrap();
+    packet.encoded_size()
+}
+
+/// Touches every piece of data in the payload, but without allocating
+fn decode_borrowed(frame: &[u8]) -> usize {
+    let packet = WitcherPacketRef::new(frame).unwrap();
+    packet.iter()
+        .map(|data| match data.unwrap() {
+            WitcherPacketDataRef::StringUTF8(s) => s.to_str().unwrap().len(),
+            WitcherPacketDataRef::StringUTF16(s) => s.len(),
+            _ => 1
+        })
+        .sum()
+}
+
+fn packet_decoding(c: &mut Criterion) {
+    let frames = [
+        ("reload_log", reload_log_frame()),
+        ("config_vars", config_vars_frame())
+    ];
+
+    let mut group = c.benchmark_group("packet_decoding");
+    for (name, frame) in frames.iter() {
+        group.throughput(Throughput::Bytes(frame.len() as u64));
+        group.bench_with_input(BenchmarkId::new("owned", name), frame, |b, frame| {
+            b.iter(|| decode_owned(black_box(frame)))
+        });
+        group.bench_with_input(BenchmarkId::new("borrowed", name), frame, |b, frame| {
+            b.iter(|| decode_borrowed(black_box(frame)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, packet_decoding);
+criterion_main!(benches);
@@ -0,0 +1,111 @@
+use std::{net::{IpAddr, SocketAddr}, time::Duration};
+
+use bytes::BytesMut;
+use tokio::{io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt}, net::TcpStream};
+use tokio_util::codec::{Decoder, Encoder, Framed};
+
+use crate::protocol::*;
+use super::{ConnectionError, ConnectionResult, WitcherPort};
+
+
+/// Asynchronous counterpart of [`super::WitcherConnection`] based on tokio.
+///
+/// Works over any tokio byte stream, e.g. [`tokio::io::duplex`] for an in-memory connection.
+#[derive(Debug)]
+pub struct AsyncWitcherConnection<S = TcpStream> {
+    stream: S,
+    codec: WitcherPacketCodec,
+    read_buf: BytesMut,
+    write_buf: BytesMut,
+    pub port: WitcherPort
+}
+
+impl AsyncWitcherConnection<TcpStream> {
+    pub async fn connect(ip: IpAddr, port: WitcherPort) -> ConnectionResult<Self> {
+        let addr = SocketAddr::new(ip, port.as_number());
+        let stream = TcpStream::connect(addr).await.map_err(ConnectionError::Connect)?;
+
+        Ok(Self::from_stream(stream, port))
+    }
+
+    pub async fn connect_timeout(ip: IpAddr, port: WitcherPort, timeout: Duration) -> ConnectionResult<Self> {
+        let addr = SocketAddr::new(ip, port.as_number());
+        let stream = tokio::time::timeout(timeout, TcpStream::connect(addr)).await
+            .map_err(|_| ConnectionError::ConnectTimeout)?
+            .map_err(ConnectionError::Connect)?;
+
+        Ok(Self::from_stream(stream, port))
+    }
+}
+
+impl<S> AsyncWitcherConnection<S>
+where S: AsyncRead + AsyncWrite + Unpin {
+    const BUFFER_SIZE: usize = 1024;
+
+
+    pub fn from_stream(stream: S, port: WitcherPort) -> Self {
+        Self {
+            stream,
+            codec: WitcherPacketCodec::new(),
+            read_buf: BytesMut::with_capacity(Self::BUFFER_SIZE),
+            write_buf: BytesMut::with_capacity(Self::BUFFER_SIZE),
+            port
+        }
+    }
+
+    /// Turns the connection into a [`Framed`] stream and sink of packets.
+    /// Any data that has been received, but not yet decoded is carried over.
+    pub fn into_framed(self) -> Framed<S, WitcherPacketCodec> {
+        let mut framed = Framed::new(self.stream, self.codec);
+        framed.read_buffer_mut().extend_from_slice(&self.read_buf);
+        framed
+    }
+
+
+    /// Set upper bounds for packets accepted by [`Self::receive`].
+    #[inline]
+    pub fn set_decode_limits(&mut self, limits: DecodeLimits) {
+        self.codec.set_limits(limits);
+    }
+
+    #[inline]
+    pub fn get_decode_limits(&self) -> &DecodeLimits {
+        self.codec.limits()
+    }
+
+
+    pub async fn send(&mut self, packet: WitcherPacket) -> ConnectionResult<()> {
+        self.write_buf.clear();
+        self.codec.encode(packet, &mut self.write_buf)?;
+        // same as in the blocking version, everything is written at once
+        self.stream.write_all(&self.write_buf).await?;
+        Ok(())
+    }
+
+    /// Waits until a whole packet is received.
+    ///
+    /// This method is cancel safe. If it is used in `tokio::select!` and some other branch completes first,
+    /// any partially received packet data is kept for the next call.
+    pub async fn receive(&mut self) -> ConnectionResult<WitcherPacket> {
+        loop {
+            if let Some(packet) = self.codec.decode(&mut self.read_buf)? {
+                return Ok(packet);
+            }
+
+            let bytes_read = self.stream.read_buf(&mut self.read_buf).await?;
+            if bytes_read == 0 {
+                if self.read_buf.is_empty() {
+                    return Err(ConnectionError::Closed);
+                } else {
+                    return Err(ConnectionError::ClosedMidPacket);
+                }
+            }
+        }
+    }
+
+
+    pub async fn shutdown(&mut self) -> ConnectionResult<()> {
+        self.stream.shutdown().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,183 @@
+use std::{io::Write, net::{IpAddr, SocketAddr, TcpStream}, time::Duration};
+
+use crate::protocol::*;
+use super::{is_timeout_kind, ConnectionError, ConnectionResult, WitcherPort, WitcherTransport};
+
+
+#[derive(Debug)]
+pub struct WitcherConnection {
+    stream: Box<dyn WitcherTransport>,
+    framer: WitcherPacketFramer,
+    pub port: WitcherPort
+}
+
+impl WitcherConnection {
+    /// A read timeout is necessary to be able to shut down the connection
+    /// Without it it would block infinitely until it would receive data or the connection was severed
+    pub const DEFAULT_READ_TIMEOUT_MILLIS: u64 = 2000;
+
+
+    pub fn connect(ip: IpAddr, port: WitcherPort) -> ConnectionResult<Self> {
+        let addr = SocketAddr::new(ip, port.as_number());
+        let stream = TcpStream::connect(addr).map_err(ConnectionError::Connect)?;
+        
+        Self::from_transport(stream, port)
+    }
+
+    pub fn connect_timeout(ip: IpAddr, port: WitcherPort, timeout: Duration) -> ConnectionResult<Self> {
+        let addr = SocketAddr::new(ip, port.as_number());
+        let stream = TcpStream::connect_timeout(&addr, timeout).map_err(|err| {
+            if is_timeout_kind(err.kind()) {
+                ConnectionError::ConnectTimeout
+            } else {
+                ConnectionError::Connect(err)
+            }
+        })?;
+
+        Self::from_transport(stream, port)
+    }
+
+    /// Creates a connection over an already established transport, e.g. one end of [`super::MemoryTransport::pair`].
+    /// Sets the default read timeout on the transport.
+    pub fn from_transport<T>(transport: T, port: WitcherPort) -> ConnectionResult<Self>
+    where T: WitcherTransport + 'static {
+        transport.set_read_timeout(Some(std::time::Duration::from_millis(Self::DEFAULT_READ_TIMEOUT_MILLIS)))?;
+
+        Ok(Self {
+            stream: Box::new(transport),
+            framer: WitcherPacketFramer::new(),
+            port
+        })
+    }
+
+    /// Creates a new handle to the same connection.
+    /// Data that has been already received by this handle, but not yet returned as a packet, is not shared with the clone.
+    pub fn try_clone(&self) -> ConnectionResult<Self> {
+        let cloned_stream = self.stream.try_clone_boxed()?;
+
+        Ok(Self {
+            stream: cloned_stream,
+            framer: WitcherPacketFramer::with_limits(*self.framer.limits()),
+            port: self.port.clone()
+        })
+    }
+
+
+    pub fn set_read_timeout(&mut self, timeout: Duration) -> ConnectionResult<()> {
+        self.stream.set_read_timeout(Some(timeout))?;
+        Ok(())
+    }
+
+    pub fn get_read_timeout(&self) -> ConnectionResult<Duration> {
+        let timeout = self.stream.read_timeout()?.ok_or(ConnectionError::NoReadTimeout)?;
+        Ok(timeout)
+    }
+
+
+    /// Set upper bounds for packets accepted by [`Self::receive`] and its variants.
+    #[inline]
+    pub fn set_decode_limits(&mut self, limits: DecodeLimits) {
+        self.framer.set_limits(limits);
+    }
+
+    #[inline]
+    pub fn get_decode_limits(&self) -> &DecodeLimits {
+        self.framer.limits()
+    }
+
+
+    pub fn send(&mut self, packet: WitcherPacket) -> ConnectionResult<()> {
+        const BUFFER_SIZE: usize = 1024;
+        let mut buf = Vec::<u8>::with_capacity(BUFFER_SIZE);
+        packet.encode_into(&mut buf)?;
+        // writing everything at once to make network debugging easier
+        // these outgoing packets are never really big, so it doesn't make sense to chop them up so much
+        self.stream.write_all(&buf)?;
+        Ok(())
+    }
+
+    /// Blocks until a whole packet is received.
+    /// 
+    /// If the read timeout elapses first, an error is returned, but the part of the packet that was already received is kept
+    /// and the next call will pick up where this one left off.
+    pub fn receive(&mut self) -> ConnectionResult<WitcherPacket> {
+        loop {
+            if let Some(packet) = self.framer.next_packet()? {
+                return Ok(packet);
+            }
+
+            if self.framer.fill_from(&mut self.stream)? == 0 {
+                if self.framer.buffered_len() == 0 {
+                    return Err(ConnectionError::Closed);
+                } else {
+                    return Err(ConnectionError::ClosedMidPacket);
+                }
+            }
+        }
+    }
+
+    /// Same as [`Self::receive`], but returns `None` instead of an error if the read timeout elapses before the whole packet is received.
+    pub fn try_receive(&mut self) -> ConnectionResult<Option<WitcherPacket>> {
+        match self.receive() {
+            Ok(packet) => Ok(Some(packet)),
+            Err(err) if err.is_timeout() => Ok(None),
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Same as [`Self::try_receive`], but throws away any invalid data instead of returning an error.
+    /// Each chunk of thrown away data is reported to `on_skip`.
+    /// 
+    /// Errors are still returned if the connection itself fails.
+    pub fn try_receive_recovering<F>(&mut self, on_skip: F) -> ConnectionResult<Option<WitcherPacket>>
+    where F: FnMut(SkippedData) {
+        Ok(self.try_receive_frame_recovering(on_skip)?.map(|(packet, _)| packet))
+    }
+
+    /// Same as [`Self::try_receive_recovering`], but also gives back the bytes of the frame the packet was decoded from.
+    pub fn try_receive_frame_recovering<F>(&mut self, mut on_skip: F) -> ConnectionResult<Option<(WitcherPacket, Vec<u8>)>>
+    where F: FnMut(SkippedData) {
+        loop {
+            if let Some(received) = self.framer.next_frame_recovering(&mut on_skip) {
+                return Ok(Some(received));
+            }
+
+            match self.framer.fill_from(&mut self.stream) {
+                Ok(0) => return Err(ConnectionError::Closed),
+                Ok(_) => {},
+                Err(err) if is_timeout_kind(err.kind()) => {
+                    return Ok(None)
+                },
+                Err(err) => {
+                    Err(err)?
+                }
+            }
+        }
+    }
+
+    /// Checks whether there is any data waiting to be received.
+    pub fn peek(&self) -> ConnectionResult<bool> {
+        if self.framer.has_frame() {
+            return Ok(true);
+        }
+
+        let mut peek_buffer = [0u8; WitcherPacket::min_encoded_size()];
+        match self.stream.peek(&mut peek_buffer) {
+            Ok(peeked) => {
+                Ok(peeked + self.framer.buffered_len() >= peek_buffer.len())
+            }
+            Err(err) if is_timeout_kind(err.kind()) => {
+                Ok(false)
+            },
+            Err(err) => {
+                Err(err)?
+            }
+        }
+    }
+
+
+    pub fn shutdown(&self) -> ConnectionResult<()> {
+        self.stream.shutdown()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+use thiserror::Error;
+
+use crate::protocol::ProtocolError;
+
+
+/// Errors that can occur when communicating with the game over a connection.
+#[derive(Debug, Error)]
+pub enum ConnectionError {
+    #[error("Failed to connect to the game")]
+    Connect(#[source] std::io::Error),
+    #[error("Connection attempt timed out")]
+    ConnectTimeout,
+    #[error("Connection closed by the peer")]
+    Closed,
+    #[error("Connection closed by the peer in the middle of a packet")]
+    ClosedMidPacket,
+    #[error("No read timeout set")]
+    NoReadTimeout,
+
+    #[error("Connection failure")]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Protocol(#[from] ProtocolError)
+}
+
+impl ConnectionError {
+    /// Whether the error was caused by a read timeout elapsing.
+    /// Such errors are not fatal and the operation can be retried.
+    pub fn is_timeout(&self) -> bool {
+        match self {
+            ConnectionError::Io(err) => is_timeout_kind(err.kind()),
+            _ => false
+        }
+    }
+}
+
+pub type ConnectionResult<T> = Result<T, ConnectionError>;
+
+
+#[inline]
+pub(crate) fn is_timeout_kind(kind: std::io::ErrorKind) -> bool {
+    matches!(kind, std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock)
+}
@@ -0,0 +1,16 @@
+mod error;
+pub use error::*;
+
+mod port;
+pub use port::*;
+
+mod transport;
+pub use transport::*;
+
+mod blocking;
+pub use blocking::*;
+
+#[cfg(feature = "tokio")]
+mod asynchronous;
+#[cfg(feature = "tokio")]
+pub use asynchronous::*;
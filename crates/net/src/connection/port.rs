@@ -0,0 +1,22 @@
+/// Describes Witcher 3's connection port
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum WitcherPort {
+    /// Connect to the game running on its own
+    #[default]
+    Game,
+    /// Connect to the game running through REDKit
+    Editor,
+    /// Connect on a custom port
+    Custom(u16)
+}
+
+impl WitcherPort {
+    #[inline]
+    pub fn as_number(&self) -> u16 {
+        match self {
+            WitcherPort::Editor => 37000,
+            WitcherPort::Game => 37001,
+            WitcherPort::Custom(p) => *p,
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,239 @@
+use std::{collections::VecDeque, io::{Read, Write}, net::TcpStream, sync::{Arc, Condvar, Mutex}, time::{Duration, Instant}};
+
+
+/// Byte stream over which [`super::WitcherConnection`] exchanges packets with the game.
+///
+/// Mirrors the parts of [`TcpStream`]'s API that the connection relies on.
+/// Handles created with [`Self::try_clone_boxed`] refer to the same stream and share its read timeout.
+pub trait WitcherTransport: Read + Write + Send + std::fmt::Debug {
+    /// Creates a new independently owned handle to the same stream.
+    fn try_clone_boxed(&self) -> std::io::Result<Box<dyn WitcherTransport>>;
+
+    /// If the timeout elapses before any data arrives, reads fail with [`std::io::ErrorKind::WouldBlock`] or [`std::io::ErrorKind::TimedOut`].
+    /// `None` means reads can block indefinitely.
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()>;
+
+    fn read_timeout(&self) -> std::io::Result<Option<Duration>>;
+
+    /// Reads data without removing it from the stream. Waits for data the same way a read would.
+    fn peek(&self, buf: &mut [u8]) -> std::io::Result<usize>;
+
+    /// Closes both directions of the stream for all of its handles.
+    fn shutdown(&self) -> std::io::Result<()>;
+}
+
+impl WitcherTransport for TcpStream {
+    fn try_clone_boxed(&self) -> std::io::Result<Box<dyn WitcherTransport>> {
+        Ok(Box::new(self.try_clone()?))
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()> {
+        TcpStream::set_read_timeout(self, timeout)
+    }
+
+    fn read_timeout(&self) -> std::io::Result<Option<Duration>> {
+        TcpStream::read_timeout(self)
+    }
+
+    fn peek(&self, buf: &mut [u8]) -> std::io::Result<usize> {
+        TcpStream::peek(self, buf)
+    }
+
+    fn shutdown(&self) -> std::io::Result<()> {
+        TcpStream::shutdown(self, std::net::Shutdown::Both)
+    }
+}
+
+
+
+/// In-memory transport, one end of a duplex pipe created with [`MemoryTransport::pair`].
+///
+/// Behaves like a loopback TCP connection without touching the network, which is useful for tests.
+/// When every handle to one end is dropped or either end is shut down, the other end reads EOF after draining already sent data.
+#[derive(Debug, Clone)]
+pub struct MemoryTransport {
+    incoming: Arc<Pipe>,
+    outgoing: Arc<Pipe>,
+    read_timeout: Arc<Mutex<Option<Duration>>>,
+    _guard: Arc<CloseGuard>
+}
+
+impl MemoryTransport {
+    /// Creates two connected ends of a duplex pipe. Whatever is written to one of them can be read from the other.
+    pub fn pair() -> (Self, Self) {
+        let a_to_b = Arc::new(Pipe::default());
+        let b_to_a = Arc::new(Pipe::default());
+
+        let end = |incoming: &Arc<Pipe>, outgoing: &Arc<Pipe>| Self {
+            incoming: incoming.clone(),
+            outgoing: outgoing.clone(),
+            read_timeout: Arc::new(Mutex::new(None)),
+            _guard: Arc::new(CloseGuard([incoming.clone(), outgoing.clone()]))
+        };
+
+        (end(&b_to_a, &a_to_b), end(&a_to_b, &b_to_a))
+    }
+
+    fn read_timeout_value(&self) -> Option<Duration> {
+        *self.read_timeout.lock().unwrap()
+    }
+}
+
+impl Read for MemoryTransport {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.incoming.read(buf, self.read_timeout_value(), true)
+    }
+}
+
+impl Write for MemoryTransport {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.outgoing.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl WitcherTransport for MemoryTransport {
+    fn try_clone_boxed(&self) -> std::io::Result<Box<dyn WitcherTransport>> {
+        Ok(Box::new(self.clone()))
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()> {
+        if timeout == Some(Duration::ZERO) {
+            // same as with TcpStream
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "cannot set a 0 duration timeout"));
+        }
+
+        *self.read_timeout.lock().unwrap() = timeout;
+        Ok(())
+    }
+
+    fn read_timeout(&self) -> std::io::Result<Option<Duration>> {
+        Ok(self.read_timeout_value())
+    }
+
+    fn peek(&self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.incoming.read(buf, self.read_timeout_value(), false)
+    }
+
+    fn shutdown(&self) -> std::io::Result<()> {
+        self.incoming.close();
+        self.outgoing.close();
+        Ok(())
+    }
+}
+
+
+/// One direction of [`MemoryTransport`]
+#[derive(Debug, Default)]
+struct Pipe {
+    state: Mutex<PipeState>,
+    readable: Condvar
+}
+
+#[derive(Debug, Default)]
+struct PipeState {
+    buf: VecDeque<u8>,
+    closed: bool
+}
+
+impl Pipe {
+    fn read(&self, buf: &mut [u8], timeout: Option<Duration>, consume: bool) -> std::io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let deadline = timeout.map(|t| Instant::now() + t);
+        let mut state = self.state.lock().unwrap();
+        while state.buf.is_empty() && !state.closed {
+            state = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Err(std::io::ErrorKind::WouldBlock.into());
+                    }
+                    self.readable.wait_timeout(state, deadline - now).unwrap().0
+                }
+                None => self.readable.wait(state).unwrap()
+            };
+        }
+
+        let n = buf.len().min(state.buf.len());
+        for (dst, src) in buf.iter_mut().zip(state.buf.iter()) {
+            *dst = *src;
+        }
+        if consume {
+            state.buf.drain(..n);
+        }
+
+        Ok(n)
+    }
+
+    fn write(&self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut state = self.state.lock().unwrap();
+        if state.closed {
+            return Err(std::io::ErrorKind::BrokenPipe.into());
+        }
+
+        state.buf.extend(buf);
+        self.readable.notify_all();
+        Ok(buf.len())
+    }
+
+    fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.readable.notify_all();
+    }
+}
+
+/// Closes pipes of an end of [`MemoryTransport`] once all of its handles are gone
+#[derive(Debug)]
+struct CloseGuard([Arc<Pipe>; 2]);
+
+impl Drop for CloseGuard {
+    fn drop(&mut self) {
+        for pipe in &self.0 {
+            pipe.close();
+        }
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::time::Duration;
+
+    use super::*;
+
+
+    #[test]
+    fn memory_transport_test() {
+        let (mut a, mut b) = MemoryTransport::pair();
+        b.set_read_timeout(Some(Duration::from_millis(10))).unwrap();
+
+        let mut buf = [0u8; 4];
+        assert_eq!(b.read(&mut buf).unwrap_err().kind(), std::io::ErrorKind::WouldBlock);
+
+        a.write_all(&[1, 2, 3]).unwrap();
+        assert_eq!(b.peek(&mut buf).unwrap(), 3);
+        assert_eq!(b.read(&mut buf).unwrap(), 3);
+        assert_eq!(&buf[..3], &[1, 2, 3]);
+
+        // the other end stays open as long as any of its handles is alive
+        let a_clone = a.try_clone_boxed().unwrap();
+        a.write_all(&[4]).unwrap();
+        drop(a);
+        assert_eq!(b.read(&mut buf).unwrap(), 1);
+        assert_eq!(b.read(&mut buf).unwrap_err().kind(), std::io::ErrorKind::WouldBlock);
+
+        drop(a_clone);
+        assert_eq!(b.read(&mut buf).unwrap(), 0);
+        assert!(b.write_all(&[5]).is_err());
+    }
+}
@@ -1,3 +1,9 @@
+// lets code generated by rw3d_net_derive refer to this crate by name also from inside of it
+extern crate self as rw3d_net;
+
 pub mod protocol;
 pub mod connection;
 pub mod messages;
+pub mod wireshark;
+#[cfg(feature = "experimental")]
+pub mod profiler;
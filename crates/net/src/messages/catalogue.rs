@@ -0,0 +1,211 @@
+use std::borrow::Cow;
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::protocol::*;
+use super::{notifications, requests, short_type_name, Message, MessageDirection, MessageId, MessageMetadata, Notification, Request, WitcherNamespace};
+
+
+/// Part a message plays in the communication with the game
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MessageKind {
+    Notification,
+    Request {
+        /// Type name of the message sent back in response
+        response: Cow<'static, str>
+    },
+    Response {
+        /// Type name of the message this one is sent in response to
+        request: Cow<'static, str>
+    }
+}
+
+/// Entry of the [`message_catalogue`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MessageDescriptor {
+    pub metadata: MessageMetadata,
+    pub kind: MessageKind,
+    /// See [`super::Message::EXPERIMENTAL`]
+    pub experimental: bool
+}
+
+impl MessageDescriptor {
+    pub fn notification<N: Notification>() -> Self {
+        Self {
+            metadata: MessageMetadata::of::<N>(),
+            kind: MessageKind::Notification,
+            experimental: N::EXPERIMENTAL
+        }
+    }
+
+    pub fn request<R: Request>() -> Self {
+        Self {
+            metadata: MessageMetadata::of::<R>(),
+            kind: MessageKind::Request {
+                response: Cow::Borrowed(short_type_name::<R::Response>())
+            },
+            experimental: R::EXPERIMENTAL
+        }
+    }
+
+    /// Describes the response to given request
+    pub fn response_to<R: Request>() -> Self {
+        Self {
+            metadata: MessageMetadata::of::<R::Response>(),
+            kind: MessageKind::Response {
+                request: Cow::Borrowed(short_type_name::<R>())
+            },
+            experimental: R::Response::EXPERIMENTAL
+        }
+    }
+
+    /// See [`super::MessageIdRegistry::register_fallback_message`]
+    fn into_fallback(mut self) -> Self {
+        self.metadata.fallback = true;
+        self
+    }
+
+
+    #[inline]
+    pub fn type_name(&self) -> &str {
+        &self.metadata.type_name
+    }
+
+    #[inline]
+    pub fn direction(&self) -> MessageDirection {
+        self.metadata.direction
+    }
+
+    #[inline]
+    pub fn namespace(&self) -> Option<&WitcherNamespace> {
+        self.metadata.namespace.as_ref()
+    }
+
+    #[inline]
+    pub fn id(&self) -> &MessageId {
+        &self.metadata.id
+    }
+
+    /// The id the way it appears at the beginning of the payload of every packet of this message
+    pub fn encoded_id(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for data in self.metadata.id.as_slice() {
+            // writing into a vector can't fail
+            data.encode_into(&mut bytes).unwrap();
+        }
+        bytes
+    }
+}
+
+
+/// Lists every message implemented by the library.
+/// Experimental messages are listed only with the `experimental` feature.
+/// Server and client messages known to [`super::MessageIdRegistry`] are registered based on this list.
+pub fn message_catalogue() -> Vec<MessageDescriptor> {
+    use notifications::*;
+    use requests::*;
+
+    let catalogue = vec![
+        MessageDescriptor::notification::<ListenToNamespace>(),
+        MessageDescriptor::notification::<ReloadScripts>(),
+        // its id is just the namespace name, which also begins ids of other messages of that namespace
+        MessageDescriptor::notification::<ScriptsReloadProgress>().into_fallback(),
+
+        MessageDescriptor::request::<ScriptsRootPath>(),
+        MessageDescriptor::response_to::<ScriptsRootPath>(),
+        MessageDescriptor::request::<ExecuteCommand>(),
+        MessageDescriptor::response_to::<ExecuteCommand>(),
+        MessageDescriptor::request::<ScriptPackages>(),
+        MessageDescriptor::response_to::<ScriptPackages>(),
+        MessageDescriptor::request::<Opcodes>(),
+        MessageDescriptor::response_to::<Opcodes>(),
+        MessageDescriptor::request::<ConfigVars>(),
+        MessageDescriptor::response_to::<ConfigVars>(),
+    ];
+
+    #[cfg(feature = "experimental")]
+    let catalogue = catalogue.into_iter()
+        .chain(experimental_message_catalogue())
+        .collect();
+
+    catalogue
+}
+
+/// Messages that are only a guess, see [`super::Message::EXPERIMENTAL`]
+#[cfg(feature = "experimental")]
+fn experimental_message_catalogue() -> Vec<MessageDescriptor> {
+    use notifications::*;
+    use requests::*;
+
+    vec![
+        MessageDescriptor::notification::<BreakHit>(),
+        MessageDescriptor::notification::<DebugContinue>(),
+        MessageDescriptor::notification::<DebugStepInto>(),
+        MessageDescriptor::notification::<DebugStepOver>(),
+        MessageDescriptor::notification::<DebugStepOut>(),
+        MessageDescriptor::notification::<ProfilerStart>(),
+
+        MessageDescriptor::request::<AddBreakpoint>(),
+        MessageDescriptor::response_to::<AddBreakpoint>(),
+        MessageDescriptor::request::<RemoveBreakpoint>(),
+        MessageDescriptor::response_to::<RemoveBreakpoint>(),
+        MessageDescriptor::request::<ToggleBreakpoint>(),
+        MessageDescriptor::response_to::<ToggleBreakpoint>(),
+        MessageDescriptor::request::<FrameLocals>(),
+        MessageDescriptor::response_to::<FrameLocals>(),
+        MessageDescriptor::request::<ExpandProperty>(),
+        MessageDescriptor::response_to::<ExpandProperty>(),
+        MessageDescriptor::request::<SetConfigVar>(),
+        MessageDescriptor::response_to::<SetConfigVar>(),
+        MessageDescriptor::request::<ProfilerStop>(),
+        MessageDescriptor::response_to::<ProfilerStop>(),
+    ]
+}
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use crate::messages::{requests::*, *};
+
+
+    #[test]
+    fn message_catalogue_test() {
+        let catalogue = message_catalogue();
+
+        for (i, desc) in catalogue.iter().enumerate() {
+            assert!(catalogue[..i].iter().all(|d| d.type_name() != desc.type_name()), "{} is listed twice", desc.type_name());
+
+            match &desc.kind {
+                MessageKind::Request { response } => {
+                    let resp = catalogue.iter().find(|d| d.type_name() == response).unwrap();
+                    assert_eq!(resp.experimental, desc.experimental, "{} and its response", desc.type_name());
+                    assert_eq!(resp.kind, MessageKind::Response { request: desc.type_name().to_string().into() });
+                    assert_ne!(resp.direction(), desc.direction());
+                },
+                MessageKind::Response { request } => {
+                    assert!(catalogue.iter().any(|d| d.type_name() == request));
+                },
+                MessageKind::Notification => {}
+            }
+        }
+
+        let desc = catalogue.iter().find(|d| d.type_name() == "ScriptsRootPathResponse").unwrap();
+        assert!(!desc.experimental);
+        assert_eq!(desc.direction(), MessageDirection::ServerToClient);
+        assert_eq!(desc.namespace(), Some(&WitcherNamespace::ScriptCompiler));
+        assert_eq!(desc.kind, MessageKind::Response { request: "ScriptsRootPath".into() });
+        assert_eq!(desc.id(), &ScriptsRootPathResponse::assemble_id());
+
+        let mut payload = Vec::new();
+        for data in ScriptsRootPathResponse::assemble_packet(ScriptsRootPathResult { abs_path: "C:/".into() }).payload {
+            data.encode_into(&mut payload).unwrap();
+        }
+        assert!(payload.starts_with(&desc.encoded_id()));
+    }
+}
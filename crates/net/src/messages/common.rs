@@ -1,39 +1,150 @@
-use strum_macros::{AsRefStr, EnumString};
 #[cfg(feature = "serde")]
 use serde::{Serialize, Deserialize};
 
 use crate::protocol::*;
 
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, AsRefStr, EnumString)]
+/// Namespaces messages are grouped in.
+/// Names the game uses that aren't known to the library are kept as [`WitcherNamespace::Other`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum WitcherNamespace {
-    #[strum(serialize = "ScriptDebugger")]
     ScriptDebugger,
-    #[strum(serialize = "ScriptProfiler")]
     ScriptProfiler,
-    #[strum(serialize = "ScriptCompiler")]
     ScriptCompiler,
-    #[strum(serialize = "scripts")]
+    #[cfg_attr(feature = "serde", serde(alias = "scripts"))]
     Scripts,
-    #[strum(serialize = "Remote")]
     Remote,
-    #[strum(serialize = "Utility")]
     Utility,
-    #[strum(serialize = "Config")]
-    Config
+    Config,
+    /// Namespace not modelled by the library, e.g. one used only by REDkit or the next-gen version of the game
+    #[cfg_attr(feature = "serde", serde(untagged))]
+    Other(String)
 }
 
+impl WitcherNamespace {
+    /// All namespaces known to the library
+    pub const KNOWN: [WitcherNamespace; 7] = [
+        WitcherNamespace::ScriptDebugger,
+        WitcherNamespace::ScriptProfiler,
+        WitcherNamespace::ScriptCompiler,
+        WitcherNamespace::Scripts,
+        WitcherNamespace::Remote,
+        WitcherNamespace::Utility,
+        WitcherNamespace::Config,
+    ];
+
+    /// Name of the namespace as it is sent to and by the game
+    pub fn as_str(&self) -> &str {
+        match self {
+            WitcherNamespace::ScriptDebugger => "ScriptDebugger",
+            WitcherNamespace::ScriptProfiler => "ScriptProfiler",
+            WitcherNamespace::ScriptCompiler => "ScriptCompiler",
+            WitcherNamespace::Scripts => "scripts",
+            WitcherNamespace::Remote => "Remote",
+            WitcherNamespace::Utility => "Utility",
+            WitcherNamespace::Config => "Config",
+            WitcherNamespace::Other(s) => s,
+        }
+    }
+
+    #[inline]
+    pub fn is_known(&self) -> bool {
+        !matches!(self, WitcherNamespace::Other(_))
+    }
+}
+
+impl AsRef<str> for WitcherNamespace {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl std::fmt::Display for WitcherNamespace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for WitcherNamespace {
+    fn from(value: &str) -> Self {
+        WitcherNamespace::KNOWN.into_iter()
+            .find(|namesp| namesp.as_str() == value)
+            .unwrap_or_else(|| WitcherNamespace::Other(value.to_string()))
+    }
+}
+
+impl From<String> for WitcherNamespace {
+    fn from(value: String) -> Self {
+        match WitcherNamespace::from(value.as_str()) {
+            WitcherNamespace::Other(_) => WitcherNamespace::Other(value),
+            known => known
+        }
+    }
+}
+
+impl std::str::FromStr for WitcherNamespace {
+    type Err = std::convert::Infallible;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(WitcherNamespace::from(s))
+    }
+}
+
+impl From<WitcherNamespace> for String {
+    fn from(value: WitcherNamespace) -> Self {
+        match value {
+            WitcherNamespace::Other(s) => s,
+            known => known.as_str().to_string()
+        }
+    }
+}
+
+/// Which side of the connection sends a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MessageDirection {
+    ClientToServer,
+    ServerToClient
+}
+
+
 impl AssemblePayload for WitcherNamespace {
     fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
-        asm.string_utf8(self.as_ref())
+        asm.string_utf8(self.as_str())
     }
 }
 
 impl DisassemblePayload for WitcherNamespace {
-    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> anyhow::Result<Self> {
+    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> ProtocolResult<Self> {
         let s = dasm.string_utf8()?;
-        let ns = WitcherNamespace::try_from(s.as_str())?;
-        Ok(ns)
+        Ok(WitcherNamespace::from(s.0))
+    }
+}
+
+
+#[cfg(all(test, feature = "serde"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn namespace_serde_format_test() {
+        let namespaces = vec![
+            WitcherNamespace::ScriptDebugger,
+            WitcherNamespace::Scripts,
+            WitcherNamespace::Other("Gameplay".to_string()),
+        ];
+
+        let json = serde_json::to_string(&namespaces).unwrap();
+        assert_eq!(json, r#"["ScriptDebugger","Scripts","Gameplay"]"#);
+
+        let deserialized: Vec<WitcherNamespace> = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, namespaces);
+
+        // the name used on the wire is accepted too
+        let deserialized: WitcherNamespace = serde_json::from_str(r#""scripts""#).unwrap();
+        assert_eq!(deserialized, WitcherNamespace::Scripts);
     }
 }
@@ -0,0 +1,686 @@
+use std::{borrow::Cow, path::Path};
+
+use serde::{de::{MapAccess, Visitor}, ser::SerializeMap, Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+use crate::protocol::*;
+use super::{AmbiguousMessageId, LenientBody, MessageDirection, MessageId, MessageIdRegistry, MessageMetadata, WitcherNamespace};
+
+
+/// Describes a message whose format is not known at compile time.
+/// It can be loaded from a TOML or JSON file as a part of [`MessageSchemaSet`].
+///
+/// Its packets are assembled from and disassembled into a [`DynamicStruct`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageSchema {
+    pub name: String,
+    pub direction: MessageDirection,
+    #[serde(default)]
+    pub namespace: Option<WitcherNamespace>,
+    /// Name of the message sent back in response to this one, if it is a request
+    #[serde(default)]
+    pub response: Option<String>,
+    /// See [`MessageIdRegistry::register_fallback_message`]
+    #[serde(default)]
+    pub fallback: bool,
+    /// Data at the beginning of the payload that identifies the message
+    pub id: Vec<WitcherPacketData>,
+    #[serde(default)]
+    pub body: Vec<FieldSchema>
+}
+
+/// Field of a [`MessageSchema`] body.
+///
+/// In a schema file it is written as one of:
+/// - `{ name = "line", type = "Int32" }`
+/// - `{ name = "condition", optional = [ ...fields ] }`
+/// - `{ name = "locals", repeated = [ ...fields ] }`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FieldSchema {
+    /// A single piece of data
+    Value {
+        name: String,
+        #[serde(rename = "type")]
+        wire_type: WireType
+    },
+    /// Group of fields preceded by an int8 flag telling whether they are present
+    Optional {
+        name: String,
+        optional: Vec<FieldSchema>
+    },
+    /// Group of fields repeated as many times as the int32 count preceding them says
+    Repeated {
+        name: String,
+        repeated: Vec<FieldSchema>
+    }
+}
+
+impl FieldSchema {
+    pub fn name(&self) -> &str {
+        match self {
+            FieldSchema::Value { name, .. } => name,
+            FieldSchema::Optional { name, .. } => name,
+            FieldSchema::Repeated { name, .. } => name,
+        }
+    }
+}
+
+
+/// Value of a [`FieldSchema`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DynamicValue {
+    Data(WitcherPacketData),
+    Repeated(Vec<DynamicStruct>),
+    Optional(Option<DynamicStruct>)
+}
+
+impl From<WitcherPacketData> for DynamicValue {
+    fn from(value: WitcherPacketData) -> Self {
+        Self::Data(value)
+    }
+}
+
+impl DynamicValue {
+    pub fn as_data(&self) -> Option<&WitcherPacketData> {
+        match self {
+            DynamicValue::Data(d) => Some(d),
+            _ => None
+        }
+    }
+
+    pub fn as_optional(&self) -> Option<Option<&DynamicStruct>> {
+        match self {
+            DynamicValue::Optional(o) => Some(o.as_ref()),
+            _ => None
+        }
+    }
+
+    pub fn as_repeated(&self) -> Option<&[DynamicStruct]> {
+        match self {
+            DynamicValue::Repeated(r) => Some(r),
+            _ => None
+        }
+    }
+
+    fn kind_name(&self) -> String {
+        match self {
+            DynamicValue::Data(d) => d.wire_type().map(|t| format!("{:?}", t)).unwrap_or("unknown data".into()),
+            DynamicValue::Repeated(_) => "repeated group".into(),
+            DynamicValue::Optional(_) => "optional group".into(),
+        }
+    }
+}
+
+
+/// Named values of fields in the order they were given.
+/// With serde it is represented as a map.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DynamicStruct {
+    fields: Vec<(String, DynamicValue)>
+}
+
+impl DynamicStruct {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder-style version of [`Self::insert`]
+    #[inline]
+    pub fn with<V: Into<DynamicValue>>(mut self, name: &str, value: V) -> Self {
+        self.insert(name, value);
+        self
+    }
+
+    /// Sets the value of a field, replacing the previous one if there was any
+    pub fn insert<V: Into<DynamicValue>>(&mut self, name: &str, value: V) {
+        let value = value.into();
+        match self.fields.iter_mut().find(|(n, _)| n == name) {
+            Some((_, v)) => *v = value,
+            None => self.fields.push((name.to_string(), value))
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&DynamicValue> {
+        self.fields.iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v)
+    }
+
+    /// Shorthand for getting a field holding a single piece of data
+    pub fn get_data(&self, name: &str) -> Option<&WitcherPacketData> {
+        self.get(name).and_then(DynamicValue::as_data)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &DynamicValue)> {
+        self.fields.iter().map(|(n, v)| (n.as_str(), v))
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+}
+
+impl FromIterator<(String, DynamicValue)> for DynamicStruct {
+    fn from_iter<T: IntoIterator<Item = (String, DynamicValue)>>(iter: T) -> Self {
+        let mut s = Self::new();
+        for (name, value) in iter {
+            s.insert(&name, value);
+        }
+        s
+    }
+}
+
+impl Serialize for DynamicStruct {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.fields.len()))?;
+        for (name, value) in &self.fields {
+            map.serialize_entry(name, value)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for DynamicStruct {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DynamicStructVisitor;
+
+        impl<'de> Visitor<'de> for DynamicStructVisitor {
+            type Value = DynamicStruct;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a map of field values")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut s = DynamicStruct::new();
+                while let Some((name, value)) = map.next_entry::<String, DynamicValue>()? {
+                    s.insert(&name, value);
+                }
+                Ok(s)
+            }
+        }
+
+        deserializer.deserialize_map(DynamicStructVisitor)
+    }
+}
+
+
+
+impl MessageSchema {
+    /// Information with which the message can be registered in [`MessageIdRegistry`]
+    pub fn metadata(&self) -> MessageMetadata {
+        MessageMetadata {
+            id: MessageId::from(self.id.clone()),
+            type_name: Cow::Owned(self.name.clone()),
+            direction: self.direction,
+            namespace: self.namespace.clone(),
+            fallback: self.fallback
+        }
+    }
+
+    /// Fails if values don't match the schema. Values of fields not present in the schema are ignored.
+    pub fn assemble_packet(&self, body: &DynamicStruct) -> ProtocolResult<WitcherPacket> {
+        let mut asm = WitcherPacketAssembler::new();
+        for data in &self.id {
+            asm = asm.data(data.clone());
+        }
+
+        let asm = assemble_fields(asm, &self.body, body)
+            .map_err(|err| ProtocolError::InvalidMessageBody(Box::new(err)))?;
+
+        Ok(asm.finish())
+    }
+
+    pub fn disassemble_packet(&self, packet: WitcherPacket) -> ProtocolResult<DynamicStruct> {
+        let mut dasm = self.disassemble_id(packet)?;
+        let mut body = DynamicStruct::new();
+        disassemble_fields(&mut dasm, &self.body, &mut body)
+            .map_err(|err| ProtocolError::InvalidMessageBody(Box::new(err)))?;
+
+        Ok(body)
+    }
+
+    /// Same as [`Message::disassemble_packet_strict`](super::Message::disassemble_packet_strict)
+    pub fn disassemble_packet_strict(&self, packet: WitcherPacket) -> ProtocolResult<DynamicStruct> {
+        let mut dasm = self.disassemble_id(packet)?;
+        let mut body = DynamicStruct::new();
+        disassemble_fields(&mut dasm, &self.body, &mut body)
+            .map_err(|err| ProtocolError::InvalidMessageBody(Box::new(dasm.locate(err))))?;
+
+        if dasm.remaining() > 0 {
+            return Err(ProtocolError::InvalidMessageBody(Box::new(ProtocolError::TrailingData { 
+                index: dasm.position(), 
+                offset: dasm.offset(), 
+                count: dasm.remaining() 
+            })));
+        }
+
+        Ok(body)
+    }
+
+    /// Same as [`Message::disassemble_packet_lenient`](super::Message::disassemble_packet_lenient).
+    /// If the body can't be disassembled, `partial` holds the fields that come before the problematic one.
+    pub fn disassemble_packet_lenient(&self, packet: WitcherPacket) -> ProtocolResult<LenientBody<DynamicStruct>> {
+        let mut dasm = self.disassemble_id(packet)?;
+        let mut body = DynamicStruct::new();
+        let body_start = dasm.clone();
+        match disassemble_fields(&mut dasm, &self.body, &mut body) {
+            Ok(()) => Ok(LenientBody {
+                body: Some(body),
+                partial: None,
+                unconsumed: dasm.into_remaining(),
+                error: None
+            }),
+            Err(err) => {
+                let error = ProtocolError::InvalidMessageBody(Box::new(dasm.locate(err)));
+                // fields disassembled before the error are kept, 
+                // but the one that failed may have taken a part of its data, so skip again only over the complete ones
+                let mut dasm = body_start;
+                for field in self.body.iter().take(body.len()) {
+                    disassemble_field(&mut dasm, field)?;
+                }
+
+                Ok(LenientBody {
+                    body: None,
+                    partial: Some(body),
+                    unconsumed: dasm.into_remaining(),
+                    error: Some(error)
+                })
+            }
+        }
+    }
+
+    fn disassemble_id(&self, packet: WitcherPacket) -> ProtocolResult<WitcherPacketDisassembler> {
+        let mut dasm = WitcherPacketDisassembler::new(packet);
+
+        for expected in &self.id {
+            let data = dasm.data()
+                .map_err(|err| ProtocolError::InvalidMessageId(Box::new(err)))?;
+            if data != *expected {
+                return Err(ProtocolError::InvalidMessageId(Box::new(dasm.locate(ProtocolError::UnexpectedValue {
+                    expected: expected.to_string(),
+                    actual: data.to_string()
+                }))));
+            }
+        }
+
+        Ok(dasm)
+    }
+}
+
+fn assemble_fields(mut asm: WitcherPacketAssembler, fields: &[FieldSchema], values: &DynamicStruct) -> ProtocolResult<WitcherPacketAssembler> {
+    for field in fields {
+        asm = assemble_field(asm, field, values.get(field.name()))
+            .field(field.name().to_string())?;
+    }
+
+    Ok(asm)
+}
+
+fn assemble_field(asm: WitcherPacketAssembler, field: &FieldSchema, value: Option<&DynamicValue>) -> ProtocolResult<WitcherPacketAssembler> {
+    let unexpected = |expected: String| ProtocolError::UnexpectedValue {
+        expected,
+        actual: value.map(DynamicValue::kind_name).unwrap_or("nothing".into())
+    };
+
+    match (field, value) {
+        (FieldSchema::Value { wire_type, .. }, Some(DynamicValue::Data(data))) => {
+            if data.wire_type() != Some(*wire_type) {
+                return Err(ProtocolError::TypeMismatch {
+                    expected: wire_type.tag(),
+                    actual: data.tag()
+                });
+            }
+            Ok(asm.data(data.clone()))
+        },
+        (FieldSchema::Optional { optional, .. }, Some(DynamicValue::Optional(group))) => {
+            match group {
+                Some(group) => assemble_fields(asm.int8(1), optional, group),
+                None => Ok(asm.int8(0))
+            }
+        },
+        (FieldSchema::Repeated { repeated, .. }, Some(DynamicValue::Repeated(groups))) => {
+            let mut asm = asm.int32(groups.len() as i32);
+            for (i, group) in groups.iter().enumerate() {
+                asm = assemble_fields(asm, repeated, group).field(format!("[{}]", i))?;
+            }
+            Ok(asm)
+        },
+        (FieldSchema::Value { wire_type, .. }, _) => Err(unexpected(format!("{:?}", wire_type))),
+        (FieldSchema::Optional { .. }, _) => Err(unexpected("optional group".into())),
+        (FieldSchema::Repeated { .. }, _) => Err(unexpected("repeated group".into())),
+    }
+}
+
+fn disassemble_fields(dasm: &mut WitcherPacketDisassembler, fields: &[FieldSchema], values: &mut DynamicStruct) -> ProtocolResult<()> {
+    for field in fields {
+        let value = disassemble_field(dasm, field)
+            .field(field.name().to_string())?;
+        values.insert(field.name(), value);
+    }
+
+    Ok(())
+}
+
+fn disassemble_field(dasm: &mut WitcherPacketDisassembler, field: &FieldSchema) -> ProtocolResult<DynamicValue> {
+    match field {
+        FieldSchema::Value { wire_type, .. } => {
+            Ok(DynamicValue::Data(dasm.data_of_type(*wire_type)?))
+        },
+        FieldSchema::Optional { optional, .. } => {
+            // same as with Flagged
+            if bool::disassemble_payload(dasm)? {
+                let mut group = DynamicStruct::new();
+                disassemble_fields(dasm, optional, &mut group)?;
+                Ok(DynamicValue::Optional(Some(group)))
+            } else {
+                Ok(DynamicValue::Optional(None))
+            }
+        },
+        FieldSchema::Repeated { repeated, .. } => {
+            let count = dasm.count()?;
+            let groups = (0..count)
+                .map(|i| {
+                    let mut group = DynamicStruct::new();
+                    disassemble_fields(dasm, repeated, &mut group).field(format!("[{}]", i))?;
+                    Ok(group)
+                })
+                .collect::<ProtocolResult<Vec<_>>>()?;
+            Ok(DynamicValue::Repeated(groups))
+        }
+    }
+}
+
+
+
+/// Errors that can occur when loading message schemas
+#[derive(Debug, Error)]
+pub enum SchemaError {
+    #[error("Failed to read the schema file")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid TOML schema")]
+    Toml(#[from] toml::de::Error),
+    #[error("Invalid JSON schema")]
+    Json(#[from] serde_json::Error),
+    #[error("Unsupported schema file format, expected a .toml or .json file")]
+    UnsupportedFormat,
+    #[error("Invalid schema of message {message}: {reason}")]
+    Invalid {
+        message: String,
+        reason: String
+    }
+}
+
+/// Collection of message schemas as written in a schema file, e.g.
+///
+/// ```toml
+/// [[message]]
+/// name = "ScriptsRootPath"
+/// direction = "ClientToServer"
+/// namespace = "ScriptCompiler"
+/// response = "ScriptsRootPathResponse"
+/// id = [
+///     { type = "StringUTF8", value = "ScriptCompiler" },
+///     { type = "StringUTF8", value = "RootPath" }
+/// ]
+///
+/// [[message]]
+/// name = "ScriptsRootPathResponse"
+/// direction = "ServerToClient"
+/// namespace = "ScriptCompiler"
+/// id = [
+///     { type = "StringUTF8", value = "ScriptCompiler" },
+///     { type = "StringUTF8", value = "RootPathConfirm" }
+/// ]
+/// body = [
+///     { name = "abs_path", type = "StringUTF16" }
+/// ]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageSchemaSet {
+    #[serde(default, rename = "message")]
+    pub messages: Vec<MessageSchema>
+}
+
+impl MessageSchemaSet {
+    pub fn from_toml_str(s: &str) -> Result<Self, SchemaError> {
+        let set: Self = toml::from_str(s)?;
+        set.validate()?;
+        Ok(set)
+    }
+
+    pub fn from_json_str(s: &str) -> Result<Self, SchemaError> {
+        let set: Self = serde_json::from_str(s)?;
+        set.validate()?;
+        Ok(set)
+    }
+
+    /// Loads schemas from a file, picking the format based on its extension
+    pub fn load(path: &Path) -> Result<Self, SchemaError> {
+        let ext = path.extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase());
+
+        match ext.as_deref() {
+            Some("toml") => Self::from_toml_str(&std::fs::read_to_string(path)?),
+            Some("json") => Self::from_json_str(&std::fs::read_to_string(path)?),
+            _ => Err(SchemaError::UnsupportedFormat)
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&MessageSchema> {
+        self.messages.iter().find(|m| m.name == name)
+    }
+
+    /// Schema of the response to given request, if it has one
+    pub fn response_of(&self, request: &MessageSchema) -> Option<&MessageSchema> {
+        request.response.as_deref().and_then(|name| self.get(name))
+    }
+
+    pub fn register_all(&self, registry: &mut MessageIdRegistry) -> Result<(), AmbiguousMessageId> {
+        for schema in &self.messages {
+            registry.register(schema.metadata())?;
+        }
+        Ok(())
+    }
+
+    /// Checks whether schemas make sense together
+    pub fn validate(&self) -> Result<(), SchemaError> {
+        for (i, schema) in self.messages.iter().enumerate() {
+            let invalid = |reason: String| SchemaError::Invalid {
+                message: schema.name.clone(),
+                reason
+            };
+
+            if self.messages[..i].iter().any(|m| m.name == schema.name) {
+                return Err(invalid("name is not unique".into()));
+            }
+            if schema.id.is_empty() {
+                return Err(invalid("id can't be empty".into()));
+            }
+            if schema.id.iter().any(|d| d.wire_type().is_none()) {
+                return Err(invalid("id can't contain data of unknown type".into()));
+            }
+            if let Some(response) = &schema.response {
+                match self.get(response) {
+                    None => return Err(invalid(format!("response message {} is not defined", response))),
+                    Some(r) if r.direction == schema.direction => {
+                        return Err(invalid(format!("response message {} goes in the same direction", response)))
+                    },
+                    _ => {}
+                }
+            }
+            validate_field_names(&schema.body).map_err(invalid)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn validate_field_names(fields: &[FieldSchema]) -> Result<(), String> {
+    for (i, field) in fields.iter().enumerate() {
+        if fields[..i].iter().any(|f| f.name() == field.name()) {
+            return Err(format!("field name {} is not unique", field.name()));
+        }
+
+        match field {
+            FieldSchema::Optional { optional: group, .. } | FieldSchema::Repeated { repeated: group, .. } => {
+                validate_field_names(group)?;
+            },
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use crate::messages::{dynamic::*, requests::*, Message};
+
+
+    const SCHEMAS: &str = r#"
+        [[message]]
+        name = "Opcodes"
+        direction = "ClientToServer"
+        namespace = "ScriptDebugger"
+        id = [
+            { type = "StringUTF8", value = "ScriptDebugger" },
+            { type = "StringUTF8", value = "OpcodeBreakdownRequest" }
+        ]
+        body = [
+            { name = "func_name", type = "StringUTF16" },
+            { name = "class", optional = [
+                { name = "name", type = "StringUTF16" }
+            ] }
+        ]
+
+        [[message]]
+        name = "ScriptPackages"
+        direction = "ClientToServer"
+        namespace = "scripts"
+        response = "ScriptPackagesResponse"
+        id = [
+            { type = "StringUTF8", value = "scripts" },
+            { type = "StringUTF8", value = "pkgSync" }
+        ]
+
+        [[message]]
+        name = "ScriptPackagesResponse"
+        direction = "ServerToClient"
+        namespace = "scripts"
+        id = [
+            { type = "StringUTF8", value = "scripts" },
+            { type = "StringUTF8", value = "pkgSyncListing" }
+        ]
+        body = [
+            { name = "packages", repeated = [
+                { name = "package_name", type = "StringUTF16" },
+                { name = "abs_scripts_root_path", type = "StringUTF16" }
+            ] }
+        ]
+    "#;
+
+    #[test]
+    fn dynamic_schema_matches_static_test() {
+        let schemas = MessageSchemaSet::from_toml_str(SCHEMAS).unwrap();
+
+        let opcodes = schemas.get("Opcodes").unwrap();
+        let body = DynamicStruct::new()
+            .with("func_name", WitcherPacketData::new_string_utf16("additem"))
+            .with("class", DynamicValue::Optional(Some(
+                DynamicStruct::new().with("name", WitcherPacketData::new_string_utf16("CInventoryComponent"))
+            )));
+        assert_eq!(
+            opcodes.assemble_packet(&body).unwrap(),
+            Opcodes::assemble_packet(OpcodesParams {
+                func_name: "additem".into(),
+                class_name: Some("CInventoryComponent".into())
+            })
+        );
+
+        let packages = schemas.response_of(schemas.get("ScriptPackages").unwrap()).unwrap();
+        let packet = ScriptPackagesResponse::assemble_packet(ScriptPackagesResult {
+            packages: vec![
+                ScriptPackageInfo {
+                    package_name: "content0".into(),
+                    abs_scripts_root_path: "C:/content0/scripts".into()
+                },
+                ScriptPackageInfo {
+                    package_name: "modFoo".into(),
+                    abs_scripts_root_path: "C:/modFoo/scripts".into()
+                }
+            ]
+        });
+        let body = packages.disassemble_packet(packet.clone()).unwrap();
+        let listed = body.get("packages").unwrap().as_repeated().unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[1].get_data("package_name"), Some(&WitcherPacketData::new_string_utf16("modFoo")));
+        assert_eq!(packages.assemble_packet(&body).unwrap(), packet);
+
+        let mut registry = MessageIdRegistry::new();
+        schemas.register_all(&mut registry).unwrap();
+        assert_eq!(registry.probe_message(&packet).unwrap().type_name, "ScriptPackagesResponse");
+    }
+
+    #[test]
+    fn dynamic_schema_errors_test() {
+        let schemas = MessageSchemaSet::from_toml_str(SCHEMAS).unwrap();
+        let opcodes = schemas.get("Opcodes").unwrap();
+
+        let wrong_type = DynamicStruct::new()
+            .with("func_name", WitcherPacketData::new_int32(1))
+            .with("class", DynamicValue::Optional(None));
+        assert!(matches!(opcodes.assemble_packet(&wrong_type).unwrap_err().root_cause(), ProtocolError::TypeMismatch { .. }));
+
+        let missing = DynamicStruct::new()
+            .with("func_name", WitcherPacketData::new_string_utf16("additem"));
+        assert!(opcodes.assemble_packet(&missing).is_err());
+
+        let packet = ScriptsRootPath::assemble_packet(());
+        assert!(matches!(opcodes.disassemble_packet(packet), Err(ProtocolError::InvalidMessageId(_))));
+
+        let packet = WitcherPacketAssembler::new()
+            .string_utf8("ScriptDebugger")
+            .string_utf8("OpcodeBreakdownRequest")
+            .string_utf16("additem")
+            .int32(5)
+            .finish();
+        let err = opcodes.disassemble_packet_strict(packet.clone()).unwrap_err();
+        let ProtocolError::InvalidMessageBody(err) = err else { panic!() };
+        assert!(matches!(*err, ProtocolError::Position { index: 3, .. }));
+
+        let lenient = opcodes.disassemble_packet_lenient(packet).unwrap();
+        assert!(lenient.body.is_none());
+        let body = lenient.partial.unwrap();
+        assert_eq!(body.len(), 1);
+        assert_eq!(body.get_data("func_name"), Some(&WitcherPacketData::new_string_utf16("additem")));
+        assert_eq!(lenient.unconsumed, vec![WitcherPacketData::new_int32(5)]);
+        assert!(lenient.error.is_some());
+
+        let json = serde_json::to_string(&schemas).unwrap();
+        assert_eq!(MessageSchemaSet::from_json_str(&json).unwrap(), schemas);
+
+        let duplicate_field = r#"
+            [[message]]
+            name = "Foo"
+            direction = "ClientToServer"
+            id = [ { type = "StringUTF8", value = "Foo" } ]
+            body = [ { name = "a", type = "Int8" }, { name = "a", type = "Int16" } ]
+        "#;
+        assert!(matches!(MessageSchemaSet::from_toml_str(duplicate_field), Err(SchemaError::Invalid { .. })));
+    }
+}
@@ -1,18 +1,27 @@
-use std::collections::HashSet;
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
 
-use anyhow::Context;
 use crate::protocol::*;
 
 
 mod common;
 pub use common::*;
 
+mod registry;
+pub use registry::*;
+
+mod catalogue;
+pub use catalogue::*;
+
 pub mod requests;
 pub use requests::{Request, Response};
 
 pub mod notifications;
 pub use notifications::Notification;
 
+#[cfg(feature = "dynamic")]
+pub mod dynamic;
+
 
 /// An abstraction over data sent to and from the game
 pub trait Message: Sized {
@@ -21,6 +30,15 @@ pub trait Message: Sized {
     // variable content of the payload
     type Body: AssemblePayload + DisassemblePayload;
 
+    /// Side of the connection that sends the message
+    const DIRECTION: MessageDirection;
+    /// Namespace the message belongs to, `None` for messages that are not specific to any of them
+    const NAMESPACE: Option<WitcherNamespace>;
+    /// Whether the id and body layout are a guess that hasn't been confirmed against the game's traffic.
+    /// The game may not understand such a message at all and its definition can change in any release.
+    /// Experimental messages of this library are only available with the `experimental` feature.
+    const EXPERIMENTAL: bool = false;
+
 
     fn assemble_id() -> MessageId {
         let data = Self::Id::default()
@@ -40,81 +58,107 @@ pub trait Message: Sized {
         asm.finish()
     }
 
-    fn disassemble_packet(packet: WitcherPacket) -> anyhow::Result<Self::Body> {
+    fn disassemble_packet(packet: WitcherPacket) -> ProtocolResult<Self::Body> {
         let mut dasm = WitcherPacketDisassembler::new(packet);
 
-        Self::Id::disassemble_payload(&mut dasm).context("Invalid or unknown packet id")?;
-        let body = Self::Body::disassemble_payload(&mut dasm).context("Invalid or unknown packet body")?;
+        Self::Id::disassemble_payload(&mut dasm)
+            .map_err(|err| ProtocolError::InvalidMessageId(Box::new(err)))?;
+        let body = Self::Body::disassemble_payload(&mut dasm)
+            .map_err(|err| ProtocolError::InvalidMessageBody(Box::new(err)))?;
 
         Ok(body)
     }
-}
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct MessageId(Vec<WitcherPacketData>);
+    /// Same as [`Self::disassemble_packet`], but also fails if there is any data left after the body.
+    /// Errors say at which payload entry and byte offset the problem was found.
+    fn disassemble_packet_strict(packet: WitcherPacket) -> ProtocolResult<Self::Body> {
+        let mut dasm = WitcherPacketDisassembler::new(packet);
 
-impl std::borrow::Borrow<[WitcherPacketData]> for MessageId {
-    fn borrow(&self) -> &[WitcherPacketData] {
-        self.0.as_slice()
+        Self::Id::disassemble_payload(&mut dasm)
+            .map_err(|err| ProtocolError::InvalidMessageId(Box::new(dasm.locate(err))))?;
+        let body = Self::Body::disassemble_payload(&mut dasm)
+            .map_err(|err| ProtocolError::InvalidMessageBody(Box::new(dasm.locate(err))))?;
+
+        if dasm.remaining() > 0 {
+            return Err(ProtocolError::InvalidMessageBody(Box::new(ProtocolError::TrailingData { 
+                index: dasm.position(), 
+                offset: dasm.offset(), 
+                count: dasm.remaining() 
+            })));
+        }
+
+        Ok(body)
+    }
+
+    /// Same as [`Self::disassemble_packet`], but doesn't fail if only the body can't be disassembled.
+    /// Fields that come before the problematic one are returned in [`LenientBody::partial`]
+    /// and data that was not disassembled is returned alongside them, so it can be inspected or decoded by other means.
+    fn disassemble_packet_lenient(packet: WitcherPacket) -> ProtocolResult<LenientBody<Self::Body, <Self::Body as DisassemblePartial>::Partial>> 
+    where Self::Body: DisassemblePartial {
+        let mut dasm = WitcherPacketDisassembler::new(packet);
+
+        Self::Id::disassemble_payload(&mut dasm)
+            .map_err(|err| ProtocolError::InvalidMessageId(Box::new(dasm.locate(err))))?;
+
+        match Self::Body::disassemble_partial(&mut dasm) {
+            Ok(body) => Ok(LenientBody {
+                body: Some(body),
+                partial: None,
+                unconsumed: dasm.into_remaining(),
+                error: None
+            }),
+            Err((partial, err)) => Ok(LenientBody {
+                body: None,
+                partial: Some(partial),
+                unconsumed: dasm.into_remaining(),
+                error: Some(ProtocolError::InvalidMessageBody(Box::new(err)))
+            })
+        }
     }
 }
 
 
-/// Messages sent to and by the game do not have a fixed format that can identify them.
-/// That's why you need to know all message formats in advance and compare beginnings of their payloads to properly identify raw packets.
-/// One registry should be used only for messages passed in one direction, i.e. server to client or client to server.
+/// Result of [`Message::disassemble_packet_lenient`].
+/// `P` is the type holding a part of the body, for typed messages it's [`DisassemblePartial::Partial`].
 #[derive(Debug)]
-pub struct MessageIdRegistry {
-    ids: HashSet<MessageId>,
-    max_known_id_length: usize
+pub struct LenientBody<T, P = T> {
+    /// The body if it could be disassembled
+    pub body: Option<T>,
+    /// Fields that were disassembled before the error if the body could not be disassembled
+    pub partial: Option<P>,
+    /// Data left after the body or, if the body could not be disassembled, data starting from the field that failed
+    pub unconsumed: Vec<WitcherPacketData>,
+    /// Reason why the body could not be disassembled
+    pub error: Option<ProtocolError>
 }
 
-impl MessageIdRegistry {
-    pub fn new() -> Self {
-        Self {
-            ids: HashSet::new(),
-            max_known_id_length: 0
-        }
+impl<T, P> LenientBody<T, P> {
+    /// Whether the body was disassembled without any data left over
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        self.body.is_some() && self.unconsumed.is_empty()
     }
+}
 
-    pub fn register_message<T>(&mut self) -> MessageId
-    where T: Message {
-        let id = T::assemble_id();
-        let id_length = id.0.len();
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+pub struct MessageId(Vec<WitcherPacketData>);
 
-        self.ids.insert(id.clone());
-        self.max_known_id_length = std::cmp::max(self.max_known_id_length, id_length);
-        id
+impl MessageId {
+    #[inline]
+    pub fn as_slice(&self) -> &[WitcherPacketData] {
+        &self.0
     }
+}
 
-    pub fn probe_message_id(&self, packet: &WitcherPacket) -> Option<MessageId> {
-        let mut longest_detected = 0;
-        let mut i = 1;
-        while i <= packet.payload.len() && i <= self.max_known_id_length {
-            let payload_slice = &packet.payload[0..i];
-            if self.ids.contains(payload_slice) {
-                longest_detected = i;
-            }
-            i += 1;
-        }
-
-        if longest_detected > 0 {
-            let payload_slice = &packet.payload[0..longest_detected];
-            let id = MessageId(payload_slice.to_owned());
-            Some(id)
-        } else {
-            None
-        }
+impl From<Vec<WitcherPacketData>> for MessageId {
+    fn from(value: Vec<WitcherPacketData>) -> Self {
+        Self(value)
     }
+}
 
-
-    pub fn register_server_messages(&mut self) {
-        self.register_message::<notifications::ScriptsReloadProgress>();
-
-        self.register_message::<requests::ScriptsRootPathResponse>();
-        self.register_message::<requests::ExecuteCommandResponse>();
-        self.register_message::<requests::ScriptPackagesResponse>();
-        self.register_message::<requests::OpcodesResponse>();
-        self.register_message::<requests::ConfigVarsResponse>();
+impl std::borrow::Borrow<[WitcherPacketData]> for MessageId {
+    fn borrow(&self) -> &[WitcherPacketData] {
+        self.0.as_slice()
     }
-}
\ No newline at end of file
+}
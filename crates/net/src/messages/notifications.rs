@@ -1,11 +1,20 @@
 use std::path::PathBuf;
 
-use anyhow::{bail, Context};
 #[cfg(feature = "serde")]
 use serde::{Serialize, Deserialize};
 
 use crate::protocol::*;
-use super::{Message, WitcherNamespace};
+use super::{Message, MessageDirection, WitcherNamespace};
+
+// messages that are a guess, only available with the `experimental` feature (see `Message::EXPERIMENTAL`)
+#[cfg(feature = "experimental")]
+mod debugger;
+#[cfg(feature = "experimental")]
+pub use debugger::*;
+#[cfg(feature = "experimental")]
+mod profiler;
+#[cfg(feature = "experimental")]
+pub use profiler::*;
 
 
 pub trait Notification: Message { }
@@ -17,48 +26,25 @@ pub struct ListenToNamespace;
 impl Message for ListenToNamespace {
     type Id = ListenToNamespaceId;
     type Body = ListenToNamespaceParams;
+
+    const DIRECTION: MessageDirection = MessageDirection::ClientToServer;
+    const NAMESPACE: Option<WitcherNamespace> = None;
 }
 
 impl Notification for ListenToNamespace {}
 
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["BIND"])]
 pub struct ListenToNamespaceId;
 
-impl AssemblePayload for ListenToNamespaceId {
-    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
-        asm.string_utf8("BIND")
-    }
-}
-
-impl DisassemblePayload for ListenToNamespaceId {
-    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> anyhow::Result<Self> {
-        dasm.fixed_string_utf8("BIND")?;
-        Ok(Self)
-    }
-}
-
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, AssemblePayload, DisassemblePayload)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ListenToNamespaceParams {
     pub namesp: WitcherNamespace
 }
 
-impl AssemblePayload for ListenToNamespaceParams {
-    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
-        self.namesp.assemble_payload(asm)
-    }
-}
-
-impl DisassemblePayload for ListenToNamespaceParams {
-    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> anyhow::Result<Self> {
-        Ok(Self {
-            namesp: WitcherNamespace::disassemble_payload(dasm)?
-        })
-    }
-}
-
 
 
 
@@ -69,29 +55,18 @@ pub struct ReloadScripts;
 impl Message for ReloadScripts {
     type Id = ReloadScriptsId;
     type Body = ();
+
+    const DIRECTION: MessageDirection = MessageDirection::ClientToServer;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::Scripts);
 }
 
 impl Notification for ReloadScripts {}
 
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["scripts", "reload"])]
 pub struct ReloadScriptsId;
 
-impl AssemblePayload for ReloadScriptsId {
-    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
-        asm.string_utf8(WitcherNamespace::Scripts.as_ref())
-            .string_utf8("reload")
-    }
-}
-
-impl DisassemblePayload for ReloadScriptsId {
-    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> anyhow::Result<Self> {
-        dasm.fixed_string_utf8(WitcherNamespace::Scripts.as_ref())?;
-        dasm.fixed_string_utf8("reload")?;
-        Ok(Self)
-    }
-}
-
 
 
 
@@ -102,27 +77,18 @@ pub struct ScriptsReloadProgress;
 impl Message for ScriptsReloadProgress {
     type Id = ScriptsReloadProgressId;
     type Body = ScriptsReloadProgressParams;
+
+    const DIRECTION: MessageDirection = MessageDirection::ServerToClient;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::ScriptCompiler);
 }
 
 impl Notification for ScriptsReloadProgress {}
 
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["ScriptCompiler"])]
 pub struct ScriptsReloadProgressId;
 
-impl AssemblePayload for ScriptsReloadProgressId {
-    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
-        asm.string_utf8(WitcherNamespace::ScriptCompiler.as_ref())
-    }
-}
-
-impl DisassemblePayload for ScriptsReloadProgressId {
-    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> anyhow::Result<Self> {
-        dasm.fixed_string_utf8(WitcherNamespace::ScriptCompiler.as_ref())?;
-        Ok(Self)
-    }
-}
-
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -143,6 +109,11 @@ pub enum ScriptsReloadProgressParams {
     },
     Finished {
         success: bool
+    },
+    /// Kind of progress not known to the library, keeps all of its data
+    Unknown {
+        kind: String,
+        data: Vec<WitcherPacketData>
     }
 }
 
@@ -174,50 +145,65 @@ impl AssemblePayload for ScriptsReloadProgressParams {
                 asm.string_utf8("finished")
                     .int8(if success {0} else {1})
             }
+            ScriptsReloadProgressParams::Unknown { kind, data } => {
+                data.into_iter().fold(asm.string_utf8(kind), |asm, d| asm.data(d))
+            }
         }
     }
 }
 
 impl DisassemblePayload for ScriptsReloadProgressParams {
-    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> anyhow::Result<Self> {
+    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> ProtocolResult<Self> {
         let kind = dasm.string_utf8()?;
 
         match kind.as_str() {
             "started" => {
-                dasm.int8().context("started::unknown0")?;
-                dasm.int8().context("started::unknown1")?; // unknown
+                dasm.int8().field("started::unknown0")?;
+                dasm.int8().field("started::unknown1")?; // unknown
 
                 Ok(Self::Started)
             },
             "log" => {
-                let message = dasm.string_utf16().context("log::message")?.0;
+                let message = dasm.string_utf16().field("log::message")?.0;
 
                 Ok(Self::Log { message })
             },
             "warn" => {
-                let line = dasm.uint32().context("warn::line")?;
-                let local_script_path = PathBuf::from(dasm.string_utf16().context("warn::local_script_path")?.0);
-                let message = dasm.string_utf16().context("warn::message")?.0;
+                let line = dasm.uint32().field("warn::line")?;
+                let local_script_path = PathBuf::from(dasm.string_utf16().field("warn::local_script_path")?.0);
+                let message = dasm.string_utf16().field("warn::message")?.0;
 
                 Ok(Self::Warn { line, local_script_path, message })
             },
             "error" => {
-                let line = dasm.uint32().context("error::line")?;
-                let local_script_path = PathBuf::from(dasm.string_utf16().context("error::local_script_path")?.0);
-                let message = dasm.string_utf16().context("error::message")?.0;
+                let line = dasm.uint32().field("error::line")?;
+                let local_script_path = PathBuf::from(dasm.string_utf16().field("error::local_script_path")?.0);
+                let message = dasm.string_utf16().field("error::message")?.0;
 
                 Ok(Self::Error { line, local_script_path, message })
             },
             "finished" => {
-                let success = dasm.int8().context("finished::return_code")? == 0;
+                let success = dasm.int8().field("finished::return_code")? == 0;
 
                 Ok(Self::Finished { success })
             },
-            _ => bail!("Unknown script reload progress kind")
+            _ => {
+                let data = std::iter::from_fn(|| dasm.data().ok()).collect();
+
+                Ok(Self::Unknown { kind: kind.0, data })
+            }
         }
     }
 }
 
+impl DisassemblePartial for ScriptsReloadProgressParams {
+    type Partial = ();
+
+    fn disassemble_partial(dasm: &mut WitcherPacketDisassembler) -> Result<Self, (Self::Partial, ProtocolError)> {
+        disassemble_whole(dasm)
+    }
+}
+
 
 
 
@@ -264,9 +250,29 @@ mod test {
             assert_eq!(packet1, packet2);
             assert_eq!(param1, param2);
         }
+        {
+            // namespace not known to the library
+            let param1 = ListenToNamespaceParams {
+                namesp: WitcherNamespace::from("RedKit")
+            };
+            assert_eq!(param1.namesp, WitcherNamespace::Other("RedKit".into()));
+            let packet1 = ListenToNamespace::assemble_packet(param1.clone());
+    
+            let mut bytes = VecDeque::new();
+            packet1.encode_into(&mut bytes).unwrap();
+    
+            let packet2 = WitcherPacket::decode_from(&mut bytes).unwrap();
+            let param2 = ListenToNamespace::disassemble_packet(packet2.clone()).unwrap();
+    
+            assert_eq!(packet1, packet2);
+            assert_eq!(param1, param2);
+            assert_eq!(packet2.payload[1], WitcherPacketData::new_string_utf8("RedKit"));
+        }
     }
 
     #[test]
+    // same shape as the tests of messages with a body, even though it's just ()
+    #[allow(clippy::let_unit_value, clippy::unit_arg, clippy::unit_cmp, clippy::clone_on_copy)]
     fn reload_scripts_notif_encode_test() {
         let param1 = ();
         let packet1 = ReloadScripts::assemble_packet(param1.clone());
@@ -355,8 +361,24 @@ mod test {
             let packet2 = WitcherPacket::decode_from(&mut bytes).unwrap();
             let param2 = ScriptsReloadProgress::disassemble_packet(packet2.clone()).unwrap();
 
+            assert_eq!(packet1, packet2);
+            assert_eq!(param1, param2);
+        }
+        {
+            let param1 = ScriptsReloadProgressParams::Unknown {
+                kind: "cancelled".into(),
+                data: vec![WitcherPacketData::new_int32(3), WitcherPacketData::new_string_utf16("by user")]
+            };
+            let packet1 = ScriptsReloadProgress::assemble_packet(param1.clone());
+
+            let mut bytes = VecDeque::new();
+            packet1.encode_into(&mut bytes).unwrap();
+
+            let packet2 = WitcherPacket::decode_from(&mut bytes).unwrap();
+            let param2 = ScriptsReloadProgress::disassemble_packet_strict(packet2.clone()).unwrap();
+
             assert_eq!(packet1, packet2);
             assert_eq!(param1, param2);
         }
     }
-}
\ No newline at end of file
+}
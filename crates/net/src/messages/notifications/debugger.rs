@@ -0,0 +1,234 @@
+use std::path::PathBuf;
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::protocol::*;
+use crate::messages::{Message, MessageDirection, WitcherNamespace};
+use super::Notification;
+
+
+#[derive(Debug)]
+pub struct BreakHit;
+
+impl Message for BreakHit {
+    type Id = BreakHitId;
+    type Body = BreakHitParams;
+
+    const DIRECTION: MessageDirection = MessageDirection::ServerToClient;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::ScriptDebugger);
+    const EXPERIMENTAL: bool = true;
+}
+
+impl Notification for BreakHit {}
+
+
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["ScriptDebugger", "BreakpointHit"])]
+pub struct BreakHitId;
+
+
+/// Sent when a script thread gets paused, either on a breakpoint or after a step
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, AssemblePayload, DisassemblePayload)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BreakHitParams {
+    pub thread_id: u32,
+    /// Frames of the paused thread starting with the innermost one
+    pub callstack: Vec<CallstackFrame>
+}
+
+/// Layout of a frame on the wire is as unconfirmed as the [`BreakHit`] message itself
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CallstackFrame {
+    pub func_name: String,
+    /// `None` for global functions
+    pub class_name: Option<String>,
+    pub local_script_path: PathBuf,
+    pub line: u32
+}
+
+impl AssemblePayload for CallstackFrame {
+    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
+        (
+            StringUtf16::from(self.func_name),
+            Flagged(self.class_name.map(StringUtf16::from)),
+            self.local_script_path,
+            self.line
+        ).assemble_payload(asm)
+    }
+}
+
+impl DisassemblePayload for CallstackFrame {
+    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> ProtocolResult<Self> {
+        let func_name = dasm.string_utf16().field("func_name")?.0;
+        let class_name = Flagged::<StringUtf16>::disassemble_payload(dasm).field("class_name")?;
+        let local_script_path = PathBuf::disassemble_payload(dasm).field("local_script_path")?;
+        let line = dasm.uint32().field("line")?;
+
+        Ok(Self {
+            func_name,
+            class_name: class_name.into_inner().map(|s| s.0),
+            local_script_path,
+            line
+        })
+    }
+}
+
+
+
+
+
+/// Resumes execution of paused scripts until the next breakpoint
+#[derive(Debug)]
+pub struct DebugContinue;
+
+impl Message for DebugContinue {
+    type Id = DebugContinueId;
+    type Body = ();
+
+    const DIRECTION: MessageDirection = MessageDirection::ClientToServer;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::ScriptDebugger);
+    const EXPERIMENTAL: bool = true;
+}
+
+impl Notification for DebugContinue {}
+
+
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["ScriptDebugger", "DebugContinue"])]
+pub struct DebugContinueId;
+
+
+
+
+
+/// Executes the current line and pauses in the function called by it, if there is any
+#[derive(Debug)]
+pub struct DebugStepInto;
+
+impl Message for DebugStepInto {
+    type Id = DebugStepIntoId;
+    type Body = ();
+
+    const DIRECTION: MessageDirection = MessageDirection::ClientToServer;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::ScriptDebugger);
+    const EXPERIMENTAL: bool = true;
+}
+
+impl Notification for DebugStepInto {}
+
+
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["ScriptDebugger", "DebugStepInto"])]
+pub struct DebugStepIntoId;
+
+
+
+
+
+/// Executes the current line and pauses on the next one in the same function
+#[derive(Debug)]
+pub struct DebugStepOver;
+
+impl Message for DebugStepOver {
+    type Id = DebugStepOverId;
+    type Body = ();
+
+    const DIRECTION: MessageDirection = MessageDirection::ClientToServer;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::ScriptDebugger);
+    const EXPERIMENTAL: bool = true;
+}
+
+impl Notification for DebugStepOver {}
+
+
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["ScriptDebugger", "DebugStepOver"])]
+pub struct DebugStepOverId;
+
+
+
+
+
+/// Executes the rest of the current function and pauses in the one that called it
+#[derive(Debug)]
+pub struct DebugStepOut;
+
+impl Message for DebugStepOut {
+    type Id = DebugStepOutId;
+    type Body = ();
+
+    const DIRECTION: MessageDirection = MessageDirection::ClientToServer;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::ScriptDebugger);
+    const EXPERIMENTAL: bool = true;
+}
+
+impl Notification for DebugStepOut {}
+
+
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["ScriptDebugger", "DebugStepOut"])]
+pub struct DebugStepOutId;
+
+
+
+#[cfg(test)]
+mod test {
+    use std::collections::VecDeque;
+
+    use super::*;
+
+
+    #[test]
+    fn break_hit_notif_encode_test() {
+        let param1 = BreakHitParams {
+            thread_id: 3,
+            callstack: vec![
+                CallstackFrame {
+                    func_name: "OnSpawned".into(),
+                    class_name: Some("W3PlayerWitcher".into()),
+                    local_script_path: r"game\player\playerWitcher.ws".into(),
+                    line: 1364
+                },
+                CallstackFrame {
+                    func_name: "spawnt".into(),
+                    class_name: None,
+                    local_script_path: r"game\commands.ws".into(),
+                    line: 12
+                }
+            ]
+        };
+        let packet1 = BreakHit::assemble_packet(param1.clone());
+
+        let mut bytes = VecDeque::new();
+        packet1.encode_into(&mut bytes).unwrap();
+
+        let packet2 = WitcherPacket::decode_from(&mut bytes).unwrap();
+        let param2 = BreakHit::disassemble_packet_strict(packet2.clone()).unwrap();
+
+        assert_eq!(packet1, packet2);
+        assert_eq!(param1, param2);
+    }
+
+    #[test]
+    fn debug_step_notif_encode_test() {
+        let packets = [
+            DebugContinue::assemble_packet(()),
+            DebugStepInto::assemble_packet(()),
+            DebugStepOver::assemble_packet(()),
+            DebugStepOut::assemble_packet(()),
+        ];
+
+        for packet1 in packets.clone() {
+            let mut bytes = VecDeque::new();
+            packet1.encode_into(&mut bytes).unwrap();
+
+            let packet2 = WitcherPacket::decode_from(&mut bytes).unwrap();
+            assert_eq!(packet1, packet2);
+        }
+
+        assert!(DebugStepOver::disassemble_packet_strict(packets[2].clone()).is_ok());
+        assert!(DebugStepOver::disassemble_packet(packets[1].clone()).is_err());
+    }
+}
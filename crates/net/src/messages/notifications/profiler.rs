@@ -0,0 +1,24 @@
+use crate::protocol::*;
+use crate::messages::{Message, MessageDirection, WitcherNamespace};
+use super::Notification;
+
+
+/// Starts gathering timings of script functions, which are sent back in response to [`crate::messages::requests::ProfilerStop`]
+#[derive(Debug)]
+pub struct ProfilerStart;
+
+impl Message for ProfilerStart {
+    type Id = ProfilerStartId;
+    type Body = ();
+
+    const DIRECTION: MessageDirection = MessageDirection::ClientToServer;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::ScriptProfiler);
+    const EXPERIMENTAL: bool = true;
+}
+
+impl Notification for ProfilerStart {}
+
+
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["ScriptProfiler", "Start"])]
+pub struct ProfilerStartId;
@@ -0,0 +1,279 @@
+use std::borrow::Cow;
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+use thiserror::Error;
+
+use crate::protocol::*;
+use super::{message_catalogue, Message, MessageDirection, MessageId, WitcherNamespace};
+
+
+/// Information about a message known to [`MessageIdRegistry`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MessageMetadata {
+    pub id: MessageId,
+    /// Name of the type implementing the message
+    pub type_name: Cow<'static, str>,
+    pub direction: MessageDirection,
+    pub namespace: Option<WitcherNamespace>,
+    /// Whether the id is allowed to be a prefix of ids of other messages going in the same direction.
+    /// See [`MessageIdRegistry::register_fallback_message`].
+    pub fallback: bool
+}
+
+impl MessageMetadata {
+    pub fn of<T: Message>() -> Self {
+        Self {
+            id: T::assemble_id(),
+            type_name: Cow::Borrowed(short_type_name::<T>()),
+            direction: T::DIRECTION,
+            namespace: T::NAMESPACE,
+            fallback: false
+        }
+    }
+}
+
+/// Name of the type without its module path
+pub(crate) fn short_type_name<T>() -> &'static str {
+    let type_name = std::any::type_name::<T>();
+    type_name.rsplit("::").next().unwrap_or(type_name)
+}
+
+
+/// Registering a message would make it impossible to tell it apart from an already registered one.
+#[derive(Debug, Error)]
+#[error("Id of message {type_name} is ambiguous with the id of already registered {registered_type_name}")]
+pub struct AmbiguousMessageId {
+    pub type_name: Cow<'static, str>,
+    pub registered_type_name: Cow<'static, str>
+}
+
+
+/// Messages sent to and by the game do not have a fixed format that can identify them.
+/// That's why you need to know all message formats in advance and compare beginnings of their payloads to properly identify raw packets.
+///
+/// Ids are stored in a prefix trie, so identifying a packet takes a single pass over the beginning of its payload.
+/// Messages going in the same direction can't share an id or have an id that is a prefix of another one's,
+/// unless the shorter one is explicitly registered as a fallback.
+#[derive(Debug, Default)]
+pub struct MessageIdRegistry {
+    root: TrieNode
+}
+
+#[derive(Debug, Default)]
+struct TrieNode {
+    // messages going in different directions can have the same id
+    messages: Vec<MessageMetadata>,
+    children: Vec<(WitcherPacketData, TrieNode)>
+}
+
+impl TrieNode {
+    fn child(&self, data: &WitcherPacketData) -> Option<&TrieNode> {
+        self.children.iter()
+            .find(|(d, _)| d == data)
+            .map(|(_, node)| node)
+    }
+
+    fn child_or_insert(&mut self, data: &WitcherPacketData) -> &mut TrieNode {
+        let pos = match self.children.iter().position(|(d, _)| d == data) {
+            Some(pos) => pos,
+            None => {
+                self.children.push((data.clone(), TrieNode::default()));
+                self.children.len() - 1
+            }
+        };
+
+        &mut self.children[pos].1
+    }
+
+    fn message_in(&self, direction: MessageDirection) -> Option<&MessageMetadata> {
+        self.messages.iter().find(|m| m.direction == direction)
+    }
+
+    fn find_descendant_message_in(&self, direction: MessageDirection) -> Option<&MessageMetadata> {
+        self.children.iter()
+            .find_map(|(_, child)| child.message_in(direction).or_else(|| child.find_descendant_message_in(direction)))
+    }
+}
+
+impl MessageIdRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the message and returns its id.
+    /// Registering the same message again does nothing.
+    pub fn register_message<T>(&mut self) -> Result<MessageId, AmbiguousMessageId>
+    where T: Message {
+        self.register(MessageMetadata::of::<T>())
+    }
+
+    /// Same as [`Self::register_message`], but the id of this message can be a prefix of ids of other messages going in the same direction.
+    /// It's then matched only if none of the longer ids match.
+    pub fn register_fallback_message<T>(&mut self) -> Result<MessageId, AmbiguousMessageId>
+    where T: Message {
+        self.register(MessageMetadata {
+            fallback: true,
+            ..MessageMetadata::of::<T>()
+        })
+    }
+
+    /// Registers a message described by given metadata and returns its id.
+    pub fn register(&mut self, meta: MessageMetadata) -> Result<MessageId, AmbiguousMessageId> {
+        let ambiguous = |registered: &MessageMetadata| AmbiguousMessageId {
+            type_name: meta.type_name.clone(),
+            registered_type_name: registered.type_name.clone()
+        };
+
+        let mut node = &mut self.root;
+        for data in meta.id.0.iter() {
+            if let Some(registered) = node.message_in(meta.direction) {
+                if !registered.fallback {
+                    return Err(ambiguous(registered));
+                }
+            }
+
+            node = node.child_or_insert(data);
+        }
+
+        if let Some(registered) = node.message_in(meta.direction) {
+            if registered.type_name == meta.type_name {
+                return Ok(registered.id.clone());
+            } else {
+                return Err(ambiguous(registered));
+            }
+        }
+
+        if !meta.fallback {
+            if let Some(registered) = node.find_descendant_message_in(meta.direction) {
+                return Err(ambiguous(registered));
+            }
+        }
+
+        let id = meta.id.clone();
+        node.messages.push(meta);
+        Ok(id)
+    }
+
+    /// Identifies the message the packet holds, returning information given when it was registered.
+    /// If the registry holds messages going in both directions and the id is shared between them, the one registered first is returned.
+    pub fn probe_message(&self, packet: &WitcherPacket) -> Option<&MessageMetadata> {
+        self.probe_message_with(packet, |node| node.messages.first())
+    }
+
+    /// Same as [`Self::probe_message`], but only considers messages going in given direction.
+    pub fn probe_message_in(&self, packet: &WitcherPacket, direction: MessageDirection) -> Option<&MessageMetadata> {
+        self.probe_message_with(packet, |node| node.message_in(direction))
+    }
+
+    /// Annotated hexdump of a received frame which also names the message if the packet decoded from it is recognised.
+    pub fn hexdump<'a>(&'a self, packet: &WitcherPacket, frame: WitcherPacketRef<'a>) -> PacketHexdump<'a> {
+        let hexdump = frame.hexdump();
+        match self.probe_message(packet) {
+            Some(meta) => hexdump.with_message_name(meta.type_name.as_ref()),
+            None => hexdump
+        }
+    }
+
+    /// Same as [`Self::probe_message`], but returns only an owned copy of the message id.
+    pub fn probe_message_id(&self, packet: &WitcherPacket) -> Option<MessageId> {
+        self.probe_message(packet).map(|meta| meta.id.clone())
+    }
+
+    fn probe_message_with<'a, F>(&'a self, packet: &WitcherPacket, node_message: F) -> Option<&'a MessageMetadata>
+    where F: Fn(&'a TrieNode) -> Option<&'a MessageMetadata> {
+        let mut node = &self.root;
+        let mut longest_detected = None;
+        for data in packet.payload.iter() {
+            match node.child(data) {
+                Some(child) => node = child,
+                None => break
+            }
+
+            if let Some(meta) = node_message(node) {
+                longest_detected = Some(meta);
+            }
+        }
+
+        longest_detected
+    }
+
+
+    /// Registers all messages that can be sent by the server
+    pub fn register_server_messages(&mut self) -> Result<(), AmbiguousMessageId> {
+        self.register_catalogue_messages(MessageDirection::ServerToClient)
+    }
+
+    /// Registers all messages that can be sent by the client
+    pub fn register_client_messages(&mut self) -> Result<(), AmbiguousMessageId> {
+        self.register_catalogue_messages(MessageDirection::ClientToServer)
+    }
+
+    fn register_catalogue_messages(&mut self, direction: MessageDirection) -> Result<(), AmbiguousMessageId> {
+        for desc in message_catalogue() {
+            if desc.metadata.direction == direction {
+                self.register(desc.metadata)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use crate::messages::{notifications::*, requests::*, *};
+
+
+    #[test]
+    fn registry_probe_test() {
+        let mut registry = MessageIdRegistry::new();
+        registry.register_server_messages().unwrap();
+        registry.register_client_messages().unwrap();
+
+        let packet = ScriptsRootPathResponse::assemble_packet(ScriptsRootPathResult {
+            abs_path: "C:/scripts".into()
+        });
+        let meta = registry.probe_message(&packet).unwrap();
+        assert_eq!(meta.id, ScriptsRootPathResponse::assemble_id());
+        assert_eq!(meta.type_name, "ScriptsRootPathResponse");
+        assert_eq!(meta.direction, MessageDirection::ServerToClient);
+        assert_eq!(meta.namespace, Some(WitcherNamespace::ScriptCompiler));
+
+        // falls back to the shorter id if the longer one doesn't match
+        let packet = ScriptsReloadProgress::assemble_packet(ScriptsReloadProgressParams::Started);
+        assert_eq!(registry.probe_message(&packet).unwrap().type_name, "ScriptsReloadProgress");
+
+        let packet = ScriptsRootPath::assemble_packet(());
+        assert_eq!(registry.probe_message_in(&packet, MessageDirection::ClientToServer).unwrap().type_name, "ScriptsRootPath");
+        assert_eq!(registry.probe_message_in(&packet, MessageDirection::ServerToClient).unwrap().type_name, "ScriptsReloadProgress");
+
+        let packet = WitcherPacketAssembler::new().string_utf8("Unknown").finish();
+        assert!(registry.probe_message(&packet).is_none());
+    }
+
+    #[test]
+    fn registry_ambiguity_test() {
+        let mut registry = MessageIdRegistry::new();
+        registry.register_message::<ScriptsRootPathResponse>().unwrap();
+        // registering again is fine
+        registry.register_message::<ScriptsRootPathResponse>().unwrap();
+
+        let err = registry.register_message::<ScriptsReloadProgress>().unwrap_err();
+        assert_eq!(err.type_name, "ScriptsReloadProgress");
+        assert_eq!(err.registered_type_name, "ScriptsRootPathResponse");
+        registry.register_fallback_message::<ScriptsReloadProgress>().unwrap();
+
+        // same id prefix, but the other direction
+        registry.register_message::<ScriptsRootPath>().unwrap();
+
+        let mut registry = MessageIdRegistry::new();
+        registry.register_message::<ScriptsReloadProgress>().unwrap();
+        assert!(registry.register_message::<ScriptsRootPathResponse>().is_err());
+    }
+}
@@ -1,11 +1,28 @@
 use std::path::PathBuf;
 
-use anyhow::Context;
 #[cfg(feature = "serde")]
 use serde::{Serialize, Deserialize};
 
 use crate::protocol::*;
-use super::{Message, WitcherNamespace};
+use super::{Message, MessageDirection, WitcherNamespace};
+
+// messages that are a guess, only available with the `experimental` feature (see `Message::EXPERIMENTAL`)
+#[cfg(feature = "experimental")]
+mod breakpoints;
+#[cfg(feature = "experimental")]
+pub use breakpoints::*;
+#[cfg(feature = "experimental")]
+mod frame_locals;
+#[cfg(feature = "experimental")]
+pub use frame_locals::*;
+#[cfg(feature = "experimental")]
+mod profiler;
+#[cfg(feature = "experimental")]
+pub use profiler::*;
+#[cfg(feature = "experimental")]
+mod set_config_var;
+#[cfg(feature = "experimental")]
+pub use set_config_var::*;
 
 
 pub trait Request: Message {
@@ -21,6 +38,9 @@ pub struct ScriptsRootPath;
 impl Message for ScriptsRootPath {
     type Id = ScriptsRootPathId;
     type Body = ();
+
+    const DIRECTION: MessageDirection = MessageDirection::ClientToServer;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::ScriptCompiler);
 }
 
 impl Request for ScriptsRootPath {
@@ -28,24 +48,10 @@ impl Request for ScriptsRootPath {
 }
 
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["ScriptCompiler", "RootPath"])]
 pub struct ScriptsRootPathId;
 
-impl AssemblePayload for ScriptsRootPathId {
-    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
-        asm.string_utf8(WitcherNamespace::ScriptCompiler.as_ref())
-            .string_utf8("RootPath")
-    }
-}
-
-impl DisassemblePayload for ScriptsRootPathId {
-    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> anyhow::Result<Self> {
-        dasm.fixed_string_utf8(WitcherNamespace::ScriptCompiler.as_ref())?;
-        dasm.fixed_string_utf8("RootPath")?;
-        Ok(Self)
-    }
-}
-
 
 
 #[derive(Debug)]
@@ -54,52 +60,25 @@ pub struct ScriptsRootPathResponse;
 impl Message for ScriptsRootPathResponse {
     type Id = ScriptsRootPathResponseId;
     type Body = ScriptsRootPathResult;
+
+    const DIRECTION: MessageDirection = MessageDirection::ServerToClient;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::ScriptCompiler);
 }
 
 impl Response for ScriptsRootPathResponse {}
 
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["ScriptCompiler", "RootPathConfirm"])]
 pub struct ScriptsRootPathResponseId;
 
-impl AssemblePayload for ScriptsRootPathResponseId {
-    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
-        asm.string_utf8(WitcherNamespace::ScriptCompiler.as_ref())
-            .string_utf8("RootPathConfirm")
-    }
-}
-
-impl DisassemblePayload for ScriptsRootPathResponseId {
-    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> anyhow::Result<Self> {
-        dasm.fixed_string_utf8(WitcherNamespace::ScriptCompiler.as_ref())?;
-        dasm.fixed_string_utf8("RootPathConfirm")?;
-        Ok(Self)
-    }
-}
-
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, AssemblePayload, DisassemblePayload)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ScriptsRootPathResult {
     pub abs_path: PathBuf
 }
 
-impl AssemblePayload for ScriptsRootPathResult {
-    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
-        asm.string_utf16(self.abs_path.to_string_lossy())
-    }
-}
-
-impl DisassemblePayload for ScriptsRootPathResult {
-    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> anyhow::Result<Self> {
-        let abs_path = PathBuf::from(dasm.string_utf16().context("abs_path")?.0);
-
-        Ok(Self {
-            abs_path
-        })
-    }
-}
-
 
 
 
@@ -111,6 +90,9 @@ pub struct ExecuteCommand;
 impl Message for ExecuteCommand {
     type Id = ExecuteCommandId;
     type Body = ExecuteCommandParams;
+
+    const DIRECTION: MessageDirection = MessageDirection::ClientToServer;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::Remote);
 }
 
 impl Request for ExecuteCommand {
@@ -118,51 +100,17 @@ impl Request for ExecuteCommand {
 }
 
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["Remote", 0x12345678, 0x81160008])]
 pub struct ExecuteCommandId;
 
-impl AssemblePayload for ExecuteCommandId {
-    #[allow(overflowing_literals)]
-    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
-        asm.string_utf8(WitcherNamespace::Remote.as_ref())
-            .int32(0x12345678) // magic number #1
-            .int32(0x81160008) // magic number #2
-    }
-}
-
-impl DisassemblePayload for ExecuteCommandId {
-    #[allow(overflowing_literals)]
-    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> anyhow::Result<Self> {
-        dasm.fixed_string_utf8(WitcherNamespace::Remote.as_ref())?;
-        dasm.fixed_int32(0x12345678)?;
-        dasm.fixed_int32(0x81160008)?;
-        Ok(Self)
-    }
-}
-
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, AssemblePayload, DisassemblePayload)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ExecuteCommandParams {
     pub cmd: String
 }
 
-impl AssemblePayload for ExecuteCommandParams {
-    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
-        asm.string_utf8(self.cmd)
-    }
-}
-
-impl DisassemblePayload for ExecuteCommandParams {
-    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> anyhow::Result<Self> {
-        let cmd = dasm.string_utf8().context("command")?.0;
-
-        Ok(Self {
-            cmd
-        })
-    }
-}
-
 
 
 #[derive(Debug)]
@@ -171,31 +119,18 @@ pub struct ExecuteCommandResponse;
 impl Message for ExecuteCommandResponse {
     type Id = ExecuteCommandResponseId;
     type Body = ExecuteCommandResult;
+
+    const DIRECTION: MessageDirection = MessageDirection::ServerToClient;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::Remote);
 }
 
 impl Response for ExecuteCommandResponse {}
 
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = [0x12345678, 0x81160008])]
 pub struct ExecuteCommandResponseId;
 
-impl AssemblePayload for ExecuteCommandResponseId {
-    #[allow(overflowing_literals)]
-    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
-        asm.int32(0x12345678)
-            .int32(0x81160008)
-    }
-}
-
-impl DisassemblePayload for ExecuteCommandResponseId {
-    #[allow(overflowing_literals)]
-    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> anyhow::Result<Self> {
-        dasm.fixed_int32(0x12345678)?;
-        dasm.fixed_int32(0x81160008)?;
-        Ok(Self)
-    }
-}
-
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -232,8 +167,8 @@ impl AssemblePayload for ExecuteCommandResult {
 }
 
 impl DisassemblePayload for ExecuteCommandResult {
-    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> anyhow::Result<Self> {
-        let text = dasm.string_utf8().context("exec output")?.0;
+    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> ProtocolResult<Self> {
+        let text = dasm.string_utf8().field("exec output")?.0;
 
         match text.as_str() {
             Self::FAIL_OUTPUT => {
@@ -257,6 +192,14 @@ impl DisassemblePayload for ExecuteCommandResult {
     }
 }
 
+impl DisassemblePartial for ExecuteCommandResult {
+    type Partial = ();
+
+    fn disassemble_partial(dasm: &mut WitcherPacketDisassembler) -> Result<Self, (Self::Partial, ProtocolError)> {
+        disassemble_whole(dasm)
+    }
+}
+
 
 
 
@@ -269,6 +212,9 @@ pub struct ScriptPackages;
 impl Message for ScriptPackages {
     type Id = ScriptPackagesId;
     type Body = ();
+
+    const DIRECTION: MessageDirection = MessageDirection::ClientToServer;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::Scripts);
 }
 
 impl Request for ScriptPackages {
@@ -276,24 +222,10 @@ impl Request for ScriptPackages {
 }
 
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["scripts", "pkgSync"])]
 pub struct ScriptPackagesId;
 
-impl AssemblePayload for ScriptPackagesId {
-    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
-        asm.string_utf8(WitcherNamespace::Scripts.as_ref())
-            .string_utf8("pkgSync")
-    }
-}
-
-impl DisassemblePayload for ScriptPackagesId {
-    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> anyhow::Result<Self> {
-        dasm.fixed_string_utf8(WitcherNamespace::Scripts.as_ref())?;
-        dasm.fixed_string_utf8("pkgSync")?;
-        Ok(Self)
-    }
-}
-
 
 
 #[derive(Debug)]
@@ -302,77 +234,33 @@ pub struct ScriptPackagesResponse;
 impl Message for ScriptPackagesResponse {
     type Id = ScriptPackagesResponseId;
     type Body = ScriptPackagesResult;
+
+    const DIRECTION: MessageDirection = MessageDirection::ServerToClient;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::Scripts);
 }
 
 impl Response for ScriptPackagesResponse {}
 
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["scripts", "pkgSyncListing"])]
 pub struct ScriptPackagesResponseId;
 
-impl AssemblePayload for ScriptPackagesResponseId {
-    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
-        asm.string_utf8(WitcherNamespace::Scripts.as_ref())
-            .string_utf8("pkgSyncListing")
-    }
-}
 
-impl DisassemblePayload for ScriptPackagesResponseId {
-    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> anyhow::Result<Self> {
-        dasm.fixed_string_utf8(WitcherNamespace::Scripts.as_ref())?;
-        dasm.fixed_string_utf8("pkgSyncListing")?;
-        Ok(Self)
-    }
-}
-
-
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, AssemblePayload, DisassemblePayload)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ScriptPackagesResult {
     pub packages: Vec<ScriptPackageInfo>
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, AssemblePayload, DisassemblePayload)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ScriptPackageInfo {
+    #[witcher(utf16)]
     pub package_name: String,
     pub abs_scripts_root_path: PathBuf
 }
 
-impl AssemblePayload for ScriptPackagesResult {
-    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
-        let mut asm = asm.int32(self.packages.len() as i32);
-
-        for p in self.packages {
-            asm = asm.string_utf16(p.package_name)
-                .string_utf16(p.abs_scripts_root_path.to_string_lossy())
-        }
-
-        asm
-    }
-}
-
-impl DisassemblePayload for ScriptPackagesResult {
-    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> anyhow::Result<Self> {
-        let package_count = dasm.int32().context("package_count")?;
-
-        let mut packages = Vec::with_capacity(package_count as usize);
-        for _ in 0..package_count {
-            let package_name = dasm.string_utf16().context("package_name")?.0;
-            let abs_package_path = PathBuf::from(dasm.string_utf16().context("abs_package_path")?.0);
-
-            packages.push(ScriptPackageInfo {
-                package_name,
-                abs_scripts_root_path: abs_package_path
-            });
-        }
-
-        Ok(Self {
-            packages
-        })
-    }
-}
-
 
 
 
@@ -383,6 +271,9 @@ pub struct Opcodes;
 impl Message for Opcodes {
     type Id = OpcodesId;
     type Body = OpcodesParams;
+
+    const DIRECTION: MessageDirection = MessageDirection::ClientToServer;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::ScriptDebugger);
 }
 
 impl Request for Opcodes {
@@ -390,24 +281,10 @@ impl Request for Opcodes {
 }
 
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["ScriptDebugger", "OpcodeBreakdownRequest"])]
 pub struct OpcodesId;
 
-impl AssemblePayload for OpcodesId {
-    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
-        asm.string_utf8(WitcherNamespace::ScriptDebugger.as_ref())
-            .string_utf8("OpcodeBreakdownRequest")
-    }
-}
-
-impl DisassemblePayload for OpcodesId {
-    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> anyhow::Result<Self> {
-        dasm.fixed_string_utf8(WitcherNamespace::ScriptDebugger.as_ref())?;
-        dasm.fixed_string_utf8("OpcodeBreakdownRequest")?;
-        Ok(Self)
-    }
-}
-
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -418,36 +295,33 @@ pub struct OpcodesParams {
 
 impl AssemblePayload for OpcodesParams {
     fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
-        let asm = asm.string_utf16(self.func_name);
-
-        if let Some(class_name) = self.class_name {
-            asm.int8(1)
-                .string_utf16(class_name)
-        } else {
-            asm.int8(0)
-        }
+        (
+            StringUtf16::from(self.func_name), 
+            Flagged(self.class_name.map(StringUtf16::from))
+        ).assemble_payload(asm)
     }
 }
 
 impl DisassemblePayload for OpcodesParams {
-    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> anyhow::Result<Self> {
-        let func_name = dasm.string_utf16().context("func_name")?.0;
-
-        let has_class_name = dasm.int8().context("has_class_name")?;
-        let class_name;
-        if has_class_name == 1 {
-            class_name = Some(dasm.string_utf16().context("class_name")?.0);
-        } else {
-            class_name = None;
-        }
+    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> ProtocolResult<Self> {
+        let func_name = dasm.string_utf16().field("func_name")?.0;
+        let class_name = Flagged::<StringUtf16>::disassemble_payload(dasm).field("class_name")?;
 
         Ok(Self {
             func_name,
-            class_name
+            class_name: class_name.into_inner().map(|s| s.0)
         })
     }
 }
 
+impl DisassemblePartial for OpcodesParams {
+    type Partial = ();
+
+    fn disassemble_partial(dasm: &mut WitcherPacketDisassembler) -> Result<Self, (Self::Partial, ProtocolError)> {
+        disassemble_whole(dasm)
+    }
+}
+
 
 
 #[derive(Debug)]
@@ -456,29 +330,18 @@ pub struct OpcodesResponse;
 impl Message for OpcodesResponse {
     type Id = OpcodesResponseId;
     type Body = OpcodesResult;
+
+    const DIRECTION: MessageDirection = MessageDirection::ServerToClient;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::ScriptDebugger);
 }
 
 impl Response for OpcodesResponse {}
 
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["ScriptDebugger", "OpcodeBreakdownResponse"])]
 pub struct OpcodesResponseId;
 
-impl AssemblePayload for OpcodesResponseId {
-    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
-        asm.string_utf8(WitcherNamespace::ScriptDebugger.as_ref())
-            .string_utf8("OpcodeBreakdownResponse")
-    }
-}
-
-impl DisassemblePayload for OpcodesResponseId {
-    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> anyhow::Result<Self> {
-        dasm.fixed_string_utf8(WitcherNamespace::ScriptDebugger.as_ref())?;
-        dasm.fixed_string_utf8("OpcodeBreakdownResponse")?;
-        Ok(Self)
-    }
-}
-
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -495,41 +358,19 @@ pub struct OpcodeBreakdown {
 
 impl AssemblePayload for OpcodesResult {
     fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
-        let mut asm = asm
+        let asm = asm
             .int32(1) // unknown
-            .string_utf16("") // unknown
-            .int32(self.breakdowns.len() as i32);
+            .string_utf16(""); // unknown
 
-        for b in self.breakdowns {
-            asm = asm
-                .int32(b.line)
-                .string_utf16(b.opcodes.join("\n"));
-        }
-
-        asm
+        self.breakdowns.assemble_payload(asm)
     }
 }
 
 impl DisassemblePayload for OpcodesResult {
-    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> anyhow::Result<Self> {
-        dasm.int32().context("unknown0")?;
-        dasm.string_utf16().context("unknown1")?;
-
-        let breakdowns_count = dasm.int32().context("breakdowns_count")?;
-        let mut breakdowns = Vec::with_capacity(breakdowns_count as usize);
-        for _ in 0..breakdowns_count {
-            let line = dasm.int32().context("line")?;
-            let opcodes = dasm.string_utf16().context("opcodes")?;
-
-            let opcodes = opcodes.split("\n")
-                .map(|s| s.to_string())
-                .collect();
-
-            breakdowns.push(OpcodeBreakdown {
-                line,
-                opcodes
-            });
-        }
+    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> ProtocolResult<Self> {
+        dasm.int32().field("unknown0")?;
+        dasm.string_utf16().field("unknown1")?;
+        let breakdowns = Vec::disassemble_payload(dasm).field("breakdowns")?;
 
         Ok(Self {
             breakdowns
@@ -537,6 +378,36 @@ impl DisassemblePayload for OpcodesResult {
     }
 }
 
+impl DisassemblePartial for OpcodesResult {
+    type Partial = ();
+
+    fn disassemble_partial(dasm: &mut WitcherPacketDisassembler) -> Result<Self, (Self::Partial, ProtocolError)> {
+        disassemble_whole(dasm)
+    }
+}
+
+impl AssemblePayload for OpcodeBreakdown {
+    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
+        asm.int32(self.line)
+            .string_utf16(self.opcodes.join("\n"))
+    }
+}
+
+impl DisassemblePayload for OpcodeBreakdown {
+    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> ProtocolResult<Self> {
+        let line = dasm.int32().field("line")?;
+        let opcodes = dasm.string_utf16().field("opcodes")?
+            .split("\n")
+            .map(|s| s.to_string())
+            .collect();
+
+        Ok(Self {
+            line,
+            opcodes
+        })
+    }
+}
+
 
 
 
@@ -547,6 +418,9 @@ pub struct ConfigVars;
 impl Message for ConfigVars {
     type Id = ConfigVarsId;
     type Body = ConfigVarsParams;
+
+    const DIRECTION: MessageDirection = MessageDirection::ClientToServer;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::Config);
 }
 
 impl Request for ConfigVars {
@@ -554,58 +428,20 @@ impl Request for ConfigVars {
 }
 
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["Config", 0xCC00CC00, "list"])]
 pub struct ConfigVarsId;
 
-impl AssemblePayload for ConfigVarsId {
-    #[allow(overflowing_literals)]
-    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
-        asm.string_utf8(WitcherNamespace::Config.as_ref())
-            .int32(0xCC00CC00)
-            .string_utf8("list")
-    }
-}
 
-impl DisassemblePayload for ConfigVarsId {
-    #[allow(overflowing_literals)]
-    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> anyhow::Result<Self> {
-        dasm.fixed_string_utf8(WitcherNamespace::Config.as_ref())?;
-        dasm.fixed_int32(0xCC00CC00)?;
-        dasm.fixed_string_utf8("list")?;
-        Ok(Self)
-    }
-}
-
-
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, AssemblePayload, DisassemblePayload)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ConfigVarsParams {
+    #[witcher(with = EmptyAsNone)]
     pub section_filter: Option<String>,
+    #[witcher(with = EmptyAsNone)]
     pub name_filter: Option<String>
 }
 
-impl AssemblePayload for ConfigVarsParams {
-    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
-        asm.string_utf8(self.section_filter.unwrap_or_default())
-            .string_utf8(self.name_filter.unwrap_or_default())
-    }
-}
-
-impl DisassemblePayload for ConfigVarsParams {
-    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> anyhow::Result<Self> {
-        let section_filter = dasm.string_utf8().context("section_filter")?.0;
-        let name_filter = dasm.string_utf8().context("name_filter")?.0;
-
-        let section_filter = if section_filter.is_empty() { None } else { Some(section_filter) };
-        let name_filter = if name_filter.is_empty() { None } else { Some(name_filter) };
-
-        Ok(Self {
-            section_filter,
-            name_filter
-        })
-    }
-}
-
 
 
 #[derive(Debug)]
@@ -614,31 +450,18 @@ pub struct ConfigVarsResponse;
 impl Message for ConfigVarsResponse {
     type Id = ConfigVarsResponseId;
     type Body = ConfigVarsResult;
+
+    const DIRECTION: MessageDirection = MessageDirection::ServerToClient;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::Config);
 }
 
 impl Response for ConfigVarsResponse {}
 
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = [0xCC00CC00, "vars"])]
 pub struct ConfigVarsResponseId;
 
-impl AssemblePayload for ConfigVarsResponseId {
-    #[allow(overflowing_literals)]
-    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
-        asm.int32(0xCC00CC00)
-            .string_utf8("vars")
-    }
-}
-
-impl DisassemblePayload for ConfigVarsResponseId {
-    #[allow(overflowing_literals)]
-    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> anyhow::Result<Self> {
-        dasm.fixed_int32(0xCC00CC00)?;
-        dasm.fixed_string_utf8("vars")?;
-        Ok(Self)
-    }
-}
-
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -673,18 +496,18 @@ impl AssemblePayload for ConfigVarsResult {
 }
 
 impl DisassemblePayload for ConfigVarsResult {
-    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> anyhow::Result<Self> {
+    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> ProtocolResult<Self> {
         let mut vars = Vec::new();
         loop {
-            let data_type = dasm.int8().context("data_type")?;
+            let data_type = dasm.int8().field("data_type")?;
             if data_type == 0 {
                 break;
             }
 
-            let _unknown0 = dasm.int8().context("unknown0")?;
-            let name = dasm.string_utf8().context("name")?.0;
-            let section = dasm.string_utf8().context("section")?.0;
-            let value = dasm.string_utf8().context("value")?.0;
+            let _unknown0 = dasm.int8().field("unknown0")?;
+            let name = dasm.string_utf8().field("name")?.0;
+            let section = dasm.string_utf8().field("section")?.0;
+            let value = dasm.string_utf8().field("value")?.0;
 
             vars.push(ConfigVarInfo {
                 data_type,
@@ -701,6 +524,14 @@ impl DisassemblePayload for ConfigVarsResult {
     }
 }
 
+impl DisassemblePartial for ConfigVarsResult {
+    type Partial = ();
+
+    fn disassemble_partial(dasm: &mut WitcherPacketDisassembler) -> Result<Self, (Self::Partial, ProtocolError)> {
+        disassemble_whole(dasm)
+    }
+}
+
 
 
 
@@ -715,6 +546,8 @@ mod test {
 
 
     #[test]
+    // same shape as the tests of messages with a body, even though it's just ()
+    #[allow(clippy::let_unit_value, clippy::unit_arg, clippy::unit_cmp, clippy::clone_on_copy)]
     fn scripts_root_path_req_encode_test() {
         let param1 = ();
         let packet1 = ScriptsRootPath::assemble_packet(param1.clone());
@@ -814,6 +647,8 @@ mod test {
     }
 
     #[test]
+    // same shape as the tests of messages with a body, even though it's just ()
+    #[allow(clippy::let_unit_value, clippy::unit_arg, clippy::unit_cmp, clippy::clone_on_copy)]
     fn script_packages_req_encode_test() {
         let param1 = ();
         let packet1 = ScriptPackages::assemble_packet(param1.clone());
@@ -958,4 +793,93 @@ mod test {
         assert_eq!(packet1, packet2);
         assert_eq!(param1, param2);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn disassemble_error_test() {
+        let packet = ScriptsRootPath::assemble_packet(());
+        let err = ScriptsRootPathResponse::disassemble_packet(packet).unwrap_err();
+        assert!(matches!(err, ProtocolError::InvalidMessageId(_)));
+
+        let packet = WitcherPacketAssembler::new()
+            .string_utf8("ScriptCompiler")
+            .string_utf8("RootPathConfirm")
+            .int32(0)
+            .finish();
+        let err = ScriptsRootPathResponse::disassemble_packet(packet).unwrap_err();
+        assert!(matches!(&err, ProtocolError::InvalidMessageBody(e) if matches!(**e, ProtocolError::Field { .. })));
+        assert!(matches!(err.root_cause(), ProtocolError::TypeMismatch { expected, actual } 
+            if *expected == StringUtf16::ENCODING_TAG && *actual == i32::ENCODING_TAG));
+        let message = err.chain_message();
+        assert!(message.starts_with("Invalid or unknown packet body: Field `"));
+        assert_eq!(message.matches("Type mismatch").count(), 1);
+
+        let packet = WitcherPacketAssembler::new()
+            .string_utf8("ScriptCompiler")
+            .string_utf8("RootPathConfirm")
+            .finish();
+        let err = ScriptsRootPathResponse::disassemble_packet(packet).unwrap_err();
+        assert!(matches!(err.root_cause(), ProtocolError::MissingData));
+    }
+
+    #[test]
+    fn strict_and_lenient_disassembly_test() {
+        let mut packet = ScriptsRootPathResponse::assemble_packet(ScriptsRootPathResult {
+            abs_path: "C:/scripts".into()
+        });
+        packet.payload.push(WitcherPacketData::new_int32(7));
+        let body_size: usize = packet.payload[..3].iter().map(|d| d.encoded_size()).sum();
+
+        assert!(ScriptsRootPathResponse::disassemble_packet(packet.clone()).is_ok());
+
+        let err = ScriptsRootPathResponse::disassemble_packet_strict(packet.clone()).unwrap_err();
+        assert!(matches!(err.root_cause(), ProtocolError::TrailingData { index: 3, count: 1, offset } if *offset == body_size));
+
+        let lenient = ScriptsRootPathResponse::disassemble_packet_lenient(packet).unwrap();
+        assert_eq!(lenient.body.unwrap().abs_path, PathBuf::from("C:/scripts"));
+        assert_eq!(lenient.unconsumed, vec![WitcherPacketData::new_int32(7)]);
+        assert!(lenient.error.is_none());
+
+
+        let packet = WitcherPacketAssembler::new()
+            .string_utf8("ScriptCompiler")
+            .string_utf8("RootPathConfirm")
+            .int32(1)
+            .finish();
+        let id_size: usize = packet.payload[..2].iter().map(|d| d.encoded_size()).sum();
+
+        let err = ScriptsRootPathResponse::disassemble_packet_strict(packet.clone()).unwrap_err();
+        let ProtocolError::InvalidMessageBody(err) = err else { panic!() };
+        assert!(matches!(*err, ProtocolError::Position { index: 2, offset, .. } if offset == id_size));
+        assert!(matches!(err.root_cause(), ProtocolError::TypeMismatch { .. }));
+
+        let lenient = ScriptsRootPathResponse::disassemble_packet_lenient(packet).unwrap();
+        assert!(lenient.body.is_none());
+        assert!(lenient.partial.unwrap().abs_path.is_none());
+        assert_eq!(lenient.unconsumed, vec![WitcherPacketData::new_int32(1)]);
+        assert!(lenient.error.is_some());
+    }
+
+    #[test]
+    fn lenient_partial_body_test() {
+        let mut packet = ConfigVars::assemble_packet(ConfigVarsParams {
+            section_filter: Some("Rendering".to_string()),
+            name_filter: Some("Gamma".to_string())
+        });
+        // name_filter is the last field
+        *packet.payload.last_mut().unwrap() = WitcherPacketData::new_int32(5);
+        packet.payload.push(WitcherPacketData::new_int8(1));
+
+        assert!(ConfigVars::disassemble_packet(packet.clone()).is_err());
+
+        let lenient = ConfigVars::disassemble_packet_lenient(packet).unwrap();
+        assert!(lenient.body.is_none());
+        let partial = lenient.partial.unwrap();
+        assert_eq!(partial.section_filter, Some(Some("Rendering".to_string())));
+        assert_eq!(partial.name_filter, None);
+        assert_eq!(lenient.unconsumed, vec![WitcherPacketData::new_int32(5), WitcherPacketData::new_int8(1)]);
+
+        let ProtocolError::InvalidMessageBody(err) = lenient.error.unwrap() else { panic!() };
+        assert!(matches!(*err, ProtocolError::Position { index: 4, .. }));
+        assert!(matches!(err.root_cause(), ProtocolError::TypeMismatch { .. }));
+    }
+}
@@ -0,0 +1,295 @@
+use std::path::PathBuf;
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::protocol::*;
+use crate::messages::{Message, MessageDirection, WitcherNamespace};
+use super::{Request, Response};
+
+
+#[derive(Debug)]
+pub struct AddBreakpoint;
+
+impl Message for AddBreakpoint {
+    type Id = AddBreakpointId;
+    type Body = BreakpointParams;
+
+    const DIRECTION: MessageDirection = MessageDirection::ClientToServer;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::ScriptDebugger);
+    const EXPERIMENTAL: bool = true;
+}
+
+impl Request for AddBreakpoint {
+    type Response = AddBreakpointResponse;
+}
+
+
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["ScriptDebugger", "BreakpointAdd"])]
+pub struct AddBreakpointId;
+
+
+/// Location of a breakpoint
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, AssemblePayload, DisassemblePayload)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BreakpointParams {
+    /// Path of the script file relative to the scripts root directory of its package
+    pub local_script_path: PathBuf,
+    pub line: u32
+}
+
+
+
+#[derive(Debug)]
+pub struct AddBreakpointResponse;
+
+impl Message for AddBreakpointResponse {
+    type Id = AddBreakpointResponseId;
+    type Body = BreakpointConfirmation;
+
+    const DIRECTION: MessageDirection = MessageDirection::ServerToClient;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::ScriptDebugger);
+    const EXPERIMENTAL: bool = true;
+}
+
+impl Response for AddBreakpointResponse {}
+
+
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["ScriptDebugger", "BreakpointAddConfirm"])]
+pub struct AddBreakpointResponseId;
+
+
+/// Game's answer to a change made to a breakpoint
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, AssemblePayload, DisassemblePayload)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BreakpointConfirmation {
+    pub local_script_path: PathBuf,
+    pub line: u32,
+    /// State of the breakpoint after the change
+    pub state: BreakpointState
+}
+
+/// Numbers the states are sent as are as unconfirmed as the breakpoint messages themselves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum BreakpointState {
+    /// There is no breakpoint at given location anymore
+    Removed,
+    Enabled,
+    Disabled,
+    /// The change could not be made, e.g. the breakpoint to remove didn't exist
+    Rejected
+}
+
+impl BreakpointState {
+    const REMOVED: i8 = 0;
+    const ENABLED: i8 = 1;
+    const DISABLED: i8 = 2;
+    const REJECTED: i8 = -1;
+}
+
+impl AssemblePayload for BreakpointState {
+    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
+        asm.int8(match self {
+            BreakpointState::Removed => Self::REMOVED,
+            BreakpointState::Enabled => Self::ENABLED,
+            BreakpointState::Disabled => Self::DISABLED,
+            BreakpointState::Rejected => Self::REJECTED,
+        })
+    }
+}
+
+impl DisassemblePayload for BreakpointState {
+    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> ProtocolResult<Self> {
+        match dasm.int8()? {
+            Self::REMOVED => Ok(BreakpointState::Removed),
+            Self::ENABLED => Ok(BreakpointState::Enabled),
+            Self::DISABLED => Ok(BreakpointState::Disabled),
+            Self::REJECTED => Ok(BreakpointState::Rejected),
+            value => Err(ProtocolError::UnknownVariant { 
+                what: "breakpoint state", 
+                value: value.to_string() 
+            })
+        }
+    }
+}
+
+
+
+
+
+#[derive(Debug)]
+pub struct RemoveBreakpoint;
+
+impl Message for RemoveBreakpoint {
+    type Id = RemoveBreakpointId;
+    type Body = BreakpointParams;
+
+    const DIRECTION: MessageDirection = MessageDirection::ClientToServer;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::ScriptDebugger);
+    const EXPERIMENTAL: bool = true;
+}
+
+impl Request for RemoveBreakpoint {
+    type Response = RemoveBreakpointResponse;
+}
+
+
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["ScriptDebugger", "BreakpointRemove"])]
+pub struct RemoveBreakpointId;
+
+
+
+#[derive(Debug)]
+pub struct RemoveBreakpointResponse;
+
+impl Message for RemoveBreakpointResponse {
+    type Id = RemoveBreakpointResponseId;
+    type Body = BreakpointConfirmation;
+
+    const DIRECTION: MessageDirection = MessageDirection::ServerToClient;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::ScriptDebugger);
+    const EXPERIMENTAL: bool = true;
+}
+
+impl Response for RemoveBreakpointResponse {}
+
+
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["ScriptDebugger", "BreakpointRemoveConfirm"])]
+pub struct RemoveBreakpointResponseId;
+
+
+
+
+
+#[derive(Debug)]
+pub struct ToggleBreakpoint;
+
+impl Message for ToggleBreakpoint {
+    type Id = ToggleBreakpointId;
+    type Body = ToggleBreakpointParams;
+
+    const DIRECTION: MessageDirection = MessageDirection::ClientToServer;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::ScriptDebugger);
+    const EXPERIMENTAL: bool = true;
+}
+
+impl Request for ToggleBreakpoint {
+    type Response = ToggleBreakpointResponse;
+}
+
+
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["ScriptDebugger", "BreakpointToggle"])]
+pub struct ToggleBreakpointId;
+
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, AssemblePayload, DisassemblePayload)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ToggleBreakpointParams {
+    pub local_script_path: PathBuf,
+    pub line: u32,
+    /// Disabled breakpoints stay in place, but don't pause the game
+    pub enabled: bool
+}
+
+
+
+#[derive(Debug)]
+pub struct ToggleBreakpointResponse;
+
+impl Message for ToggleBreakpointResponse {
+    type Id = ToggleBreakpointResponseId;
+    type Body = BreakpointConfirmation;
+
+    const DIRECTION: MessageDirection = MessageDirection::ServerToClient;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::ScriptDebugger);
+    const EXPERIMENTAL: bool = true;
+}
+
+impl Response for ToggleBreakpointResponse {}
+
+
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["ScriptDebugger", "BreakpointToggleConfirm"])]
+pub struct ToggleBreakpointResponseId;
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::VecDeque;
+
+
+    #[test]
+    fn breakpoint_req_encode_test() {
+        let param1 = BreakpointParams {
+            local_script_path: r"game\player\playerWitcher.ws".into(),
+            line: 1364
+        };
+        let packet1 = AddBreakpoint::assemble_packet(param1.clone());
+
+        let mut bytes = VecDeque::new();
+        packet1.encode_into(&mut bytes).unwrap();
+
+        let packet2 = WitcherPacket::decode_from(&mut bytes).unwrap();
+        let param2 = AddBreakpoint::disassemble_packet(packet2.clone()).unwrap();
+
+        assert_eq!(packet1, packet2);
+        assert_eq!(param1, param2);
+        assert!(RemoveBreakpoint::disassemble_packet(packet2).is_err());
+
+
+        let param1 = ToggleBreakpointParams {
+            local_script_path: r"game\player\playerWitcher.ws".into(),
+            line: 1364,
+            enabled: false
+        };
+        let packet1 = ToggleBreakpoint::assemble_packet(param1.clone());
+
+        let mut bytes = VecDeque::new();
+        packet1.encode_into(&mut bytes).unwrap();
+
+        let packet2 = WitcherPacket::decode_from(&mut bytes).unwrap();
+        let param2 = ToggleBreakpoint::disassemble_packet(packet2.clone()).unwrap();
+
+        assert_eq!(packet1, packet2);
+        assert_eq!(param1, param2);
+    }
+
+    #[test]
+    fn breakpoint_resp_encode_test() {
+        for state in [BreakpointState::Removed, BreakpointState::Enabled, BreakpointState::Disabled, BreakpointState::Rejected] {
+            let param1 = BreakpointConfirmation {
+                local_script_path: r"game\player\playerWitcher.ws".into(),
+                line: 1364,
+                state
+            };
+            let packet1 = RemoveBreakpointResponse::assemble_packet(param1.clone());
+
+            let mut bytes = VecDeque::new();
+            packet1.encode_into(&mut bytes).unwrap();
+
+            let packet2 = WitcherPacket::decode_from(&mut bytes).unwrap();
+            let param2 = RemoveBreakpointResponse::disassemble_packet(packet2.clone()).unwrap();
+
+            assert_eq!(packet1, packet2);
+            assert_eq!(param1, param2);
+        }
+
+        let packet = WitcherPacketAssembler::new()
+            .string_utf8("ScriptDebugger")
+            .string_utf8("BreakpointAddConfirm")
+            .string_utf16("foo.ws")
+            .uint32(1)
+            .int8(5)
+            .finish();
+        let err = AddBreakpointResponse::disassemble_packet(packet).unwrap_err();
+        assert!(matches!(err.root_cause(), ProtocolError::UnknownVariant { what: "breakpoint state", .. }));
+    }
+}
@@ -0,0 +1,307 @@
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::protocol::*;
+use crate::messages::{Message, MessageDirection, WitcherNamespace};
+use super::{Request, Response};
+
+
+#[derive(Debug)]
+pub struct FrameLocals;
+
+impl Message for FrameLocals {
+    type Id = FrameLocalsId;
+    type Body = FrameLocalsParams;
+
+    const DIRECTION: MessageDirection = MessageDirection::ClientToServer;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::ScriptDebugger);
+    const EXPERIMENTAL: bool = true;
+}
+
+impl Request for FrameLocals {
+    type Response = FrameLocalsResponse;
+}
+
+
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["ScriptDebugger", "LocalsRequest"])]
+pub struct FrameLocalsId;
+
+
+/// Frame of a callstack of a paused thread, as reported by [`crate::messages::notifications::BreakHit`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, AssemblePayload, DisassemblePayload)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FrameLocalsParams {
+    pub thread_id: u32,
+    /// Index in the callstack, 0 being the innermost frame
+    pub frame_index: u32
+}
+
+
+
+#[derive(Debug)]
+pub struct FrameLocalsResponse;
+
+impl Message for FrameLocalsResponse {
+    type Id = FrameLocalsResponseId;
+    type Body = FrameLocalsResult;
+
+    const DIRECTION: MessageDirection = MessageDirection::ServerToClient;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::ScriptDebugger);
+    const EXPERIMENTAL: bool = true;
+}
+
+impl Response for FrameLocalsResponse {}
+
+
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["ScriptDebugger", "LocalsResponse"])]
+pub struct FrameLocalsResponseId;
+
+
+/// Variables visible in a callstack frame. 
+/// All lists are empty if the thread is not paused or the frame doesn't exist.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, AssemblePayload, DisassemblePayload)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FrameLocalsResult {
+    pub parameters: Vec<ScriptValue>,
+    pub locals: Vec<ScriptValue>,
+    /// Object the function was called on, `None` for global functions
+    pub this: Option<ScriptValue>
+}
+
+/// Node of a tree of values in a callstack frame.
+/// Children of objects and arrays are not sent along with them, they need to be requested with [`ExpandProperty`].
+/// Layout of a value on the wire is as unconfirmed as the [`FrameLocals`] message itself.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, AssemblePayload, DisassemblePayload)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ScriptValue {
+    /// Name of the variable or member, index in brackets for array elements
+    #[witcher(utf16)]
+    pub name: String,
+    #[witcher(utf16)]
+    pub type_name: String,
+    /// Value formatted the way the game does it
+    #[witcher(utf16)]
+    pub display_value: String,
+    /// Path to pass to [`ExpandProperty`] to get the children of this value, `None` if it has none
+    pub child_handle: Option<PropertyPath>
+}
+
+impl ScriptValue {
+    #[inline]
+    pub fn is_expandable(&self) -> bool {
+        self.child_handle.is_some()
+    }
+}
+
+/// Path to a value in a callstack frame starting at one of its variables, e.g. `this.inv.items[3]`
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PropertyPath(pub String);
+
+impl PropertyPath {
+    /// Path to the value of a member or array element of the value at this path
+    pub fn child(&self, name: &str) -> Self {
+        if name.starts_with('[') {
+            Self(format!("{}{}", self.0, name))
+        } else {
+            Self(format!("{}.{}", self.0, name))
+        }
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for PropertyPath {
+    #[inline]
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl std::fmt::Display for PropertyPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AssemblePayload for PropertyPath {
+    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
+        asm.string_utf16(self.0)
+    }
+}
+
+impl DisassemblePayload for PropertyPath {
+    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> ProtocolResult<Self> {
+        Ok(Self(dasm.string_utf16()?.0))
+    }
+}
+
+
+
+
+
+#[derive(Debug)]
+pub struct ExpandProperty;
+
+impl Message for ExpandProperty {
+    type Id = ExpandPropertyId;
+    type Body = ExpandPropertyParams;
+
+    const DIRECTION: MessageDirection = MessageDirection::ClientToServer;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::ScriptDebugger);
+    const EXPERIMENTAL: bool = true;
+}
+
+impl Request for ExpandProperty {
+    type Response = ExpandPropertyResponse;
+}
+
+
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["ScriptDebugger", "PropertyExpandRequest"])]
+pub struct ExpandPropertyId;
+
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, AssemblePayload, DisassemblePayload)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ExpandPropertyParams {
+    pub thread_id: u32,
+    pub frame_index: u32,
+    pub path: PropertyPath
+}
+
+
+
+#[derive(Debug)]
+pub struct ExpandPropertyResponse;
+
+impl Message for ExpandPropertyResponse {
+    type Id = ExpandPropertyResponseId;
+    type Body = ExpandPropertyResult;
+
+    const DIRECTION: MessageDirection = MessageDirection::ServerToClient;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::ScriptDebugger);
+    const EXPERIMENTAL: bool = true;
+}
+
+impl Response for ExpandPropertyResponse {}
+
+
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["ScriptDebugger", "PropertyExpandResponse"])]
+pub struct ExpandPropertyResponseId;
+
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, AssemblePayload, DisassemblePayload)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ExpandPropertyResult {
+    pub path: PropertyPath,
+    /// Members of an object or elements of an array, empty if the path doesn't lead to an existing value
+    pub children: Vec<ScriptValue>
+}
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::VecDeque;
+
+
+    #[test]
+    fn frame_locals_encode_test() {
+        let param1 = FrameLocalsParams {
+            thread_id: 1,
+            frame_index: 0
+        };
+        let packet1 = FrameLocals::assemble_packet(param1);
+
+        let mut bytes = VecDeque::new();
+        packet1.encode_into(&mut bytes).unwrap();
+
+        let packet2 = WitcherPacket::decode_from(&mut bytes).unwrap();
+        let param2 = FrameLocals::disassemble_packet(packet2.clone()).unwrap();
+
+        assert_eq!(packet1, packet2);
+        assert_eq!(param1, param2);
+
+
+        let param1 = FrameLocalsResult {
+            parameters: vec![
+                ScriptValue {
+                    name: "amount".into(),
+                    type_name: "int".into(),
+                    display_value: "12".into(),
+                    child_handle: None
+                }
+            ],
+            locals: vec![],
+            this: Some(ScriptValue {
+                name: "this".into(),
+                type_name: "CR4Player".into(),
+                display_value: "CR4Player".into(),
+                child_handle: Some("this".into())
+            })
+        };
+        let packet1 = FrameLocalsResponse::assemble_packet(param1.clone());
+
+        let mut bytes = VecDeque::new();
+        packet1.encode_into(&mut bytes).unwrap();
+
+        let packet2 = WitcherPacket::decode_from(&mut bytes).unwrap();
+        let param2 = FrameLocalsResponse::disassemble_packet_strict(packet2.clone()).unwrap();
+
+        assert_eq!(packet1, packet2);
+        assert_eq!(param1, param2);
+    }
+
+    #[test]
+    fn expand_property_encode_test() {
+        let path = PropertyPath::from("this").child("inv").child("items").child("[3]");
+        assert_eq!(path.as_str(), "this.inv.items[3]");
+
+        let param1 = ExpandPropertyParams {
+            thread_id: 1,
+            frame_index: 2,
+            path: path.clone()
+        };
+        let packet1 = ExpandProperty::assemble_packet(param1.clone());
+
+        let mut bytes = VecDeque::new();
+        packet1.encode_into(&mut bytes).unwrap();
+
+        let packet2 = WitcherPacket::decode_from(&mut bytes).unwrap();
+        let param2 = ExpandProperty::disassemble_packet(packet2.clone()).unwrap();
+
+        assert_eq!(packet1, packet2);
+        assert_eq!(param1, param2);
+
+
+        let param1 = ExpandPropertyResult {
+            path: path.clone(),
+            children: vec![
+                ScriptValue {
+                    name: "uniqueId".into(),
+                    type_name: "SItemUniqueId".into(),
+                    display_value: "{ id = 1734 }".into(),
+                    child_handle: Some(path.child("uniqueId"))
+                }
+            ]
+        };
+        let packet1 = ExpandPropertyResponse::assemble_packet(param1.clone());
+
+        let mut bytes = VecDeque::new();
+        packet1.encode_into(&mut bytes).unwrap();
+
+        let packet2 = WitcherPacket::decode_from(&mut bytes).unwrap();
+        let param2 = ExpandPropertyResponse::disassemble_packet_strict(packet2.clone()).unwrap();
+
+        assert_eq!(packet1, packet2);
+        assert_eq!(param1, param2);
+    }
+}
@@ -0,0 +1,163 @@
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::protocol::*;
+use crate::messages::{Message, MessageDirection, WitcherNamespace};
+use super::{Request, Response};
+
+
+#[derive(Debug)]
+pub struct ProfilerStop;
+
+impl Message for ProfilerStop {
+    type Id = ProfilerStopId;
+    type Body = ();
+
+    const DIRECTION: MessageDirection = MessageDirection::ClientToServer;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::ScriptProfiler);
+    const EXPERIMENTAL: bool = true;
+}
+
+impl Request for ProfilerStop {
+    type Response = ProfilerStopResponse;
+}
+
+
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["ScriptProfiler", "Stop"])]
+pub struct ProfilerStopId;
+
+
+
+#[derive(Debug)]
+pub struct ProfilerStopResponse;
+
+impl Message for ProfilerStopResponse {
+    type Id = ProfilerStopResponseId;
+    type Body = ProfilerReport;
+
+    const DIRECTION: MessageDirection = MessageDirection::ServerToClient;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::ScriptProfiler);
+    const EXPERIMENTAL: bool = true;
+}
+
+impl Response for ProfilerStopResponse {}
+
+
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["ScriptProfiler", "Report"])]
+pub struct ProfilerStopResponseId;
+
+
+/// Data gathered by the profiler since it was started.
+/// See [`crate::profiler`] for exporting it to formats understood by profile viewers.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, AssemblePayload, DisassemblePayload)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProfilerReport {
+    /// How long scripts were being profiled for
+    pub duration_micros: i64,
+    pub functions: Vec<FunctionTiming>,
+    pub stacks: Vec<StackSample>
+}
+
+/// Time spent in a single function over the whole profiling session
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, AssemblePayload, DisassemblePayload)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FunctionTiming {
+    /// Name of the function preceded by its class if it has any, e.g. `CR4Player::OnSpawned`
+    #[witcher(utf16)]
+    pub func_name: String,
+    pub call_count: u32,
+    /// Time spent in the function itself
+    pub self_micros: i64,
+    /// Time spent in the function together with functions it called
+    pub total_micros: i64
+}
+
+/// Samples taken while scripts were executing the same callstack
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StackSample {
+    /// Function names the same as in [`FunctionTiming`], starting with the outermost frame
+    pub frames: Vec<String>,
+    pub sample_count: u32,
+    /// Time spent in the innermost frame of this callstack
+    pub self_micros: i64
+}
+
+impl AssemblePayload for StackSample {
+    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
+        (
+            self.frames.into_iter().map(StringUtf16::from).collect::<Vec<_>>(),
+            self.sample_count,
+            self.self_micros
+        ).assemble_payload(asm)
+    }
+}
+
+impl DisassemblePayload for StackSample {
+    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> ProtocolResult<Self> {
+        let frames = Vec::<StringUtf16>::disassemble_payload(dasm).field("frames")?;
+        let sample_count = dasm.uint32().field("sample_count")?;
+        let self_micros = dasm.int64().field("self_micros")?;
+
+        Ok(Self {
+            frames: frames.into_iter().map(|s| s.0).collect(),
+            sample_count,
+            self_micros
+        })
+    }
+}
+
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::VecDeque;
+
+
+    #[test]
+    fn profiler_report_encode_test() {
+        let packet1 = ProfilerStop::assemble_packet(());
+
+        let mut bytes = VecDeque::new();
+        packet1.encode_into(&mut bytes).unwrap();
+
+        let packet2 = WitcherPacket::decode_from(&mut bytes).unwrap();
+        ProfilerStop::disassemble_packet_strict(packet2.clone()).unwrap();
+
+        assert_eq!(packet1, packet2);
+
+
+        let param1 = ProfilerReport {
+            duration_micros: 5_000_000,
+            functions: vec![
+                FunctionTiming {
+                    func_name: "CR4Player::OnTick".into(),
+                    call_count: 150,
+                    self_micros: 1200,
+                    total_micros: 4800
+                }
+            ],
+            stacks: vec![
+                StackSample {
+                    frames: vec!["CR4Player::OnTick".into(), "CR4Player::UpdateHud".into()],
+                    sample_count: 36,
+                    self_micros: 3600
+                }
+            ]
+        };
+        let packet1 = ProfilerStopResponse::assemble_packet(param1.clone());
+
+        let mut bytes = VecDeque::new();
+        packet1.encode_into(&mut bytes).unwrap();
+
+        let packet2 = WitcherPacket::decode_from(&mut bytes).unwrap();
+        let param2 = ProfilerStopResponse::disassemble_packet_strict(packet2.clone()).unwrap();
+
+        assert_eq!(packet1, packet2);
+        assert_eq!(param1, param2);
+    }
+}
@@ -0,0 +1,115 @@
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::protocol::*;
+use crate::messages::{Message, MessageDirection, WitcherNamespace};
+use super::{Request, Response};
+
+
+#[derive(Debug)]
+pub struct SetConfigVar;
+
+impl Message for SetConfigVar {
+    type Id = SetConfigVarId;
+    type Body = SetConfigVarParams;
+
+    const DIRECTION: MessageDirection = MessageDirection::ClientToServer;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::Config);
+    const EXPERIMENTAL: bool = true;
+}
+
+impl Request for SetConfigVar {
+    type Response = SetConfigVarResponse;
+}
+
+
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["Config", 0xCC00CC00, "set"])]
+pub struct SetConfigVarId;
+
+
+/// Strings are sent the same way [`super::ConfigVarsResult`] sends them
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, AssemblePayload, DisassemblePayload)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SetConfigVarParams {
+    pub section: String,
+    pub name: String,
+    /// New value written the same way as [`super::ConfigVarInfo::value`], e.g. "true" for bools
+    pub value: String
+}
+
+
+
+#[derive(Debug)]
+pub struct SetConfigVarResponse;
+
+impl Message for SetConfigVarResponse {
+    type Id = SetConfigVarResponseId;
+    type Body = SetConfigVarResult;
+
+    const DIRECTION: MessageDirection = MessageDirection::ServerToClient;
+    const NAMESPACE: Option<WitcherNamespace> = Some(WitcherNamespace::Config);
+    const EXPERIMENTAL: bool = true;
+}
+
+impl Response for SetConfigVarResponse {}
+
+
+#[derive(Debug, Default, AssemblePayload, DisassemblePayload)]
+#[witcher(id = [0xCC00CC00, "set"])]
+pub struct SetConfigVarResponseId;
+
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, AssemblePayload, DisassemblePayload)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SetConfigVarResult {
+    /// False if the var doesn't exist or the value doesn't fit its type
+    pub success: bool
+}
+
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::ConfigVars;
+    use std::collections::VecDeque;
+
+
+    #[test]
+    fn set_config_var_encode_test() {
+        let param1 = SetConfigVarParams {
+            section: "Visuals".into(),
+            name: "GammaValue".into(),
+            value: "1.2".into()
+        };
+        let packet1 = SetConfigVar::assemble_packet(param1.clone());
+
+        let mut bytes = VecDeque::new();
+        packet1.encode_into(&mut bytes).unwrap();
+
+        let packet2 = WitcherPacket::decode_from(&mut bytes).unwrap();
+        let param2 = SetConfigVar::disassemble_packet_strict(packet2.clone()).unwrap();
+
+        assert_eq!(packet1, packet2);
+        assert_eq!(param1, param2);
+        // shares the namespace and marker with the list request
+        assert!(ConfigVars::disassemble_packet(packet2).is_err());
+
+
+        let param1 = SetConfigVarResult {
+            success: true
+        };
+        let packet1 = SetConfigVarResponse::assemble_packet(param1.clone());
+
+        let mut bytes = VecDeque::new();
+        packet1.encode_into(&mut bytes).unwrap();
+
+        let packet2 = WitcherPacket::decode_from(&mut bytes).unwrap();
+        let param2 = SetConfigVarResponse::disassemble_packet_strict(packet2.clone()).unwrap();
+
+        assert_eq!(packet1, packet2);
+        assert_eq!(param1, param2);
+    }
+}
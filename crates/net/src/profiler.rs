@@ -0,0 +1,130 @@
+//! Exporting script profiler reports to formats understood by profile viewers
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::messages::requests::ProfilerReport;
+
+
+/// Converts the report into a JSON file which can be opened in [speedscope](https://www.speedscope.app).
+/// Every callstack is weighted by the time spent in its innermost frame, same as in [`collapsed_stacks`].
+pub fn speedscope_json(report: &ProfilerReport, name: &str) -> String {
+    let mut frame_indices: HashMap<&str, usize> = HashMap::new();
+    let mut frames = Vec::new();
+    let mut samples = Vec::new();
+    let mut weights = Vec::new();
+
+    for stack in &report.stacks {
+        let indices: Vec<String> = stack.frames.iter()
+            .map(|frame| {
+                let i = *frame_indices.entry(frame.as_str()).or_insert_with(|| {
+                    frames.push(format!("{{\"name\":{}}}", json_string(frame)));
+                    frames.len() - 1
+                });
+                i.to_string()
+            })
+            .collect();
+
+        samples.push(format!("[{}]", indices.join(",")));
+        weights.push(stack.self_micros.max(0));
+    }
+
+    let end_value: i64 = weights.iter().sum();
+    let weights: Vec<String> = weights.iter().map(|w| w.to_string()).collect();
+
+    let mut json = String::new();
+    json.push('{');
+    write!(json, "\"$schema\":\"https://www.speedscope.app/file-format-schema.json\",").unwrap();
+    write!(json, "\"name\":{},", json_string(name)).unwrap();
+    write!(json, "\"exporter\":\"rw3d_net {}\",", env!("CARGO_PKG_VERSION")).unwrap();
+    write!(json, "\"activeProfileIndex\":0,").unwrap();
+    write!(json, "\"shared\":{{\"frames\":[{}]}},", frames.join(",")).unwrap();
+    write!(json, "\"profiles\":[{{\"type\":\"sampled\",\"name\":{},\"unit\":\"microseconds\",\"startValue\":0,\"endValue\":{},\"samples\":[{}],\"weights\":[{}]}}]",
+        json_string(name), end_value, samples.join(","), weights.join(",")).unwrap();
+    json.push('}');
+    json
+}
+
+/// Converts the report into collapsed stacks, which is the input format of flamegraph tools
+/// like `flamegraph.pl` or `inferno-flamegraph`. Each line is a callstack followed by the time spent in its innermost frame in microseconds,
+/// so that the flamegraph shows the same proportions as [`speedscope_json`].
+pub fn collapsed_stacks(report: &ProfilerReport) -> String {
+    let mut collapsed = String::new();
+    for stack in report.stacks.iter().filter(|s| s.self_micros > 0 && !s.frames.is_empty()) {
+        // semicolons separate frames, so they can't appear in the names
+        let frames: Vec<String> = stack.frames.iter().map(|f| f.replace(';', ":")).collect();
+        writeln!(collapsed, "{} {}", frames.join(";"), stack.self_micros).unwrap();
+    }
+
+    collapsed
+}
+
+fn json_string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c)
+        }
+    }
+    json.push('"');
+    json
+}
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use crate::messages::requests::*;
+
+    use super::*;
+
+
+    fn report() -> ProfilerReport {
+        ProfilerReport {
+            duration_micros: 1_000_000,
+            functions: vec![],
+            stacks: vec![
+                StackSample {
+                    frames: vec!["CR4Player::OnTick".into(), "CR4Player::UpdateHud".into()],
+                    sample_count: 3,
+                    self_micros: 300
+                },
+                StackSample {
+                    frames: vec!["CR4Player::OnTick".into(), "GetWitcherPlayer".into()],
+                    sample_count: 1,
+                    self_micros: 50
+                },
+                StackSample {
+                    frames: vec!["Log\"Quoted\"".into()],
+                    sample_count: 0,
+                    self_micros: 0
+                }
+            ]
+        }
+    }
+
+    #[test]
+    fn collapsed_stacks_test() {
+        assert_eq!(collapsed_stacks(&report()), "CR4Player::OnTick;CR4Player::UpdateHud 300\nCR4Player::OnTick;GetWitcherPlayer 50\n");
+    }
+
+    #[test]
+    fn speedscope_json_test() {
+        let json = speedscope_json(&report(), "Witcher 3 scripts");
+        assert!(json.contains(r#""frames":[{"name":"CR4Player::OnTick"},{"name":"CR4Player::UpdateHud"},{"name":"GetWitcherPlayer"},{"name":"Log\"Quoted\""}]"#));
+        assert!(json.contains(r#""samples":[[0,1],[0,2],[3]],"weights":[300,50,0]"#));
+        assert!(json.contains(r#""endValue":350"#));
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["profiles"][0]["type"], "sampled");
+    }
+}
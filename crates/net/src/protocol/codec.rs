@@ -0,0 +1,118 @@
+use bytes::{Buf, BufMut, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use super::encoding::*;
+use super::error::ProtocolError;
+use super::framing::probe_frame_size;
+use super::limits::DecodeLimits;
+use super::packet::WitcherPacket;
+
+
+/// Codec for use with [`tokio_util::codec::Framed`] and its relatives.
+/// Packets are only handed to the decoder once all bytes declared in their header have arrived.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WitcherPacketCodec {
+    limits: DecodeLimits
+}
+
+impl WitcherPacketCodec {
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_limits(DecodeLimits::default())
+    }
+
+    #[inline]
+    pub fn with_limits(limits: DecodeLimits) -> Self {
+        Self {
+            limits
+        }
+    }
+
+    #[inline]
+    pub fn limits(&self) -> &DecodeLimits {
+        &self.limits
+    }
+
+    #[inline]
+    pub fn set_limits(&mut self, limits: DecodeLimits) {
+        self.limits = limits;
+    }
+}
+
+impl Decoder for WitcherPacketCodec {
+    type Item = WitcherPacket;
+    type Error = ProtocolError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        match probe_frame_size(src, self.limits.max_packet_size)? {
+            Some(frame_size) => {
+                let frame = src.split_to(frame_size);
+                let packet = WitcherPacket::decode_limited(&mut frame.reader(), &self.limits)?;
+                Ok(Some(packet))
+            },
+            None => Ok(None)
+        }
+    }
+}
+
+impl Encoder<WitcherPacket> for WitcherPacketCodec {
+    type Error = ProtocolError;
+
+    fn encode(&mut self, item: WitcherPacket, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        self.encode(&item, dst)
+    }
+}
+
+impl Encoder<&WitcherPacket> for WitcherPacketCodec {
+    type Error = ProtocolError;
+
+    fn encode(&mut self, item: &WitcherPacket, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        dst.reserve(item.encoded_size());
+        item.encode_into(&mut dst.writer())
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::*;
+
+    use super::*;
+
+
+    #[test]
+    fn codec_partial_frame_test() {
+        let packet1 = WitcherPacketAssembler::new()
+            .string_utf8("ScriptCompiler")
+            .string_utf16("C:\\scripts")
+            .finish();
+
+        let mut codec = WitcherPacketCodec::new();
+        let mut encoded = BytesMut::new();
+        codec.encode(&packet1, &mut encoded).unwrap();
+        codec.encode(&packet1, &mut encoded).unwrap();
+
+        let mut src = BytesMut::new();
+        src.extend_from_slice(&encoded[..7]);
+        assert!(codec.decode(&mut src).unwrap().is_none());
+        assert_eq!(src.len(), 7);
+
+        src.extend_from_slice(&encoded[7..]);
+        let packet2 = codec.decode(&mut src).unwrap().unwrap();
+        let packet3 = codec.decode(&mut src).unwrap().unwrap();
+
+        assert_eq!(packet1, packet2);
+        assert_eq!(packet1, packet3);
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn codec_invalid_head_test() {
+        let mut codec = WitcherPacketCodec::new();
+        let mut src = BytesMut::from(&[0xBE, 0xEF, 0x00, 0x06, 0xBE, 0xEF][..]);
+        assert!(codec.decode(&mut src).is_err());
+    }
+}
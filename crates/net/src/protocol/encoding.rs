@@ -1,11 +1,15 @@
-use std::{borrow::Cow, io::{Read, Write}};
+use std::{borrow::Cow, io::{Read, Write}, path::PathBuf};
 
-use anyhow::{bail, Context};
 use shrinkwraprs::Shrinkwrap;
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+use super::error::{ProtocolError, ProtocolResult};
+use super::limits::DecodeLimits;
 
 
 pub trait Encode {
-    fn encode_into<S: Write>(&self, stream: &mut S) -> anyhow::Result<()>;
+    fn encode_into<S: Write>(&self, stream: &mut S) -> ProtocolResult<()>;
 }
 
 
@@ -31,7 +35,7 @@ pub trait EncodingTag {
 
 
 pub trait Decode: Sized {
-    fn decode_from<S: Read>(stream: &mut S) -> anyhow::Result<Self>;
+    fn decode_from<S: Read>(stream: &mut S) -> ProtocolResult<Self>;
 }
 
 
@@ -39,7 +43,7 @@ pub trait Decode: Sized {
 
 impl Encode for i8 {
     #[inline]
-    fn encode_into<S: std::io::Write>(&self, stream: &mut S) -> anyhow::Result<()> {
+    fn encode_into<S: std::io::Write>(&self, stream: &mut S) -> ProtocolResult<()> {
         stream.write_all(&self.to_be_bytes())?;
         Ok(())
     }
@@ -54,9 +58,9 @@ impl EncodingTag for i8 {
 }
 
 impl Decode for i8 {
-    fn decode_from<S: std::io::Read>(stream: &mut S) -> anyhow::Result<Self> {
+    fn decode_from<S: std::io::Read>(stream: &mut S) -> ProtocolResult<Self> {
         let mut buf = [0u8; 1];
-        stream.read_exact(&mut buf).context("Failed to read bytes from stream for i8")?;
+        stream.read_exact(&mut buf)?;
         let val = i8::from_be_bytes(buf);
         Ok(val)
     }
@@ -66,7 +70,7 @@ impl Decode for i8 {
 
 impl Encode for i16 {
     #[inline]
-    fn encode_into<S: std::io::Write>(&self, stream: &mut S) -> anyhow::Result<()> {
+    fn encode_into<S: std::io::Write>(&self, stream: &mut S) -> ProtocolResult<()> {
         stream.write_all(&self.to_be_bytes())?;
         Ok(())
     }
@@ -81,9 +85,9 @@ impl EncodingTag for i16 {
 }
 
 impl Decode for i16 {
-    fn decode_from<S: std::io::Read>(stream: &mut S) -> anyhow::Result<Self> {
+    fn decode_from<S: std::io::Read>(stream: &mut S) -> ProtocolResult<Self> {
         let mut buf = [0u8; 2];
-        stream.read_exact(&mut buf).context("Failed to read bytes from stream for i16")?;
+        stream.read_exact(&mut buf)?;
         let val = i16::from_be_bytes(buf);
         Ok(val)
     }
@@ -93,16 +97,20 @@ impl Decode for i16 {
 
 impl Encode for u16 {
     #[inline]
-    fn encode_into<S: std::io::Write>(&self, stream: &mut S) -> anyhow::Result<()> {
+    fn encode_into<S: std::io::Write>(&self, stream: &mut S) -> ProtocolResult<()> {
         stream.write_all(&self.to_be_bytes())?;
         Ok(())
     }
 }
 
+impl ConstSizedEncode for u16 {
+    const ENCODED_SIZE: usize = 2;
+}
+
 impl Decode for u16 {
-    fn decode_from<S: std::io::Read>(stream: &mut S) -> anyhow::Result<Self> {
+    fn decode_from<S: std::io::Read>(stream: &mut S) -> ProtocolResult<Self> {
         let mut buf = [0u8; 2];
-        stream.read_exact(&mut buf).context("Failed to read bytes from stream for u16")?;
+        stream.read_exact(&mut buf)?;
         let val = u16::from_be_bytes(buf);
         Ok(val)
     }
@@ -112,7 +120,7 @@ impl Decode for u16 {
 
 impl Encode for i32 {
     #[inline]
-    fn encode_into<S: std::io::Write>(&self, stream: &mut S) -> anyhow::Result<()> {
+    fn encode_into<S: std::io::Write>(&self, stream: &mut S) -> ProtocolResult<()> {
         stream.write_all(&self.to_be_bytes())?;
         Ok(())
     }
@@ -127,9 +135,9 @@ impl EncodingTag for i32 {
 }
 
 impl Decode for i32 {
-    fn decode_from<S: std::io::Read>(stream: &mut S) -> anyhow::Result<Self> {
+    fn decode_from<S: std::io::Read>(stream: &mut S) -> ProtocolResult<Self> {
         let mut buf = [0u8; 4];
-        stream.read_exact(&mut buf).context("Failed to read bytes from stream for i32")?;
+        stream.read_exact(&mut buf)?;
         let val = i32::from_be_bytes(buf);
         Ok(val)
     }
@@ -139,7 +147,7 @@ impl Decode for i32 {
 
 impl Encode for u32 {
     #[inline]
-    fn encode_into<S: std::io::Write>(&self, stream: &mut S) -> anyhow::Result<()> {
+    fn encode_into<S: std::io::Write>(&self, stream: &mut S) -> ProtocolResult<()> {
         stream.write_all(&self.to_be_bytes())?;
         Ok(())
     }
@@ -154,9 +162,9 @@ impl EncodingTag for u32 {
 }
 
 impl Decode for u32 {
-    fn decode_from<S: std::io::Read>(stream: &mut S) -> anyhow::Result<Self> {
+    fn decode_from<S: std::io::Read>(stream: &mut S) -> ProtocolResult<Self> {
         let mut buf = [0u8; 4];
-        stream.read_exact(&mut buf).context("Failed to read bytes from stream for u32")?;
+        stream.read_exact(&mut buf)?;
         let val = u32::from_be_bytes(buf);
         Ok(val)
     }
@@ -166,7 +174,7 @@ impl Decode for u32 {
 
 impl Encode for i64 {
     #[inline]
-    fn encode_into<S: std::io::Write>(&self, stream: &mut S) -> anyhow::Result<()> {
+    fn encode_into<S: std::io::Write>(&self, stream: &mut S) -> ProtocolResult<()> {
         stream.write_all(&self.to_be_bytes())?;
         Ok(())
     }
@@ -181,9 +189,9 @@ impl EncodingTag for i64 {
 }
 
 impl Decode for i64 {
-    fn decode_from<S: std::io::Read>(stream: &mut S) -> anyhow::Result<Self> {
+    fn decode_from<S: std::io::Read>(stream: &mut S) -> ProtocolResult<Self> {
         let mut buf = [0u8; 8];
-        stream.read_exact(&mut buf).context("Failed to read bytes from stream for i64")?;
+        stream.read_exact(&mut buf)?;
         let val = i64::from_be_bytes(buf);
         Ok(val)
     }
@@ -191,26 +199,51 @@ impl Decode for i64 {
 
 
 
-#[derive(Shrinkwrap, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Shrinkwrap, Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
 pub struct StringUtf8(pub String);
 
 impl StringUtf8 {
+    /// Length in bytes
     #[inline]
-    pub fn len_tagged(&self) -> Tagged<i16> {
-        Tagged::new(self.len() as i16)
+    pub fn len_encoded(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Fails if the string is too long to have its length encoded
+    #[inline]
+    pub fn len_tagged(&self) -> ProtocolResult<Tagged<i16>> {
+        let len = i16::try_from(self.len_encoded())
+            .map_err(|_| ProtocolError::LimitExceeded { 
+                what: "UTF8 string length", 
+                actual: self.len_encoded(), 
+                limit: i16::MAX as usize 
+            })?;
+        Ok(Tagged::new(len))
     }
 
     #[inline]
     pub fn bytes_encoded(&self) -> &[u8] {
         self.0.as_bytes()
     }
+
+    /// Decodes the string, failing if its length is negative or exceeds `max_len` bytes
+    pub fn decode_limited<S: Read>(stream: &mut S, max_len: usize) -> ProtocolResult<Self> {
+        let len = decode_string_len(stream, max_len)?;
+
+        let mut content_buf = vec![0u8; len];
+        stream.read_exact(&mut content_buf)?;
+
+        let s = String::from_utf8_lossy(&content_buf).to_string();
+        Ok(Self(s))
+    }
 }
 
 impl Encode for StringUtf8 {
     #[inline]
-    fn encode_into<S: std::io::Write>(&self, stream: &mut S) -> anyhow::Result<()> {
-        self.len_tagged().encode_into(stream)?;
-        stream.write_all(&self.bytes_encoded())?;
+    fn encode_into<S: std::io::Write>(&self, stream: &mut S) -> ProtocolResult<()> {
+        self.len_tagged()?.encode_into(stream)?;
+        stream.write_all(self.bytes_encoded())?;
         Ok(())
     }
 }
@@ -218,8 +251,7 @@ impl Encode for StringUtf8 {
 impl DynSizedEncode for StringUtf8 {
     #[inline]
     fn encoded_size(&self) -> usize {
-        let len = self.len_tagged();
-        len.encoded_size() + len.into_inner() as usize
+        i16::ENCODING_TAG.len() + i16::ENCODED_SIZE + self.len_encoded()
     }
 }
 
@@ -228,15 +260,8 @@ impl EncodingTag for StringUtf8 {
 }
 
 impl Decode for StringUtf8 {
-    fn decode_from<S: std::io::Read>(stream: &mut S) -> anyhow::Result<Self> {
-        let len_tagged = Tagged::<i16>::decode_from(stream).context("Failed to decode UTF8 string length")?;
-        let len = len_tagged.into_inner() as usize;
-
-        let mut content_buf = vec![0u8; len];
-        stream.read_exact(&mut content_buf).context("Failed to read UTF8 string contents")?;
-
-        let s = String::from_utf8_lossy(&content_buf).to_string();
-        Ok(Self(s))
+    fn decode_from<S: std::io::Read>(stream: &mut S) -> ProtocolResult<Self> {
+        Self::decode_limited(stream, DecodeLimits::WIRE_MAX_STRING_LEN)
     }
 }
 
@@ -266,28 +291,41 @@ impl From<Cow<'_, str>> for StringUtf8 {
 
 
 
-#[derive(Shrinkwrap, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Shrinkwrap, Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
 pub struct StringUtf16(pub String);
 
 impl StringUtf16 {
+    /// Length in UTF16 code units
     #[inline]
-    pub fn len_tagged(&self) -> Tagged<i16> {
-        Tagged::new(self.encode_utf16().count() as i16)
+    pub fn len_encoded(&self) -> usize {
+        self.0.encode_utf16().count()
+    }
+
+    /// Fails if the string is too long to have its length encoded
+    #[inline]
+    pub fn len_tagged(&self) -> ProtocolResult<Tagged<i16>> {
+        let len = i16::try_from(self.len_encoded())
+            .map_err(|_| ProtocolError::LimitExceeded { 
+                what: "UTF16 string length", 
+                actual: self.len_encoded(), 
+                limit: i16::MAX as usize 
+            })?;
+        Ok(Tagged::new(len))
     }
 
     #[inline]
     pub fn bytes_encoded(&self) -> Vec<u8> {
         self.0.encode_utf16()
-            .map(|b| b.to_be_bytes())
-            .flatten()
+            .flat_map(|b| b.to_be_bytes())
             .collect::<Vec<_>>()
     }
 }
 
 impl Encode for StringUtf16 {
     #[inline]
-    fn encode_into<S: std::io::Write>(&self, stream: &mut S) -> anyhow::Result<()> {
-        self.len_tagged().encode_into(stream)?;
+    fn encode_into<S: std::io::Write>(&self, stream: &mut S) -> ProtocolResult<()> {
+        self.len_tagged()?.encode_into(stream)?;
         stream.write_all(&self.bytes_encoded())?;
         Ok(())
     }
@@ -296,8 +334,7 @@ impl Encode for StringUtf16 {
 impl DynSizedEncode for StringUtf16 {
     #[inline]
     fn encoded_size(&self) -> usize {
-        let len = self.len_tagged();
-        len.encoded_size() + len.into_inner() as usize * 2
+        i16::ENCODING_TAG.len() + i16::ENCODED_SIZE + self.len_encoded() * 2
     }
 }
 
@@ -305,21 +342,17 @@ impl EncodingTag for StringUtf16 {
     const ENCODING_TAG: [u8; 2] = [0x9C, 0x16];
 }
 
-impl Decode for StringUtf16 {
-    fn decode_from<S: std::io::Read>(stream: &mut S) -> anyhow::Result<Self> {
-        let len_tagged = Tagged::<i16>::decode_from(stream).context("Failed to decode UTF16 string length")?;
-        let len = len_tagged.into_inner() as usize;
+impl StringUtf16 {
+    /// Decodes the string, failing if its length is negative or exceeds `max_len` code units
+    pub fn decode_limited<S: Read>(stream: &mut S, max_len: usize) -> ProtocolResult<Self> {
+        let len = decode_string_len(stream, max_len)?;
 
         let mut content_buf = vec![0u8; len * 2];
-        stream.read_exact(&mut content_buf).context("Failed to read UTF16 string contents")?;
-
-        if content_buf.len() % 2 != 0 {
-            bail!("Uneven byte length content retrieved for UTF16 string");
-        }
+        stream.read_exact(&mut content_buf)?;
 
         let decoded_content = content_buf.chunks_exact(2)
             .map(|hilo| <[u8; 2]>::try_from(hilo).unwrap())
-            .map(|hilo| u16::from_be_bytes(hilo))
+            .map(u16::from_be_bytes)
             .collect::<Vec<_>>();
 
         let s = String::from_utf16_lossy(decoded_content.as_slice());
@@ -327,6 +360,33 @@ impl Decode for StringUtf16 {
     }
 }
 
+impl Decode for StringUtf16 {
+    fn decode_from<S: std::io::Read>(stream: &mut S) -> ProtocolResult<Self> {
+        Self::decode_limited(stream, DecodeLimits::WIRE_MAX_STRING_LEN)
+    }
+}
+
+fn decode_string_len<S: Read>(stream: &mut S, max_len: usize) -> ProtocolResult<usize> {
+    let len = Tagged::<i16>::decode_from(stream)?.into_inner();
+    if len < 0 {
+        return Err(ProtocolError::NegativeLength { 
+            what: "string length", 
+            value: len as i64 
+        });
+    }
+
+    let len = len as usize;
+    if len > max_len {
+        return Err(ProtocolError::LimitExceeded { 
+            what: "String length", 
+            actual: len, 
+            limit: max_len 
+        });
+    }
+
+    Ok(len)
+}
+
 impl std::fmt::Display for StringUtf16 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.0.fmt(f)
@@ -351,33 +411,55 @@ impl From<Cow<'_, str>> for StringUtf16 {
     }
 }
 
+/// Paths are sent as UTF16 strings. Any non-Unicode sequences get replaced.
+impl From<PathBuf> for StringUtf16 {
+    fn from(value: PathBuf) -> Self {
+        Self::from(value.to_string_lossy())
+    }
+}
 
 
-#[derive(Shrinkwrap, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct UnknownTag(pub [u8; 2]);
 
-impl From<[u8; 2]> for UnknownTag {
-    fn from(value: [u8; 2]) -> Self {
-        Self(value)
+/// Data behind a tag that was not recognized.
+/// 
+/// Its size is unknown, so it takes up all of the remaining bytes of the packet payload.
+/// Keeping them makes it possible to encode the packet back exactly as it was received.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UnknownData {
+    pub tag: [u8; 2],
+    pub bytes: Vec<u8>
+}
+
+impl UnknownData {
+    #[inline]
+    pub fn new(tag: [u8; 2], bytes: Vec<u8>) -> Self {
+        Self {
+            tag,
+            bytes
+        }
     }
 }
 
-impl Encode for UnknownTag {
-    fn encode_into<S: std::io::Write>(&self, stream: &mut S) -> anyhow::Result<()> {
-        stream.write_all(&self.0)?;
+impl Encode for UnknownData {
+    fn encode_into<S: std::io::Write>(&self, stream: &mut S) -> ProtocolResult<()> {
+        stream.write_all(&self.tag)?;
+        stream.write_all(&self.bytes)?;
         Ok(())
     }
 }
 
-impl ConstSizedEncode for UnknownTag {
-    const ENCODED_SIZE: usize = 2;
+impl DynSizedEncode for UnknownData {
+    fn encoded_size(&self) -> usize {
+        self.tag.len() + self.bytes.len()
+    }
 }
 
-// not implementing Decode as this is a de facto error type
+// not implementing Decode as the number of bytes is only known in the context of the whole packet
 
 
 
 #[derive(Shrinkwrap, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
 pub struct Tagged<T> {
     inner: T
 }
@@ -407,7 +489,7 @@ where T: EncodingTag {
 impl<T> Encode for Tagged<T>
 where T: Encode + EncodingTag {
     #[inline]
-    fn encode_into<S: std::io::Write>(&self, stream: &mut S) -> anyhow::Result<()> {
+    fn encode_into<S: std::io::Write>(&self, stream: &mut S) -> ProtocolResult<()> {
         stream.write_all(&T::ENCODING_TAG)?;
         self.inner.encode_into(stream)
     }
@@ -423,11 +505,14 @@ where T: DynSizedEncode + EncodingTag {
 
 impl<T> Decode for Tagged<T> 
 where T: Decode + EncodingTag {
-    fn decode_from<S: std::io::Read>(stream: &mut S) -> anyhow::Result<Self> {
+    fn decode_from<S: std::io::Read>(stream: &mut S) -> ProtocolResult<Self> {
         let mut buf = [0u8; 2];
-        stream.read_exact(&mut buf).context("Failed to read encoding tag")?;
+        stream.read_exact(&mut buf)?;
         if buf != T::ENCODING_TAG {
-            bail!("Invalid encoding tag");
+            return Err(ProtocolError::InvalidTag { 
+                expected: T::ENCODING_TAG, 
+                actual: buf 
+            });
         }
 
         let inner = T::decode_from(stream)?;
@@ -536,4 +621,36 @@ mod tests {
         assert_eq!(decoded, data);
         assert_eq!(bytes_read, 38);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn string_negative_length_decode_test() {
+        let mut bytes = VecDeque::new();
+        Tagged::new(-2i16).encode_into(&mut bytes).unwrap();
+        bytes.extend([0u8; 4]);
+
+        assert!(StringUtf8::decode_from(&mut bytes.clone()).is_err());
+        assert!(StringUtf16::decode_from(&mut bytes).is_err());
+    }
+
+    #[test]
+    fn string_length_limit_test() {
+        let data = StringUtf8::from("Gaderypoluki");
+        let mut bytes = VecDeque::new();
+        data.encode_into(&mut bytes).unwrap();
+
+        assert!(StringUtf8::decode_limited(&mut bytes.clone(), 11).is_err());
+        assert!(StringUtf8::decode_limited(&mut bytes, 12).is_ok());
+    }
+
+    #[test]
+    fn string_too_long_encode_test() {
+        let data = StringUtf8::from("a".repeat(i16::MAX as usize + 1));
+        assert!(data.encode_into(&mut Vec::new()).is_err());
+
+        let data = StringUtf16::from("ą".repeat(i16::MAX as usize + 1));
+        assert!(data.encode_into(&mut Vec::new()).is_err());
+
+        let data = StringUtf16::from("ą".repeat(i16::MAX as usize));
+        assert!(data.encode_into(&mut Vec::new()).is_ok());
+    }
+}
@@ -0,0 +1,125 @@
+use std::borrow::Cow;
+
+use thiserror::Error;
+
+
+/// Errors that can occur when encoding, decoding, assembling or disassembling packets.
+#[derive(Debug, Error)]
+pub enum ProtocolError {
+    #[error("Failed to read or write packet data")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid packet head: {0:02X?}")]
+    InvalidHead([u8; 2]),
+    #[error("Invalid packet tail: {0:02X?}")]
+    InvalidTail([u8; 2]),
+    #[error("Invalid packet size: {0}")]
+    InvalidSize(usize),
+
+    #[error("Invalid encoding tag, expected {} but got {}", tag_name(.expected), tag_name(.actual))]
+    InvalidTag {
+        expected: [u8; 2],
+        actual: [u8; 2]
+    },
+    #[error("Negative {what}: {value}")]
+    NegativeLength {
+        what: &'static str,
+        value: i64
+    },
+    #[error("{what} of {actual} exceeds the limit of {limit}")]
+    LimitExceeded {
+        what: &'static str,
+        actual: usize,
+        limit: usize
+    },
+
+    #[error("Type mismatch, expected {} but got {}", tag_name(.expected), tag_name(.actual))]
+    TypeMismatch {
+        expected: [u8; 2],
+        actual: [u8; 2]
+    },
+    #[error("Failed to match expected data, expected {expected} but got {actual}")]
+    UnexpectedValue {
+        expected: String,
+        actual: String
+    },
+    #[error("No more data found in the payload")]
+    MissingData,
+    #[error("Unknown {what}: {value}")]
+    UnknownVariant {
+        what: &'static str,
+        value: String
+    },
+
+    #[error("{count} unexpected data entries left in the payload starting at entry {index} (offset {offset})")]
+    TrailingData {
+        index: usize,
+        offset: usize,
+        count: usize
+    },
+
+    #[error("At payload entry {index} (offset {offset})")]
+    Position {
+        index: usize,
+        offset: usize,
+        source: Box<ProtocolError>
+    },
+    #[error("Field `{field}`")]
+    Field {
+        field: Cow<'static, str>,
+        source: Box<ProtocolError>
+    },
+    #[error("Invalid or unknown packet id")]
+    InvalidMessageId(#[source] Box<ProtocolError>),
+    #[error("Invalid or unknown packet body")]
+    InvalidMessageBody(#[source] Box<ProtocolError>),
+}
+
+impl ProtocolError {
+    /// Returns the innermost error, skipping over any context like field names
+    pub fn root_cause(&self) -> &ProtocolError {
+        match self {
+            ProtocolError::Field { source, .. } => source.root_cause(),
+            ProtocolError::Position { source, .. } => source.root_cause(),
+            ProtocolError::InvalidMessageId(source) => source.root_cause(),
+            ProtocolError::InvalidMessageBody(source) => source.root_cause(),
+            _ => self
+        }
+    }
+
+    /// Message of this error followed by messages of all errors that caused it
+    pub fn chain_message(&self) -> String {
+        let mut message = self.to_string();
+        let mut source = std::error::Error::source(self);
+        while let Some(err) = source {
+            message.push_str(": ");
+            message.push_str(&err.to_string());
+            source = err.source();
+        }
+        message
+    }
+}
+
+pub type ProtocolResult<T> = Result<T, ProtocolError>;
+
+
+/// Extension for attaching the name of the field that was being processed when an error occured
+pub trait FieldContext<T> {
+    fn field<S: Into<Cow<'static, str>>>(self, field: S) -> ProtocolResult<T>;
+}
+
+impl<T> FieldContext<T> for ProtocolResult<T> {
+    #[inline]
+    fn field<S: Into<Cow<'static, str>>>(self, field: S) -> ProtocolResult<T> {
+        self.map_err(|err| ProtocolError::Field {
+            field: field.into(),
+            source: Box::new(err)
+        })
+    }
+}
+
+
+fn tag_name(tag: &[u8; 2]) -> String {
+    let name = super::packet_data::WitcherPacketData::type_name_for_tag(*tag);
+    format!("{} {:02X?}", name.unwrap_or("Unknown"), tag)
+}
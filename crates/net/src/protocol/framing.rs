@@ -0,0 +1,391 @@
+use std::io::Read;
+
+use super::encoding::*;
+use super::error::{ProtocolError, ProtocolResult};
+use super::limits::DecodeLimits;
+use super::packet::WitcherPacket;
+
+
+/// Checks whether given bytes start with a whole packet frame.
+///
+/// Returns the size of the frame if all of its bytes are present, `None` if more bytes are needed
+/// or an error if the beginning of the buffer doesn't look like a packet at all or declares a size bigger than `max_packet_size`.
+pub fn probe_frame_size(buf: &[u8], max_packet_size: usize) -> ProtocolResult<Option<usize>> {
+    if buf.len() < WitcherPacket::HEAD.len() + u16::ENCODED_SIZE {
+        return Ok(None);
+    }
+
+    if buf[0..2] != WitcherPacket::HEAD {
+        return Err(ProtocolError::InvalidHead([buf[0], buf[1]]));
+    }
+
+    let frame_size = u16::from_be_bytes([buf[2], buf[3]]) as usize;
+    if frame_size < WitcherPacket::min_encoded_size() {
+        return Err(ProtocolError::InvalidSize(frame_size));
+    }
+    if frame_size > max_packet_size {
+        return Err(ProtocolError::LimitExceeded { 
+            what: "Packet size", 
+            actual: frame_size, 
+            limit: max_packet_size 
+        });
+    }
+
+    if buf.len() < frame_size {
+        Ok(None)
+    } else {
+        Ok(Some(frame_size))
+    }
+}
+
+
+/// Bytes that had to be thrown away to get the stream back in sync.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedData {
+    pub bytes: Vec<u8>,
+    pub reason: SkipReason
+}
+
+/// Why [`SkippedData`] had to be thrown away.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    /// Data didn't start with [`WitcherPacket::HEAD`]
+    InvalidHead,
+    /// Declared packet size was smaller than the smallest possible packet or bigger than allowed by [`DecodeLimits`]
+    InvalidSize,
+    /// There was no [`WitcherPacket::TAIL`] where declared packet size said it would be
+    InvalidTail,
+    /// Frame had correct boundaries, but its payload could not be decoded
+    MalformedPacket(String),
+    /// Packet was decoded, but could not be interpreted as the message it was identified as
+    MalformedMessage(String)
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkipReason::InvalidHead => write!(f, "Invalid packet head"),
+            SkipReason::InvalidSize => write!(f, "Invalid packet size"),
+            SkipReason::InvalidTail => write!(f, "Invalid packet tail"),
+            SkipReason::MalformedPacket(err) => write!(f, "Malformed packet: {}", err),
+            SkipReason::MalformedMessage(err) => write!(f, "Malformed message: {}", err),
+        }
+    }
+}
+
+
+/// Buffer accumulating bytes read from a stream, which are then split into whole packet frames.
+///
+/// Reading straight from the socket can be interrupted at any moment, e.g. by a read timeout.
+/// Bytes that were read until then are kept here and the next read continues where the last one left off,
+/// so the stream never goes out of sync. Packets are decoded only after all bytes declared in their header have been received.
+#[derive(Debug, Clone, Default)]
+pub struct WitcherPacketFramer {
+    buf: Vec<u8>,
+    limits: DecodeLimits
+}
+
+impl WitcherPacketFramer {
+    const READ_CHUNK_SIZE: usize = 4096;
+
+
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_limits(DecodeLimits::default())
+    }
+
+    #[inline]
+    pub fn with_limits(limits: DecodeLimits) -> Self {
+        Self {
+            buf: Vec::new(),
+            limits
+        }
+    }
+
+    #[inline]
+    pub fn limits(&self) -> &DecodeLimits {
+        &self.limits
+    }
+
+    #[inline]
+    pub fn set_limits(&mut self, limits: DecodeLimits) {
+        self.limits = limits;
+    }
+
+    /// Does a single read call on the stream and stores whatever has been read.
+    /// Returns the number of bytes read, 0 meaning the stream has reached its end.
+    ///
+    /// If reading fails, e.g. because of a timeout, the data received before is not lost.
+    pub fn fill_from<S: Read>(&mut self, stream: &mut S) -> std::io::Result<usize> {
+        let mut chunk = [0u8; Self::READ_CHUNK_SIZE];
+        let bytes_read = stream.read(&mut chunk)?;
+        self.buf.extend_from_slice(&chunk[..bytes_read]);
+        Ok(bytes_read)
+    }
+
+    #[inline]
+    pub fn extend_from_slice(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    /// Number of bytes received, but not yet returned as a part of a packet.
+    #[inline]
+    pub fn buffered_len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Whether there is a whole frame ready to be taken from the buffer.
+    #[inline]
+    pub fn has_frame(&self) -> bool {
+        matches!(probe_frame_size(&self.buf, self.limits.max_packet_size), Ok(Some(_)))
+    }
+
+    /// Takes out bytes of the next whole frame if it has already been received.
+    pub fn next_frame(&mut self) -> ProtocolResult<Option<Vec<u8>>> {
+        match probe_frame_size(&self.buf, self.limits.max_packet_size)? {
+            Some(frame_size) => {
+                let frame = self.buf.drain(..frame_size).collect();
+                Ok(Some(frame))
+            },
+            None => Ok(None)
+        }
+    }
+
+    /// Takes out and decodes the next packet if all of its bytes have already been received.
+    ///
+    /// Bytes of the frame are removed from the buffer even if the packet fails to decode,
+    /// so it is safe to continue reading afterwards.
+    pub fn next_packet(&mut self) -> ProtocolResult<Option<WitcherPacket>> {
+        match self.next_frame()? {
+            Some(frame) => {
+                let packet = WitcherPacket::decode_limited(&mut frame.as_slice(), &self.limits)?;
+                Ok(Some(packet))
+            },
+            None => Ok(None)
+        }
+    }
+
+    /// Same as [`Self::next_packet`], but instead of failing on invalid data it throws it away to get back to a correct packet.
+    /// If the packet size can be trusted, the whole frame is skipped. Otherwise data gets skipped until the next [`WitcherPacket::HEAD`].
+    /// 
+    /// Every chunk of data thrown away is passed to `on_skip`.
+    pub fn next_packet_recovering<F>(&mut self, on_skip: F) -> Option<WitcherPacket>
+    where F: FnMut(SkippedData) {
+        self.next_frame_recovering(on_skip).map(|(packet, _)| packet)
+    }
+
+    /// Same as [`Self::next_packet_recovering`], but also gives back the bytes of the frame the packet was decoded from.
+    pub fn next_frame_recovering<F>(&mut self, mut on_skip: F) -> Option<(WitcherPacket, Vec<u8>)>
+    where F: FnMut(SkippedData) {
+        loop {
+            if self.buf.len() >= WitcherPacket::HEAD.len() && self.buf[0..2] != WitcherPacket::HEAD {
+                self.skip_to_next_head(SkipReason::InvalidHead, &mut on_skip);
+                continue;
+            }
+
+            match probe_frame_size(&self.buf, self.limits.max_packet_size) {
+                Ok(Some(frame_size)) => {
+                    if self.buf[frame_size - 2..frame_size] != WitcherPacket::TAIL {
+                        self.skip_to_next_head(SkipReason::InvalidTail, &mut on_skip);
+                        continue;
+                    }
+
+                    let frame: Vec<u8> = self.buf.drain(..frame_size).collect();
+                    match WitcherPacket::decode_limited(&mut frame.as_slice(), &self.limits) {
+                        Ok(packet) => return Some((packet, frame)),
+                        Err(err) => on_skip(SkippedData {
+                            bytes: frame,
+                            reason: SkipReason::MalformedPacket(err.chain_message())
+                        })
+                    }
+                },
+                Ok(None) => return None,
+                Err(_) => {
+                    // head was already checked, so it's the size that's wrong
+                    self.skip_to_next_head(SkipReason::InvalidSize, &mut on_skip);
+                }
+            }
+        }
+    }
+
+    /// Throws away data at least up to the first byte and until the next packet head.
+    fn skip_to_next_head<F>(&mut self, reason: SkipReason, on_skip: &mut F)
+    where F: FnMut(SkippedData) {
+        let skip_len = self.buf[1..].windows(WitcherPacket::HEAD.len())
+            .position(|w| w == WitcherPacket::HEAD)
+            .map(|pos| pos + 1)
+            .unwrap_or_else(|| {
+                // the first byte of the head may have been received without the second one yet
+                if self.buf.len() > 1 && self.buf[self.buf.len() - 1] == WitcherPacket::HEAD[0] {
+                    self.buf.len() - 1
+                } else {
+                    self.buf.len()
+                }
+            });
+
+        let bytes = self.buf.drain(..skip_len).collect();
+        on_skip(SkippedData {
+            bytes,
+            reason
+        });
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use crate::protocol::*;
+
+
+    /// Yields data in small chunks and times out every other read
+    struct ChoppyStream {
+        data: VecDeque<u8>,
+        chunk_size: usize,
+        time_out_next: bool
+    }
+
+    impl std::io::Read for ChoppyStream {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.time_out_next = !self.time_out_next;
+            if !self.time_out_next {
+                return Err(std::io::ErrorKind::TimedOut.into());
+            }
+
+            let n = self.chunk_size.min(buf.len()).min(self.data.len());
+            for b in buf.iter_mut().take(n) {
+                *b = self.data.pop_front().unwrap();
+            }
+            Ok(n)
+        }
+    }
+
+
+    #[test]
+    fn framer_survives_timeouts_test() {
+        let packet1 = WitcherPacketAssembler::new()
+            .string_utf8("Config")
+            .int32(0xCC00CC)
+            .string_utf8("vars")
+            .string_utf8("A reasonably long value that will surely get chopped up")
+            .finish();
+        let packet2 = WitcherPacketAssembler::new()
+            .string_utf16("Zażółć gęślą jaźń")
+            .finish();
+
+        let mut data = VecDeque::new();
+        packet1.encode_into(&mut data).unwrap();
+        packet2.encode_into(&mut data).unwrap();
+
+        let mut stream = ChoppyStream {
+            data,
+            chunk_size: 5,
+            time_out_next: false
+        };
+
+        let mut framer = WitcherPacketFramer::new();
+        let mut packets = Vec::new();
+        loop {
+            match framer.fill_from(&mut stream) {
+                Ok(0) => break,
+                Ok(_) => {},
+                Err(err) => assert_eq!(err.kind(), std::io::ErrorKind::TimedOut)
+            }
+
+            while let Some(packet) = framer.next_packet().unwrap() {
+                packets.push(packet);
+            }
+        }
+
+        assert_eq!(packets, vec![packet1, packet2]);
+        assert_eq!(framer.buffered_len(), 0);
+    }
+
+    #[test]
+    fn framer_recovery_test() {
+        let packet = WitcherPacketAssembler::new()
+            .string_utf8("ScriptCompiler")
+            .string_utf8("log")
+            .finish();
+
+        let mut packet_bytes = Vec::new();
+        packet.encode_into(&mut packet_bytes).unwrap();
+
+        let mut bad_len_bytes = packet_bytes.clone();
+        // make the length of the second string negative: head, size, first string's tag, tagged length and content,
+        // then second string's tag and the tag of its length
+        let second_len_pos = 2 + 2 + 2 + 4 + "ScriptCompiler".len() + 2 + 2;
+        bad_len_bytes[second_len_pos..second_len_pos + 2].copy_from_slice(&[0xFF, 0xFF]);
+
+        let mut bad_tail_bytes = packet_bytes.clone();
+        let tail_pos = bad_tail_bytes.len() - 2;
+        bad_tail_bytes[tail_pos..].copy_from_slice(&[0x00, 0x00]);
+
+        let mut framer = WitcherPacketFramer::new();
+        framer.extend_from_slice(&[0x01, 0x02, 0x03]);
+        framer.extend_from_slice(&packet_bytes);
+        framer.extend_from_slice(&bad_len_bytes);
+        framer.extend_from_slice(&packet_bytes);
+        framer.extend_from_slice(&bad_tail_bytes);
+        framer.extend_from_slice(&packet_bytes);
+        framer.extend_from_slice(&[0xDE, 0xAD, 0x00, 0x01]);
+        framer.extend_from_slice(&packet_bytes);
+
+        let mut skipped = Vec::new();
+        let mut packets = Vec::new();
+        while let Some(packet) = framer.next_packet_recovering(|s| skipped.push(s)) {
+            packets.push(packet);
+        }
+
+        assert_eq!(packets, vec![packet.clone(), packet.clone(), packet.clone(), packet]);
+        assert_eq!(skipped.len(), 4);
+        assert_eq!(skipped[0], SkippedData { bytes: vec![0x01, 0x02, 0x03], reason: SkipReason::InvalidHead });
+        assert!(matches!(skipped[1].reason, SkipReason::MalformedPacket(_)));
+        assert_eq!(skipped[1].bytes, bad_len_bytes);
+        assert_eq!(skipped[2], SkippedData { bytes: bad_tail_bytes, reason: SkipReason::InvalidTail });
+        assert_eq!(skipped[3], SkippedData { bytes: vec![0xDE, 0xAD, 0x00, 0x01], reason: SkipReason::InvalidSize });
+        assert_eq!(framer.buffered_len(), 0);
+    }
+
+    #[test]
+    fn framer_keeps_frame_bytes_test() {
+        let packet = WitcherPacketAssembler::new()
+            .string_utf8("ab")
+            .finish();
+
+        let mut bytes = Vec::new();
+        packet.encode_into(&mut bytes).unwrap();
+        // invalid UTF-8 is decoded lossily and would take more bytes if encoded again
+        let content_pos = bytes.len() - 2 - 2;
+        bytes[content_pos..content_pos + 2].copy_from_slice(&[0xFF, 0xFE]);
+
+        let mut framer = WitcherPacketFramer::new();
+        framer.extend_from_slice(&bytes);
+        let (packet, frame) = framer.next_frame_recovering(|_| panic!("nothing should be skipped")).unwrap();
+        assert_eq!(packet.payload()[0], WitcherPacketData::new_string_utf8("\u{FFFD}\u{FFFD}"));
+        assert_eq!(frame, bytes);
+    }
+
+    #[test]
+    fn framer_waits_for_whole_frame_test() {
+        let packet = WitcherPacketAssembler::new()
+            .string_utf8("ScriptCompiler")
+            .finish();
+
+        let mut bytes = Vec::new();
+        packet.encode_into(&mut bytes).unwrap();
+
+        let mut framer = WitcherPacketFramer::new();
+        framer.extend_from_slice(&bytes[..bytes.len() - 1]);
+        assert!(!framer.has_frame());
+        assert!(framer.next_packet().unwrap().is_none());
+
+        framer.extend_from_slice(&bytes[bytes.len() - 1..]);
+        assert!(framer.has_frame());
+        assert_eq!(framer.next_packet().unwrap(), Some(packet));
+    }
+}
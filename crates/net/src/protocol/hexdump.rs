@@ -0,0 +1,259 @@
+use std::borrow::Cow;
+
+use super::encoding::*;
+use super::packet::WitcherPacket;
+use super::packet_data::WitcherPacketData;
+use super::packet_ref::WitcherPacketRef;
+
+
+/// Displays raw bytes of a packet with annotations saying what each part of them means,
+/// see [`WitcherPacketRef::hexdump`] and [`WitcherPacket::hexdump`].
+///
+/// ```text
+/// WitcherPacket, 40 bytes, ScriptsRootPath
+/// 0000  DE AD                     head
+/// 0002  00 28                     size: 40
+/// 0004  AC 08                     [0] tag: StringUTF8
+/// 0006  81 16 00 0E               [0] length: 14
+/// 000A  53 63 72 69 70 74 43 6F   [0] content: utf8"ScriptCompiler"
+/// 0012  6D 70 69 6C 65 72
+/// ...
+/// 0026  BE EF                     tail
+/// ```
+#[derive(Debug, Clone)]
+pub struct PacketHexdump<'a> {
+    source: HexdumpSource<'a>,
+    message_name: Option<Cow<'a, str>>
+}
+
+#[derive(Debug, Clone)]
+enum HexdumpSource<'a> {
+    Packet(&'a WitcherPacket),
+    Frame(WitcherPacketRef<'a>)
+}
+
+impl WitcherPacket {
+    /// Returns an object which displays the packet as an annotated hexdump of the bytes it would be encoded into.
+    /// The packet is rendered even if it's too big to be encoded.
+    /// 
+    /// A decoded packet doesn't always encode back into the bytes it was received as, e.g. when it had invalid strings.
+    /// Use [`WitcherPacketRef::hexdump`] to show received data.
+    #[inline]
+    pub fn hexdump(&self) -> PacketHexdump<'_> {
+        PacketHexdump {
+            source: HexdumpSource::Packet(self),
+            message_name: None
+        }
+    }
+}
+
+impl<'a> WitcherPacketRef<'a> {
+    /// Returns an object which displays bytes of the frame exactly as they are as an annotated hexdump.
+    /// If the payload is malformed, everything starting from the data that couldn't be decoded is shown without annotations.
+    #[inline]
+    pub fn hexdump(&self) -> PacketHexdump<'a> {
+        PacketHexdump {
+            source: HexdumpSource::Frame(*self),
+            message_name: None
+        }
+    }
+}
+
+impl<'a> PacketHexdump<'a> {
+    const BYTES_PER_ROW: usize = 8;
+
+    /// Name of the message the packet was identified as, shown in the header
+    pub fn with_message_name<S: Into<Cow<'a, str>>>(mut self, name: S) -> Self {
+        self.message_name = Some(name.into());
+        self
+    }
+
+    fn write_segment(&self, f: &mut std::fmt::Formatter<'_>, offset: &mut usize, bytes: &[u8], annotation: &str) -> std::fmt::Result {
+        for (i, row) in bytes.chunks(Self::BYTES_PER_ROW).enumerate() {
+            write!(f, "{:04X}  ", *offset + i * Self::BYTES_PER_ROW)?;
+
+            let hex = row.iter()
+                .map(|b| format!("{:02X}", b))
+                .collect::<Vec<_>>()
+                .join(" ");
+            if i == 0 && !annotation.is_empty() {
+                writeln!(f, "{:w$}   {}", hex, annotation, w = Self::BYTES_PER_ROW * 3 - 1)?;
+            } else {
+                writeln!(f, "{}", hex)?;
+            }
+        }
+
+        *offset += bytes.len();
+        Ok(())
+    }
+
+    fn write_header(&self, f: &mut std::fmt::Formatter<'_>, size: usize) -> std::fmt::Result {
+        write!(f, "WitcherPacket, {} bytes", size)?;
+        if let Some(name) = &self.message_name {
+            write!(f, ", {}", name)?;
+        }
+        writeln!(f)
+    }
+
+    /// Writes encoded bytes of a single piece of payload data
+    fn write_data(&self, f: &mut std::fmt::Formatter<'_>, offset: &mut usize, i: usize, data: &WitcherPacketData, bytes: &[u8]) -> std::fmt::Result {
+        let (tag, rest) = bytes.split_at(2);
+        self.write_segment(f, offset, tag, &format!("[{}] tag: {}", i, type_name(data)))?;
+
+        let content = match data {
+            WitcherPacketData::StringUTF8(_) | WitcherPacketData::StringUTF16(_) => {
+                // length is written as tagged int16
+                let (len, content) = rest.split_at(4);
+                let len_value = i16::from_be_bytes([len[2], len[3]]);
+                self.write_segment(f, offset, len, &format!("[{}] length: {}", i, len_value))?;
+                content
+            }
+            _ => rest
+        };
+
+        if content.is_empty() {
+            return Ok(());
+        }
+        let annotation = match data {
+            WitcherPacketData::Unknown(_) => format!("[{}] content: unknown", i),
+            _ => format!("[{}] content: {}", i, data.notation())
+        };
+        self.write_segment(f, offset, content, &annotation)
+    }
+
+    fn fmt_frame(&self, f: &mut std::fmt::Formatter<'_>, packet: &WitcherPacketRef<'_>) -> std::fmt::Result {
+        let frame = packet.frame();
+        self.write_header(f, frame.len())?;
+
+        let mut offset = 0;
+        self.write_segment(f, &mut offset, &frame[..2], "head")?;
+        let size = u16::from_be_bytes([frame[2], frame[3]]);
+        self.write_segment(f, &mut offset, &frame[2..4], &format!("size: {}", size))?;
+
+        let payload = packet.payload_bytes();
+        let mut iter = packet.iter();
+        let mut pos = 0;
+        for i in 0.. {
+            match iter.next() {
+                Some(Ok(data)) => {
+                    let len = payload.len() - pos - iter.remaining_len();
+                    self.write_data(f, &mut offset, i, &data.to_owned(), &payload[pos..pos + len])?;
+                    pos += len;
+                },
+                Some(Err(err)) => {
+                    self.write_segment(f, &mut offset, &payload[pos..], &format!("[{}] malformed: {}", i, err.root_cause()))?;
+                    break;
+                },
+                None => break
+            }
+        }
+
+        self.write_segment(f, &mut offset, &frame[frame.len() - 2..], "tail")
+    }
+
+    fn fmt_packet(&self, f: &mut std::fmt::Formatter<'_>, packet: &WitcherPacket) -> std::fmt::Result {
+        let size = packet.encoded_size();
+        self.write_header(f, size)?;
+
+        let mut offset = 0;
+        self.write_segment(f, &mut offset, &WitcherPacket::HEAD, "head")?;
+        let size_bytes = u16::try_from(size).unwrap_or(u16::MAX).to_be_bytes();
+        let size_annotation = if size > u16::MAX as usize {
+            format!("size: {} (too big to be encoded)", size)
+        } else {
+            format!("size: {}", size)
+        };
+        self.write_segment(f, &mut offset, &size_bytes, &size_annotation)?;
+
+        for (i, data) in packet.payload().iter().enumerate() {
+            let mut bytes = Vec::with_capacity(data.encoded_size());
+            if data.encode_into(&mut bytes).is_err() {
+                // data too big for the length prefix, only the tag can be shown
+                self.write_segment(f, &mut offset, &data.tag(), &format!("[{}] tag: {}, data can't be encoded", i, type_name(data)))?;
+                offset += data.encoded_size() - 2;
+                continue;
+            }
+
+            self.write_data(f, &mut offset, i, data, &bytes)?;
+        }
+
+        self.write_segment(f, &mut offset, &WitcherPacket::TAIL, "tail")
+    }
+}
+
+impl std::fmt::Display for PacketHexdump<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.source {
+            HexdumpSource::Packet(packet) => self.fmt_packet(f, packet),
+            HexdumpSource::Frame(packet) => self.fmt_frame(f, packet)
+        }
+    }
+}
+
+fn type_name(data: &WitcherPacketData) -> &'static str {
+    WitcherPacketData::type_name_for_tag(data.tag()).unwrap_or("Unknown")
+}
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::*;
+
+
+    #[test]
+    fn packet_hexdump_test() {
+        let packet = WitcherPacketAssembler::new()
+            .string_utf8("Remote")
+            .int32(0x12345678)
+            .string_utf16("")
+            .finish();
+
+        let dump = packet.hexdump().with_message_name("Test").to_string();
+        let lines: Vec<_> = dump.lines().collect();
+        assert_eq!(lines, vec![
+            "WitcherPacket, 30 bytes, Test",
+            "0000  DE AD                     head",
+            "0002  00 1E                     size: 30",
+            "0004  AC 08                     [0] tag: StringUTF8",
+            "0006  81 16 00 06               [0] length: 6",
+            "000A  52 65 6D 6F 74 65         [0] content: utf8\"Remote\"",
+            "0010  81 32                     [1] tag: Int32",
+            "0012  12 34 56 78               [1] content: i32:305419896",
+            "0016  9C 16                     [2] tag: StringUTF16",
+            "0018  81 16 00 00               [2] length: 0",
+            "001C  BE EF                     tail",
+        ]);
+    }
+
+    #[test]
+    fn frame_hexdump_test() {
+        let packet = WitcherPacketAssembler::new()
+            .string_utf8("ab")
+            .int32(5)
+            .finish();
+
+        let mut bytes = Vec::new();
+        packet.encode_into(&mut bytes).unwrap();
+        // invalid UTF-8 which would be encoded differently after decoding
+        bytes[10..12].copy_from_slice(&[0xFF, 0xFE]);
+        // int32 turned into a string without a valid length
+        bytes[12..14].copy_from_slice(&[0x9C, 0x16]);
+
+        let frame = WitcherPacketRef::new(&bytes).unwrap();
+        let dump = frame.hexdump().to_string();
+        let lines: Vec<_> = dump.lines().collect();
+        assert_eq!(lines, vec![
+            "WitcherPacket, 20 bytes",
+            "0000  DE AD                     head",
+            "0002  00 14                     size: 20",
+            "0004  AC 08                     [0] tag: StringUTF8",
+            "0006  81 16 00 02               [0] length: 2",
+            "000A  FF FE                     [0] content: utf8\"\u{FFFD}\u{FFFD}\"",
+            "000C  9C 16 00 00 00 05         [1] malformed: Invalid encoding tag, expected Int16 [81, 16] but got Unknown [00, 00]",
+            "0012  BE EF                     tail",
+        ]);
+    }
+}
@@ -0,0 +1,35 @@
+use super::packet::WitcherPacket;
+
+
+/// Upper bounds for data accepted when decoding packets.
+///
+/// Defaults are the maximums allowed by the wire format itself.
+/// Lower them to protect against peers that send absurdly big or malformed data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeLimits {
+    /// Maximum size of a whole packet in bytes, including its head, size and tail
+    pub max_packet_size: usize,
+    /// Maximum length of a single string, in bytes for UTF8 and in code units for UTF16
+    pub max_string_len: usize,
+    /// Maximum number of data entries in a single packet's payload
+    pub max_payload_len: usize
+}
+
+impl DecodeLimits {
+    /// Packet size is encoded as u16
+    pub const WIRE_MAX_PACKET_SIZE: usize = u16::MAX as usize;
+    /// String lengths are encoded as i16
+    pub const WIRE_MAX_STRING_LEN: usize = i16::MAX as usize;
+    /// Every data entry takes at least 3 bytes (tag and the smallest value)
+    pub const WIRE_MAX_PAYLOAD_LEN: usize = (Self::WIRE_MAX_PACKET_SIZE - WitcherPacket::min_encoded_size()) / 3;
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        Self {
+            max_packet_size: Self::WIRE_MAX_PACKET_SIZE,
+            max_string_len: Self::WIRE_MAX_STRING_LEN,
+            max_payload_len: Self::WIRE_MAX_PAYLOAD_LEN
+        }
+    }
+}
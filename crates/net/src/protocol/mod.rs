@@ -1,3 +1,6 @@
+mod error;
+pub use error::*;
+
 mod encoding;
 pub use encoding::*;
 
@@ -7,5 +10,29 @@ pub use packet_data::*;
 mod packet;
 pub use packet::*;
 
+mod notation;
+pub use notation::*;
+
+mod hexdump;
+pub use hexdump::*;
+
+mod packet_ref;
+pub use packet_ref::*;
+
 mod packet_assembly;
-pub use packet_assembly::*;
\ No newline at end of file
+pub use packet_assembly::*;
+pub use rw3d_net_derive::{AssemblePayload, DisassemblePayload};
+
+mod framing;
+pub use framing::*;
+
+mod limits;
+pub use limits::*;
+
+#[cfg(feature = "serde")]
+mod serialization;
+
+#[cfg(feature = "tokio")]
+mod codec;
+#[cfg(feature = "tokio")]
+pub use codec::*;
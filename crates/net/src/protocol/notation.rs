@@ -0,0 +1,312 @@
+use std::{fmt::Write, str::FromStr};
+
+use thiserror::Error;
+
+use super::encoding::*;
+use super::packet::WitcherPacket;
+use super::packet_data::WitcherPacketData;
+
+
+// Text notation of packet data, one token per piece of data separated by whitespace, e.g.
+// `utf8"Remote" i32:0x12345678 utf16"foo" i8:1 unknown:AB01:00ff`
+
+
+/// Text could not be parsed as packet notation
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Invalid packet notation at position {position}: {reason}")]
+pub struct NotationError {
+    /// Byte offset in the text where the problem was found
+    pub position: usize,
+    pub reason: String
+}
+
+
+/// Displays a piece of data in the packet notation, see [`WitcherPacketData::notation`]
+#[derive(Debug, Clone, Copy)]
+pub struct DataNotation<'a>(&'a WitcherPacketData);
+
+impl WitcherPacketData {
+    /// Returns an object which displays the data in the notation that can be parsed back with [`FromStr`].
+    /// [`WitcherPacket`]'s [`Display`](std::fmt::Display) uses the same notation.
+    ///
+    /// Integers are written in decimal, but hexadecimal numbers like `i32:0x12345678` are also accepted when parsing.
+    #[inline]
+    pub fn notation(&self) -> DataNotation<'_> {
+        DataNotation(self)
+    }
+}
+
+impl std::fmt::Display for DataNotation<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            WitcherPacketData::Int8(d) => write!(f, "i8:{}", **d),
+            WitcherPacketData::Int16(d) => write!(f, "i16:{}", **d),
+            WitcherPacketData::Int32(d) => write!(f, "i32:{}", **d),
+            WitcherPacketData::Uint32(d) => write!(f, "u32:{}", **d),
+            WitcherPacketData::Int64(d) => write!(f, "i64:{}", **d),
+            WitcherPacketData::StringUTF8(d) => write_string(f, "utf8", &d.0),
+            WitcherPacketData::StringUTF16(d) => write_string(f, "utf16", &d.0),
+            WitcherPacketData::Unknown(d) => {
+                write!(f, "unknown:{:02X}{:02X}:", d.tag[0], d.tag[1])?;
+                d.bytes.iter().try_for_each(|b| write!(f, "{:02x}", b))
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut std::fmt::Formatter<'_>, prefix: &str, s: &str) -> std::fmt::Result {
+    f.write_str(prefix)?;
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c.is_control() => write!(f, "\\u{{{:x}}}", c as u32)?,
+            c => f.write_char(c)?
+        }
+    }
+    f.write_char('"')
+}
+
+
+impl FromStr for WitcherPacketData {
+    type Err = NotationError;
+
+    /// Parses a single piece of data written in the notation described in [`WitcherPacketData::notation`]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s);
+        parser.skip_whitespace();
+        let data = parser.data()?;
+        parser.skip_whitespace();
+        if !parser.is_done() {
+            return Err(parser.error("expected the end of the text"));
+        }
+        Ok(data)
+    }
+}
+
+impl FromStr for WitcherPacket {
+    type Err = NotationError;
+
+    /// Parses whitespace separated payload data written in the notation described in [`WitcherPacketData::notation`]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s);
+        let mut payload = Vec::new();
+        loop {
+            parser.skip_whitespace();
+            if parser.is_done() {
+                break;
+            }
+            payload.push(parser.data()?);
+
+            if !parser.is_done() && !parser.rest().starts_with(char::is_whitespace) {
+                return Err(parser.error("expected whitespace between data"));
+            }
+        }
+
+        Ok(WitcherPacket::from_payload(payload))
+    }
+}
+
+
+/// Integers written in hexadecimal are parsed as bit patterns, so e.g. `i8:0xff` is -1
+macro_rules! parse_int {
+    ($value:expr, $t:ty, $unsigned:ty) => {
+        match hex_digits($value) {
+            Some(hex) => <$unsigned>::from_str_radix(hex, 16).map(|n| n as $t),
+            None => $value.parse::<$t>()
+        }.map_err(|err| err.to_string())
+    };
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { text, pos: 0 }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn is_done(&self) -> bool {
+        self.pos >= self.text.len()
+    }
+
+    fn error<S: Into<String>>(&self, reason: S) -> NotationError {
+        self.error_at(self.pos, reason)
+    }
+
+    fn error_at<S: Into<String>>(&self, position: usize, reason: S) -> NotationError {
+        NotationError { position, reason: reason.into() }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// Takes characters while the predicate holds
+    fn take_while<F: Fn(char) -> bool>(&mut self, pred: F) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|c| !pred(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.rest().chars().next()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn data(&mut self) -> Result<WitcherPacketData, NotationError> {
+        let start = self.pos;
+        let kind = self.take_while(|c| c.is_ascii_alphanumeric());
+
+        match self.next_char() {
+            Some('"') => {
+                let s = self.string_literal()?;
+                match kind {
+                    "utf8" => Ok(WitcherPacketData::new_string_utf8(s)),
+                    "utf16" => Ok(WitcherPacketData::new_string_utf16(s)),
+                    _ => Err(self.error_at(start, format!("unknown string type `{}`", kind)))
+                }
+            }
+            Some(':') => {
+                let value_start = self.pos;
+                let value = self.take_while(|c| !c.is_whitespace());
+                self.value(kind, value)
+                    .map_err(|reason| self.error_at(value_start, reason))
+            }
+            _ => Err(self.error_at(start, "expected a type followed by `:` or a string literal"))
+        }
+    }
+
+    fn string_literal(&mut self) -> Result<String, NotationError> {
+        let mut s = String::new();
+        loop {
+            let escape_start = self.pos;
+            match self.next_char() {
+                None => return Err(self.error("unterminated string literal")),
+                Some('"') => return Ok(s),
+                Some('\\') => match self.next_char() {
+                    Some('"') => s.push('"'),
+                    Some('\\') => s.push('\\'),
+                    Some('n') => s.push('\n'),
+                    Some('r') => s.push('\r'),
+                    Some('t') => s.push('\t'),
+                    Some('u') => {
+                        let code = self.rest().strip_prefix('{')
+                            .and_then(|r| r.split_once('}'))
+                            .map(|(code, _)| code)
+                            .ok_or_else(|| self.error_at(escape_start, "expected a unicode escape like \\u{1F}"))?;
+                        let c = u32::from_str_radix(code, 16).ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| self.error_at(escape_start, "invalid unicode escape"))?;
+                        s.push(c);
+                        self.pos += code.len() + 2;
+                    }
+                    _ => return Err(self.error_at(escape_start, "unknown escape sequence"))
+                },
+                Some(c) => s.push(c)
+            }
+        }
+    }
+
+    fn value(&self, kind: &str, value: &str) -> Result<WitcherPacketData, String> {
+        let data = match kind {
+            "i8" => WitcherPacketData::new_int8(parse_int!(value, i8, u8)?),
+            "i16" => WitcherPacketData::new_int16(parse_int!(value, i16, u16)?),
+            "i32" => WitcherPacketData::new_int32(parse_int!(value, i32, u32)?),
+            "u32" => WitcherPacketData::new_uint32(parse_int!(value, u32, u32)?),
+            "i64" => WitcherPacketData::new_int64(parse_int!(value, i64, u64)?),
+            "unknown" => {
+                let (tag, bytes) = value.split_once(':')
+                    .ok_or("expected unknown data like AB01:00ff")?;
+                let tag = parse_hex_bytes(tag)?;
+                let tag: [u8; 2] = tag.try_into().map_err(|_| "tag must be 2 bytes long")?;
+                WitcherPacketData::new_unknown(UnknownData { tag, bytes: parse_hex_bytes(bytes)? })
+            }
+            _ => return Err(format!("unknown type `{}`", kind))
+        };
+
+        Ok(data)
+    }
+}
+
+fn hex_digits(value: &str) -> Option<&str> {
+    value.strip_prefix("0x").or_else(|| value.strip_prefix("0X"))
+}
+
+fn parse_hex_bytes(s: &str) -> Result<Vec<u8>, String> {
+    if !s.len().is_multiple_of(2) || !s.is_ascii() {
+        return Err(format!("`{}` is not a valid sequence of hex bytes", s));
+    }
+
+    (0..s.len()).step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).map_err(|err| err.to_string()))
+        .collect()
+}
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::*;
+
+
+    #[test]
+    fn packet_notation_round_trip_test() {
+        let packet = WitcherPacketAssembler::new()
+            .string_utf8("Remote")
+            .int32(0x12345678)
+            .string_utf16("foo \"bar\"\n\u{1}")
+            .int8(-1)
+            .int16(-300)
+            .uint32(u32::MAX)
+            .int64(i64::MIN)
+            .finish();
+
+        let text = packet.to_string();
+        assert!(text.starts_with(r#"utf8"Remote" i32:305419896 utf16"foo \"bar\"\n\u{1}" i8:-1"#));
+        assert_eq!(text.parse::<WitcherPacket>().unwrap(), packet);
+
+        let mut payload = packet.into_payload();
+        payload.push(WitcherPacketData::new_unknown(UnknownData { tag: [0xAB, 0x01], bytes: vec![0, 0xff] }));
+        let packet = WitcherPacket::from_payload(payload);
+        assert!(packet.to_string().ends_with("unknown:AB01:00ff"));
+        assert_eq!(packet.to_string().parse::<WitcherPacket>().unwrap(), packet);
+    }
+
+    #[test]
+    fn packet_notation_parse_test() {
+        let packet: WitcherPacket = r#"  utf8"Remote"   i32:0x12345678 utf16"foo" i8:0xff "#.parse().unwrap();
+        assert_eq!(packet, WitcherPacketAssembler::new()
+            .string_utf8("Remote")
+            .int32(0x12345678)
+            .string_utf16("foo")
+            .int8(-1)
+            .finish());
+
+        assert_eq!("".parse::<WitcherPacket>().unwrap(), WitcherPacket::new());
+        assert_eq!("u32:7".parse::<WitcherPacketData>().unwrap(), WitcherPacketData::new_uint32(7));
+
+        assert_eq!(r#"utf8"Remote" i32:abc"#.parse::<WitcherPacket>().unwrap_err().position, 17);
+        assert_eq!(r#"utf8"Remote"i32:1"#.parse::<WitcherPacket>().unwrap_err().position, 12);
+        assert!(r#"utf8"Remote"#.parse::<WitcherPacket>().is_err());
+        assert!("x32:1".parse::<WitcherPacketData>().is_err());
+        assert!("i8:256".parse::<WitcherPacketData>().is_err());
+        assert!("u64:1".parse::<WitcherPacketData>().is_err());
+        assert!("i32:1 i32:2".parse::<WitcherPacketData>().is_err());
+    }
+}
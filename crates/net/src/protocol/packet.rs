@@ -1,12 +1,21 @@
 use std::io::Read;
 
-use anyhow::{bail, Context};
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
 
 use super::encoding::*;
+use super::error::{FieldContext, ProtocolError, ProtocolResult};
+use super::limits::DecodeLimits;
 use super::packet_data::WitcherPacketData;
 
 
-#[derive(Clone, PartialEq, Eq)]
+/// With the `serde` feature a packet is represented as a list of its payload data.
+/// Strings are decoded lossily, so invalid UTF-8 or UTF-16 on the wire doesn't survive a round trip through serde.
+/// 
+/// [`Display`](std::fmt::Display) writes the payload in a text notation that can be parsed back with [`FromStr`](std::str::FromStr), 
+/// see [`WitcherPacketData::notation`].
+#[derive(Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
 pub struct WitcherPacket {
     pub(crate) payload: Vec<WitcherPacketData>
 }
@@ -15,67 +24,161 @@ impl WitcherPacket {
     pub const HEAD: [u8; 2] = [0xDE, 0xAD];
     pub const TAIL: [u8; 2] = [0xBE, 0xEF];
 
-    fn new() -> Self {
+    /// Creates a packet with empty payload
+    #[inline]
+    pub fn new() -> Self {
         Self {
             payload: Vec::new()
         }
     }
 
+    #[inline]
+    pub fn from_payload(payload: Vec<WitcherPacketData>) -> Self {
+        Self {
+            payload
+        }
+    }
+
+    #[inline]
+    pub fn payload(&self) -> &[WitcherPacketData] {
+        &self.payload
+    }
+
+    #[inline]
+    pub fn payload_mut(&mut self) -> &mut Vec<WitcherPacketData> {
+        &mut self.payload
+    }
+
+    #[inline]
+    pub fn into_payload(self) -> Vec<WitcherPacketData> {
+        self.payload
+    }
+
+    /// Builder-style way of appending data to the payload
+    #[inline]
+    pub fn with(mut self, data: WitcherPacketData) -> Self {
+        self.payload.push(data);
+        self
+    }
+
+    #[inline]
+    pub fn push(&mut self, data: WitcherPacketData) {
+        self.payload.push(data);
+    }
+
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&WitcherPacketData> {
+        self.payload.get(index)
+    }
+
+    #[inline]
+    pub fn iter(&self) -> std::slice::Iter<'_, WitcherPacketData> {
+        self.payload.iter()
+    }
+
     pub const fn min_encoded_size() -> usize {
         Self::HEAD.len() + i16::ENCODED_SIZE + Self::TAIL.len()
     }
-}
 
-impl Encode for WitcherPacket {
-    fn encode_into<S: std::io::Write>(&self, stream: &mut S) -> anyhow::Result<()> {
-        stream.write_all(&Self::HEAD)?;
-        (self.encoded_size() as u16).encode_into(stream)?;
-        for data in self.payload.iter() {
-            data.encode_into(stream)?;
+    /// Checks whether the packet can be encoded without breaking wire format limits.
+    /// Returns the size of the encoded packet.
+    pub fn check_encodable(&self) -> ProtocolResult<u16> {
+        for (i, data) in self.payload.iter().enumerate() {
+            data.check_encodable().field(format!("payload[{}]", i))?;
         }
-        stream.write_all(&Self::TAIL)?;
-        Ok(())
-    }
-}
 
-impl DynSizedEncode for WitcherPacket {
-    fn encoded_size(&self) -> usize {
-        self.payload.iter()
-            .fold(Self::min_encoded_size(), |acc, p| acc + p.encoded_size())
+        let encoded_size = self.encoded_size();
+        u16::try_from(encoded_size)
+            .map_err(|_| ProtocolError::LimitExceeded { 
+                what: "Packet size", 
+                actual: encoded_size, 
+                limit: u16::MAX as usize 
+            })
     }
-}
 
-impl Decode for WitcherPacket {
-    fn decode_from<S: Read>(stream: &mut S) -> anyhow::Result<Self> {
+    /// Decodes the packet, failing if any of given limits are exceeded
+    pub fn decode_limited<S: Read>(stream: &mut S, limits: &DecodeLimits) -> ProtocolResult<Self> {
         let mut short_buf = [0u8; 2];
 
-        stream.read_exact(&mut short_buf).context("No packet head")?;
+        stream.read_exact(&mut short_buf)?;
         if short_buf != Self::HEAD {
-            bail!("Invalid packet head: {:?}", short_buf)
+            return Err(ProtocolError::InvalidHead(short_buf));
+        }
+
+        let encoded_size = u16::decode_from(stream)?;
+        if encoded_size as usize > limits.max_packet_size {
+            return Err(ProtocolError::LimitExceeded { 
+                what: "Packet size", 
+                actual: encoded_size as usize, 
+                limit: limits.max_packet_size 
+            });
         }
 
-        let mut encoded_size = u16::decode_from(stream).context("Failed to decode packet size")?;
         let mut packet = WitcherPacket::new();
 
         if encoded_size > Self::min_encoded_size() as u16 {
-            encoded_size -= Self::min_encoded_size() as u16;
+            // the limit counts bytes actually read, strings decoded lossily can re-encode to a different size
+            let mut payload_stream = stream.by_ref().take((encoded_size as usize - Self::min_encoded_size()) as u64);
+
+            while payload_stream.limit() > 0 {
+                if packet.payload.len() >= limits.max_payload_len {
+                    return Err(ProtocolError::LimitExceeded { 
+                        what: "Payload length", 
+                        actual: packet.payload.len() + 1, 
+                        limit: limits.max_payload_len 
+                    });
+                }
+
+                let mut data = WitcherPacketData::decode_limited(&mut payload_stream, limits)
+                    .field(format!("payload[{}]", packet.payload.len()))?;
+                if let WitcherPacketData::Unknown(unknown) = &mut data {
+                    // the size of data behind an unknown tag is also unknown, 
+                    // so everything up to the tail is kept as is to not lose any information
+                    unknown.bytes = vec![0u8; payload_stream.limit() as usize];
+                    payload_stream.read_exact(&mut unknown.bytes)?;
+                }
 
-            while encoded_size > 0 {
-                let data = WitcherPacketData::decode_from(stream).context("Failed to decode payload data")?;
-                encoded_size = encoded_size.checked_sub(data.encoded_size() as u16).unwrap_or(0);
                 packet.payload.push(data);
             }
         }
 
-        stream.read_exact(&mut short_buf).context("No packet tail")?;
+        stream.read_exact(&mut short_buf)?;
         if short_buf != Self::TAIL {
-            bail!("Invalid packet tail: {:?}", short_buf)
+            return Err(ProtocolError::InvalidTail(short_buf));
         }
 
         Ok(packet)
     }
 }
 
+impl Encode for WitcherPacket {
+    fn encode_into<S: std::io::Write>(&self, stream: &mut S) -> ProtocolResult<()> {
+        // check everything first so nothing gets written if the packet can't be encoded whole
+        let encoded_size = self.check_encodable()?;
+
+        stream.write_all(&Self::HEAD)?;
+        encoded_size.encode_into(stream)?;
+        for data in self.payload.iter() {
+            data.encode_into(stream)?;
+        }
+        stream.write_all(&Self::TAIL)?;
+        Ok(())
+    }
+}
+
+impl DynSizedEncode for WitcherPacket {
+    fn encoded_size(&self) -> usize {
+        self.payload.iter()
+            .fold(Self::min_encoded_size(), |acc, p| acc + p.encoded_size())
+    }
+}
+
+impl Decode for WitcherPacket {
+    fn decode_from<S: Read>(stream: &mut S) -> ProtocolResult<Self> {
+        Self::decode_limited(stream, &DecodeLimits::default())
+    }
+}
+
 
 
 impl std::fmt::Debug for WitcherPacket {
@@ -94,9 +197,143 @@ impl std::fmt::Debug for WitcherPacket {
 
 impl std::fmt::Display for WitcherPacket {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for p in &self.payload {
-            f.write_fmt( format_args!("{} ", p) )?;
+        for (i, p) in self.payload.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            f.write_fmt( format_args!("{}", p.notation()) )?;
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+impl From<Vec<WitcherPacketData>> for WitcherPacket {
+    #[inline]
+    fn from(payload: Vec<WitcherPacketData>) -> Self {
+        Self::from_payload(payload)
+    }
+}
+
+impl FromIterator<WitcherPacketData> for WitcherPacket {
+    fn from_iter<T: IntoIterator<Item = WitcherPacketData>>(iter: T) -> Self {
+        Self::from_payload(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for WitcherPacket {
+    type Item = WitcherPacketData;
+    type IntoIter = std::vec::IntoIter<WitcherPacketData>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.payload.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a WitcherPacket {
+    type Item = &'a WitcherPacketData;
+    type IntoIter = std::slice::Iter<'a, WitcherPacketData>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.payload.iter()
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::*;
+
+
+    #[test]
+    fn packet_too_big_encode_test() {
+        let packet = WitcherPacketAssembler::new()
+            .string_utf8("a".repeat(30000))
+            .string_utf8("b".repeat(30000))
+            .string_utf8("c".repeat(10000))
+            .finish();
+
+        let mut bytes = Vec::new();
+        assert!(packet.encode_into(&mut bytes).is_err());
+        assert!(bytes.is_empty());
+    }
+
+    #[test]
+    fn packet_invalid_utf8_decode_test() {
+        let packet = WitcherPacketAssembler::new()
+            .string_utf8("abcd")
+            .int32(5)
+            .finish();
+
+        let mut bytes = Vec::new();
+        packet.encode_into(&mut bytes).unwrap();
+        // replacement characters take up more bytes than the invalid ones they replace
+        let content_pos = bytes.len() - 2 - 6 - 4;
+        bytes[content_pos..content_pos + 4].copy_from_slice(&[0xFF; 4]);
+
+        let decoded = WitcherPacket::decode_from(&mut bytes.as_slice()).unwrap();
+        assert_eq!(decoded.payload, vec![
+            WitcherPacketData::new_string_utf8("\u{FFFD}".repeat(4)),
+            WitcherPacketData::new_int32(5)
+        ]);
+    }
+
+    #[test]
+    fn packet_decode_limits_test() {
+        let packet = WitcherPacketAssembler::new()
+            .string_utf8("Config")
+            .int32(1)
+            .int32(2)
+            .finish();
+
+        let mut bytes = Vec::new();
+        packet.encode_into(&mut bytes).unwrap();
+
+        let limits = DecodeLimits {
+            max_payload_len: 2,
+            ..Default::default()
+        };
+        assert!(matches!(
+            WitcherPacket::decode_limited(&mut bytes.as_slice(), &limits), 
+            Err(ProtocolError::LimitExceeded { what: "Payload length", .. })
+        ));
+
+        let limits = DecodeLimits {
+            max_string_len: 5,
+            ..Default::default()
+        };
+        assert!(WitcherPacket::decode_limited(&mut bytes.as_slice(), &limits).is_err());
+
+        let limits = DecodeLimits {
+            max_packet_size: bytes.len() - 1,
+            ..Default::default()
+        };
+        assert!(WitcherPacket::decode_limited(&mut bytes.as_slice(), &limits).is_err());
+
+        assert_eq!(WitcherPacket::decode_limited(&mut bytes.as_slice(), &DecodeLimits::default()).unwrap(), packet);
+    }
+
+    #[test]
+    fn packet_unknown_data_lossless_test() {
+        let packet = WitcherPacketAssembler::new()
+            .string_utf8("ScriptProfiler")
+            .int32(5)
+            .finish();
+
+        let mut bytes = Vec::new();
+        packet.encode_into(&mut bytes).unwrap();
+        // replace the tag of int32 with something unrecognized
+        let second_tag_pos = 2 + 2 + 2 + 4 + "ScriptProfiler".len();
+        bytes[second_tag_pos..second_tag_pos + 2].copy_from_slice(&[0x12, 0x34]);
+
+        let decoded = WitcherPacket::decode_from(&mut bytes.as_slice()).unwrap();
+        assert_eq!(decoded.payload.len(), 2);
+        assert_eq!(decoded.payload[1], WitcherPacketData::new_unknown(UnknownData::new([0x12, 0x34], vec![0, 0, 0, 5])));
+
+        let mut reencoded = Vec::new();
+        decoded.encode_into(&mut reencoded).unwrap();
+        assert_eq!(reencoded, bytes);
+    }
+}
@@ -1,9 +1,11 @@
-use anyhow::{bail, Context};
+use std::path::PathBuf;
 
-use super::{StringUtf16, StringUtf8, WitcherPacket, WitcherPacketData};
+use shrinkwraprs::Shrinkwrap;
 
+use super::{DynSizedEncode, EncodingTag, FieldContext, ProtocolError, ProtocolResult, StringUtf16, StringUtf8, WireType, WitcherPacket, WitcherPacketData};
 
-#[derive(Debug, Clone)]
+
+#[derive(Debug, Clone, Default)]
 pub struct WitcherPacketAssembler {
     payload: Vec<WitcherPacketData>
 }
@@ -51,6 +53,12 @@ impl WitcherPacketAssembler {
         self.push(WitcherPacketData::new_string_utf16(data.into()))
     }
 
+    /// Appends already constructed data
+    #[inline]
+    pub fn data(self, data: WitcherPacketData) -> Self {
+        self.push(data)
+    }
+
     #[inline]
     pub fn finish(self) -> WitcherPacket {
         WitcherPacket {
@@ -75,7 +83,13 @@ impl WitcherPacketAssembler {
 
 #[derive(Debug, Clone)]
 pub struct WitcherPacketDisassembler {
-    payload_rev: Vec<WitcherPacketData>
+    payload_rev: Vec<WitcherPacketData>,
+    // index and byte offset of the next data entry
+    position: usize,
+    offset: usize,
+    // same, but of the entry taken most recently
+    last_position: usize,
+    last_offset: usize
 }
 
 impl WitcherPacketDisassembler {
@@ -85,124 +99,238 @@ impl WitcherPacketDisassembler {
         packet.payload.reverse();
 
         Self {
-            payload_rev: packet.payload
+            payload_rev: packet.payload,
+            position: 0,
+            offset: 0,
+            last_position: 0,
+            last_offset: 0
         }
     }
 
     #[inline]
-    pub fn int8(&mut self) -> anyhow::Result<i8> {
-        self.pop()?.try_as_int_8().map(|t| t.into_inner()).context("Type mismatch")
+    pub fn int8(&mut self) -> ProtocolResult<i8> {
+        self.pop_as(WitcherPacketData::try_as_int_8, i8::ENCODING_TAG).map(|t| t.into_inner())
     }
 
     #[inline]
-    pub fn fixed_int8(&mut self, expected: i8) -> anyhow::Result<i8> {
-        let data = self.pop()?.try_as_int_8().map(|t| t.into_inner()).context("Type mismatch")?;
+    pub fn fixed_int8(&mut self, expected: i8) -> ProtocolResult<i8> {
+        let data = self.pop_as(WitcherPacketData::try_as_int_8, i8::ENCODING_TAG).map(|t| t.into_inner())?;
         if data != expected {
-            bail!("Failed to match expected data: {expected}");
+            return Err(ProtocolError::UnexpectedValue { 
+                expected: expected.to_string(), 
+                actual: data.to_string() 
+            });
         }
 
         Ok(data)
     }
 
     #[inline]
-    pub fn int16(&mut self) -> anyhow::Result<i16> {
-        self.pop()?.try_as_int_16().map(|t| t.into_inner()).context("Type mismatch")
+    pub fn int16(&mut self) -> ProtocolResult<i16> {
+        self.pop_as(WitcherPacketData::try_as_int_16, i16::ENCODING_TAG).map(|t| t.into_inner())
     }
 
     #[inline]
-    pub fn fixed_int16(&mut self, expected: i16) -> anyhow::Result<i16> {
-        let data = self.pop()?.try_as_int_16().map(|t| t.into_inner()).context("Type mismatch")?;
+    pub fn fixed_int16(&mut self, expected: i16) -> ProtocolResult<i16> {
+        let data = self.pop_as(WitcherPacketData::try_as_int_16, i16::ENCODING_TAG).map(|t| t.into_inner())?;
         if data != expected {
-            bail!("Failed to match expected data: {expected}");
+            return Err(ProtocolError::UnexpectedValue { 
+                expected: expected.to_string(), 
+                actual: data.to_string() 
+            });
         }
 
         Ok(data)
     }
 
     #[inline]
-    pub fn int32(&mut self) -> anyhow::Result<i32> {
-        self.pop()?.try_as_int_32().map(|t| t.into_inner()).context("Type mismatch")
+    pub fn int32(&mut self) -> ProtocolResult<i32> {
+        self.pop_as(WitcherPacketData::try_as_int_32, i32::ENCODING_TAG).map(|t| t.into_inner())
     }
 
     #[inline]
-    pub fn fixed_int32(&mut self, expected: i32) -> anyhow::Result<i32> {
-        let data = self.pop()?.try_as_int_32().map(|t| t.into_inner()).context("Type mismatch")?;
+    pub fn fixed_int32(&mut self, expected: i32) -> ProtocolResult<i32> {
+        let data = self.pop_as(WitcherPacketData::try_as_int_32, i32::ENCODING_TAG).map(|t| t.into_inner())?;
         if data != expected {
-            bail!("Failed to match expected data: {expected}");
+            return Err(ProtocolError::UnexpectedValue { 
+                expected: expected.to_string(), 
+                actual: data.to_string() 
+            });
         }
 
         Ok(data)
     }
 
     #[inline]
-    pub fn uint32(&mut self) -> anyhow::Result<u32> {
-        self.pop()?.try_as_uint_32().map(|t| t.into_inner()).context("Type mismatch")
+    pub fn uint32(&mut self) -> ProtocolResult<u32> {
+        self.pop_as(WitcherPacketData::try_as_uint_32, u32::ENCODING_TAG).map(|t| t.into_inner())
     }
 
     #[inline]
-    pub fn fixed_uint32(&mut self, expected: u32) -> anyhow::Result<u32> {
-        let data = self.pop()?.try_as_uint_32().map(|t| t.into_inner()).context("Type mismatch")?;
+    pub fn fixed_uint32(&mut self, expected: u32) -> ProtocolResult<u32> {
+        let data = self.pop_as(WitcherPacketData::try_as_uint_32, u32::ENCODING_TAG).map(|t| t.into_inner())?;
         if data != expected {
-            bail!("Failed to match expected data: {expected}");
+            return Err(ProtocolError::UnexpectedValue { 
+                expected: expected.to_string(), 
+                actual: data.to_string() 
+            });
         }
 
         Ok(data)
     }
 
     #[inline]
-    pub fn int64(&mut self) -> anyhow::Result<i64> {
-        self.pop()?.try_as_int_64().map(|t| t.into_inner()).context("Type mismatch")
+    pub fn int64(&mut self) -> ProtocolResult<i64> {
+        self.pop_as(WitcherPacketData::try_as_int_64, i64::ENCODING_TAG).map(|t| t.into_inner())
     }
 
     #[inline]
-    pub fn fixed_int64(&mut self, expected: i64) -> anyhow::Result<i64> {
-        let data = self.pop()?.try_as_int_64().map(|t| t.into_inner()).context("Type mismatch")?;
+    pub fn fixed_int64(&mut self, expected: i64) -> ProtocolResult<i64> {
+        let data = self.pop_as(WitcherPacketData::try_as_int_64, i64::ENCODING_TAG).map(|t| t.into_inner())?;
         if data != expected {
-            bail!("Failed to match expected data: {expected}");
+            return Err(ProtocolError::UnexpectedValue { 
+                expected: expected.to_string(), 
+                actual: data.to_string() 
+            });
         }
 
         Ok(data)
     }
 
     #[inline]
-    pub fn string_utf8(&mut self) -> anyhow::Result<StringUtf8> {
-        self.pop()?.try_as_string_utf_8().map(|t| t.into_inner()).context("Type mismatch")
+    pub fn string_utf8(&mut self) -> ProtocolResult<StringUtf8> {
+        self.pop_as(WitcherPacketData::try_as_string_utf_8, StringUtf8::ENCODING_TAG).map(|t| t.into_inner())
     }
 
     #[inline]
-    pub fn fixed_string_utf8(&mut self, expected: &str) -> anyhow::Result<StringUtf8> {
-        let data = self.pop()?.try_as_string_utf_8().map(|t| t.into_inner()).context("Type mismatch")?;
+    pub fn fixed_string_utf8(&mut self, expected: &str) -> ProtocolResult<StringUtf8> {
+        let data = self.pop_as(WitcherPacketData::try_as_string_utf_8, StringUtf8::ENCODING_TAG).map(|t| t.into_inner())?;
         if data.as_str() != expected {
-            bail!("Failed to match expected data: {expected}");
+            return Err(ProtocolError::UnexpectedValue { 
+                expected: expected.to_string(), 
+                actual: data.to_string() 
+            });
         }
 
         Ok(data)
     }
 
     #[inline]
-    pub fn string_utf16(&mut self) -> anyhow::Result<StringUtf16> {
-        self.pop()?.try_as_string_utf_16().map(|t| t.into_inner()).context("Type mismatch")
+    pub fn string_utf16(&mut self) -> ProtocolResult<StringUtf16> {
+        self.pop_as(WitcherPacketData::try_as_string_utf_16, StringUtf16::ENCODING_TAG).map(|t| t.into_inner())
     }
 
     #[inline]
-    pub fn fixed_string_utf16(&mut self, expected: &str) -> anyhow::Result<StringUtf16> {
-        let data = self.pop()?.try_as_string_utf_16().map(|t| t.into_inner()).context("Type mismatch")?;
+    pub fn fixed_string_utf16(&mut self, expected: &str) -> ProtocolResult<StringUtf16> {
+        let data = self.pop_as(WitcherPacketData::try_as_string_utf_16, StringUtf16::ENCODING_TAG).map(|t| t.into_inner())?;
         if data.as_str() != expected {
-            bail!("Failed to match expected data: {expected}");
+            return Err(ProtocolError::UnexpectedValue { 
+                expected: expected.to_string(), 
+                actual: data.to_string() 
+            });
         }
 
         Ok(data)
     }
 
     #[inline]
-    pub fn any(&mut self) -> anyhow::Result<WitcherPacketData> {
+    pub fn any(&mut self) -> ProtocolResult<WitcherPacketData> {
+        self.pop()
+    }
+
+    /// Reads an int32 element count of a collection that follows.
+    /// Fails if the count is negative or bigger than the number of remaining data entries,
+    /// so it is safe to allocate memory based on it.
+    pub fn count(&mut self) -> ProtocolResult<usize> {
+        let count = self.int32()?;
+        if count < 0 {
+            return Err(ProtocolError::NegativeLength { 
+                what: "element count", 
+                value: count as i64 
+            });
+        }
+
+        let count = count as usize;
+        if count > self.remaining() {
+            return Err(ProtocolError::LimitExceeded { 
+                what: "Element count", 
+                actual: count, 
+                limit: self.remaining() 
+            });
+        }
+
+        Ok(count)
+    }
+
+    /// Number of data entries left to disassemble
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.payload_rev.len()
+    }
+
+    /// Index of the next data entry in the payload
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Offset in bytes of the next data entry from the beginning of the payload
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Data entries that were not disassembled yet, in the order they appear in the payload
+    pub fn into_remaining(mut self) -> Vec<WitcherPacketData> {
+        self.payload_rev.reverse();
+        self.payload_rev
+    }
+
+    /// Attaches the position of the most recently taken data entry to the error.
+    /// Should be called right after the error occurs.
+    pub fn locate(&self, err: ProtocolError) -> ProtocolError {
+        ProtocolError::Position {
+            index: self.last_position,
+            offset: self.last_offset,
+            source: Box::new(err)
+        }
+    }
+
+    /// Takes the next piece of data whatever its type is
+    #[inline]
+    pub fn data(&mut self) -> ProtocolResult<WitcherPacketData> {
         self.pop()
     }
 
+    /// Takes the next piece of data, failing if it's not of the expected type
+    pub fn data_of_type(&mut self, expected: WireType) -> ProtocolResult<WitcherPacketData> {
+        let data = self.pop()?;
+        if data.wire_type() != Some(expected) {
+            return Err(ProtocolError::TypeMismatch { 
+                expected: expected.tag(), 
+                actual: data.tag() 
+            });
+        }
+        Ok(data)
+    }
+
 
     #[inline]
-    fn pop(&mut self) -> anyhow::Result<WitcherPacketData> {
-        self.payload_rev.pop().context("No more data found")
+    fn pop(&mut self) -> ProtocolResult<WitcherPacketData> {
+        self.last_position = self.position;
+        self.last_offset = self.offset;
+
+        let data = self.payload_rev.pop().ok_or(ProtocolError::MissingData)?;
+        self.position += 1;
+        self.offset += data.encoded_size();
+        Ok(data)
+    }
+
+    #[inline]
+    fn pop_as<T>(&mut self, try_as: fn(WitcherPacketData) -> Option<T>, expected: [u8; 2]) -> ProtocolResult<T> {
+        let data = self.pop()?;
+        let actual = data.tag();
+        try_as(data).ok_or(ProtocolError::TypeMismatch { expected, actual })
     }
 }
 
@@ -213,7 +341,28 @@ pub trait AssemblePayload: Sized {
 }
 
 pub trait DisassemblePayload: Sized {
-    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> anyhow::Result<Self>;
+    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> ProtocolResult<Self>;
+}
+
+/// Payload that can give back the fields that came before the one that couldn't be disassembled.
+/// `#[derive(DisassemblePayload)]` implements it for structs, with `Partial` being a generated `<Name>Partial` struct 
+/// that has the same fields, but each of them wrapped in an `Option`.
+pub trait DisassemblePartial: DisassemblePayload {
+    type Partial;
+
+    /// Same as [`DisassemblePayload::disassemble_payload`], but on error returns fields disassembled so far.
+    /// The error already has its position attached and `dasm` is left at the beginning of the field that failed.
+    fn disassemble_partial(dasm: &mut WitcherPacketDisassembler) -> Result<Self, (Self::Partial, ProtocolError)>;
+}
+
+/// [`DisassemblePartial::disassemble_partial`] for types that can only be disassembled as a whole
+pub fn disassemble_whole<T: DisassemblePayload>(dasm: &mut WitcherPacketDisassembler) -> Result<T, ((), ProtocolError)> {
+    let start = dasm.clone();
+    T::disassemble_payload(dasm).map_err(|err| {
+        let err = dasm.locate(err);
+        *dasm = start;
+        ((), err)
+    })
 }
 
 
@@ -224,7 +373,361 @@ impl AssemblePayload for () {
 }
 
 impl DisassemblePayload for () {
-    fn disassemble_payload(_: &mut WitcherPacketDisassembler) -> anyhow::Result<Self> {
+    fn disassemble_payload(_: &mut WitcherPacketDisassembler) -> ProtocolResult<Self> {
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+impl DisassemblePartial for () {
+    type Partial = ();
+
+    fn disassemble_partial(_: &mut WitcherPacketDisassembler) -> Result<Self, (Self::Partial, ProtocolError)> {
+        Ok(())
+    }
+}
+
+
+macro_rules! impl_payload_for_primitive {
+    ($t:ty, $asm_method:ident, $dasm_method:ident) => {
+        impl AssemblePayload for $t {
+            #[inline]
+            fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
+                asm.$asm_method(self)
+            }
+        }
+
+        impl DisassemblePayload for $t {
+            #[inline]
+            fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> ProtocolResult<Self> {
+                dasm.$dasm_method()
+            }
+        }
+    };
+}
+
+impl_payload_for_primitive!(i8, int8, int8);
+impl_payload_for_primitive!(i16, int16, int16);
+impl_payload_for_primitive!(i32, int32, int32);
+impl_payload_for_primitive!(u32, uint32, uint32);
+impl_payload_for_primitive!(i64, int64, int64);
+impl_payload_for_primitive!(StringUtf8, string_utf8, string_utf8);
+impl_payload_for_primitive!(StringUtf16, string_utf16, string_utf16);
+
+/// Strings are sent as UTF8 by default
+impl AssemblePayload for String {
+    #[inline]
+    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
+        asm.string_utf8(self)
+    }
+}
+
+impl DisassemblePayload for String {
+    #[inline]
+    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> ProtocolResult<Self> {
+        dasm.string_utf8().map(|s| s.0)
+    }
+}
+
+/// Booleans are sent as int8 with 1 meaning true
+impl AssemblePayload for bool {
+    #[inline]
+    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
+        asm.int8(if self { 1 } else { 0 })
+    }
+}
+
+impl DisassemblePayload for bool {
+    #[inline]
+    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> ProtocolResult<Self> {
+        Ok(dasm.int8()? != 0)
+    }
+}
+
+/// Paths are sent as UTF16 strings, same as [`Utf16Path`]
+impl AssemblePayload for PathBuf {
+    #[inline]
+    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
+        Utf16Path(self).assemble_payload(asm)
+    }
+}
+
+impl DisassemblePayload for PathBuf {
+    #[inline]
+    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> ProtocolResult<Self> {
+        Utf16Path::disassemble_payload(dasm).map(Utf16Path::into_inner)
+    }
+}
+
+/// Optional values are sent with a presence flag, same as [`Flagged`]
+impl<T: AssemblePayload> AssemblePayload for Option<T> {
+    #[inline]
+    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
+        Flagged(self).assemble_payload(asm)
+    }
+}
+
+impl<T: DisassemblePayload> DisassemblePayload for Option<T> {
+    #[inline]
+    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> ProtocolResult<Self> {
+        Flagged::disassemble_payload(dasm).map(Flagged::into_inner)
+    }
+}
+
+/// Collections are sent with their element count in front, same as [`CountPrefixed`]
+impl<T: AssemblePayload> AssemblePayload for Vec<T> {
+    #[inline]
+    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
+        CountPrefixed(self).assemble_payload(asm)
+    }
+}
+
+impl<T: DisassemblePayload> DisassemblePayload for Vec<T> {
+    #[inline]
+    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> ProtocolResult<Self> {
+        CountPrefixed::disassemble_payload(dasm).map(CountPrefixed::into_inner)
+    }
+}
+
+
+macro_rules! impl_payload_for_tuple {
+    ($($t:ident),+) => {
+        impl<$($t: AssemblePayload),+> AssemblePayload for ($($t,)+) {
+            #[allow(non_snake_case)]
+            fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
+                let ($($t,)+) = self;
+                $(let asm = $t.assemble_payload(asm);)+
+                asm
+            }
+        }
+
+        impl<$($t: DisassemblePayload),+> DisassemblePayload for ($($t,)+) {
+            fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> ProtocolResult<Self> {
+                Ok(($($t::disassemble_payload(dasm)?,)+))
+            }
+        }
+    };
+}
+
+impl_payload_for_tuple!(A);
+impl_payload_for_tuple!(A, B);
+impl_payload_for_tuple!(A, B, C);
+impl_payload_for_tuple!(A, B, C, D);
+impl_payload_for_tuple!(A, B, C, D, E);
+impl_payload_for_tuple!(A, B, C, D, E, F);
+
+
+
+/// Optional value preceded by an int8 flag, 1 if the value is present and 0 if it is not.
+#[derive(Shrinkwrap, Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Flagged<T>(pub Option<T>);
+
+impl<T> Flagged<T> {
+    #[inline]
+    pub fn into_inner(self) -> Option<T> {
+        self.0
+    }
+}
+
+impl<T> From<Option<T>> for Flagged<T> {
+    #[inline]
+    fn from(value: Option<T>) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: AssemblePayload> AssemblePayload for Flagged<T> {
+    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
+        match self.0 {
+            Some(value) => value.assemble_payload(asm.int8(1)),
+            None => asm.int8(0)
+        }
+    }
+}
+
+impl<T: DisassemblePayload> DisassemblePayload for Flagged<T> {
+    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> ProtocolResult<Self> {
+        if bool::disassemble_payload(dasm)? {
+            Ok(Self(Some(T::disassemble_payload(dasm)?)))
+        } else {
+            Ok(Self(None))
+        }
+    }
+}
+
+
+/// Collection preceded by an int32 count of its elements.
+#[derive(Shrinkwrap, Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CountPrefixed<V>(pub V);
+
+impl<V> CountPrefixed<V> {
+    #[inline]
+    pub fn into_inner(self) -> V {
+        self.0
+    }
+}
+
+impl<T> From<Vec<T>> for CountPrefixed<Vec<T>> {
+    #[inline]
+    fn from(value: Vec<T>) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: AssemblePayload> AssemblePayload for CountPrefixed<Vec<T>> {
+    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
+        let mut asm = asm.int32(self.0.len() as i32);
+        for item in self.0 {
+            asm = item.assemble_payload(asm);
+        }
+
+        asm
+    }
+}
+
+impl<T: DisassemblePayload> DisassemblePayload for CountPrefixed<Vec<T>> {
+    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> ProtocolResult<Self> {
+        let count = dasm.count()?;
+
+        let mut items = Vec::with_capacity(count);
+        for i in 0..count {
+            items.push(T::disassemble_payload(dasm).field(format!("[{}]", i))?);
+        }
+
+        Ok(Self(items))
+    }
+}
+
+
+/// Optional value, which is sent as its default (e.g. an empty string) when it's not present.
+#[derive(Shrinkwrap, Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EmptyAsNone<T>(pub Option<T>);
+
+impl<T> EmptyAsNone<T> {
+    #[inline]
+    pub fn into_inner(self) -> Option<T> {
+        self.0
+    }
+}
+
+impl<T> From<Option<T>> for EmptyAsNone<T> {
+    #[inline]
+    fn from(value: Option<T>) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: AssemblePayload + Default> AssemblePayload for EmptyAsNone<T> {
+    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
+        self.0.unwrap_or_default().assemble_payload(asm)
+    }
+}
+
+impl<T: DisassemblePayload + Default + PartialEq> DisassemblePayload for EmptyAsNone<T> {
+    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> ProtocolResult<Self> {
+        let value = T::disassemble_payload(dasm)?;
+        if value == T::default() {
+            Ok(Self(None))
+        } else {
+            Ok(Self(Some(value)))
+        }
+    }
+}
+
+
+/// Path sent as a UTF16 string. Any non-Unicode sequences get replaced when assembling.
+#[derive(Shrinkwrap, Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Utf16Path(pub PathBuf);
+
+impl Utf16Path {
+    #[inline]
+    pub fn into_inner(self) -> PathBuf {
+        self.0
+    }
+}
+
+impl From<PathBuf> for Utf16Path {
+    #[inline]
+    fn from(value: PathBuf) -> Self {
+        Self(value)
+    }
+}
+
+impl AssemblePayload for Utf16Path {
+    fn assemble_payload(self, asm: WitcherPacketAssembler) -> WitcherPacketAssembler {
+        asm.string_utf16(self.0.to_string_lossy())
+    }
+}
+
+impl DisassemblePayload for Utf16Path {
+    fn disassemble_payload(dasm: &mut WitcherPacketDisassembler) -> ProtocolResult<Self> {
+        Ok(Self(PathBuf::from(dasm.string_utf16()?.0)))
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+
+    fn round_trip<T>(value: T, expected: WitcherPacket) 
+    where T: AssemblePayload + DisassemblePayload + Clone + PartialEq + std::fmt::Debug {
+        let packet = value.clone().assemble_payload(WitcherPacketAssembler::new()).finish();
+        assert_eq!(packet, expected);
+
+        let mut dasm = WitcherPacketDisassembler::new(packet);
+        assert_eq!(T::disassemble_payload(&mut dasm).unwrap(), value);
+        assert_eq!(dasm.remaining(), 0);
+    }
+
+
+    #[test]
+    fn primitive_payload_test() {
+        round_trip(true, WitcherPacketAssembler::new().int8(1).finish());
+        round_trip(false, WitcherPacketAssembler::new().int8(0).finish());
+        round_trip(String::from("Remote"), WitcherPacketAssembler::new().string_utf8("Remote").finish());
+        round_trip(PathBuf::from("content/scripts"), WitcherPacketAssembler::new().string_utf16("content/scripts").finish());
+        round_trip(
+            (1i8, 2u32, StringUtf16::from("three")), 
+            WitcherPacketAssembler::new().int8(1).uint32(2).string_utf16("three").finish()
+        );
+    }
+
+    #[test]
+    fn flagged_payload_test() {
+        round_trip(
+            Flagged(Some(StringUtf16::from("CPlayer"))), 
+            WitcherPacketAssembler::new().int8(1).string_utf16("CPlayer").finish()
+        );
+        round_trip(Flagged::<i32>(None), WitcherPacketAssembler::new().int8(0).finish());
+        round_trip(Some(5i64), WitcherPacketAssembler::new().int8(1).int64(5).finish());
+    }
+
+    #[test]
+    fn count_prefixed_payload_test() {
+        round_trip(
+            CountPrefixed(vec![(1i32, String::from("a")), (2i32, String::from("b"))]),
+            WitcherPacketAssembler::new().int32(2).int32(1).string_utf8("a").int32(2).string_utf8("b").finish()
+        );
+        round_trip(Vec::<i8>::new(), WitcherPacketAssembler::new().int32(0).finish());
+
+        let too_many = WitcherPacketAssembler::new().int32(3).int8(1).int8(2).finish();
+        let mut dasm = WitcherPacketDisassembler::new(too_many);
+        assert!(Vec::<i8>::disassemble_payload(&mut dasm).is_err());
+    }
+
+    #[test]
+    fn empty_as_none_payload_test() {
+        round_trip(
+            EmptyAsNone(Some(String::from("Graphics"))), 
+            WitcherPacketAssembler::new().string_utf8("Graphics").finish()
+        );
+        round_trip(EmptyAsNone::<String>(None), WitcherPacketAssembler::new().string_utf8("").finish());
+        round_trip(EmptyAsNone::<StringUtf16>(None), WitcherPacketAssembler::new().string_utf16("").finish());
+    }
+}
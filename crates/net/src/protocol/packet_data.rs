@@ -1,10 +1,15 @@
-use anyhow::Context;
 use strum_macros::EnumTryAs;
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
 
 use super::encoding::*;
+use super::error::ProtocolResult;
+use super::limits::DecodeLimits;
 
 
+/// With the `serde` feature data is represented as `{ "type": "Int32", "value": 5 }`
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, EnumTryAs)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(tag = "type", content = "value"))]
 pub enum WitcherPacketData {
     Int8(Tagged<i8>),
     Int16(Tagged<i16>),
@@ -13,8 +18,8 @@ pub enum WitcherPacketData {
     Int64(Tagged<i64>),
     StringUTF8(Tagged<StringUtf8>),
     StringUTF16(Tagged<StringUtf16>),
-    /// for cases where the data tag was not recognized
-    Unknown(UnknownTag)
+    /// for cases where the data tag was not recognized, holds all the remaining payload bytes
+    Unknown(UnknownData)
 }
 
 impl WitcherPacketData {
@@ -46,13 +51,85 @@ impl WitcherPacketData {
         Self::StringUTF16(Tagged::new(s.into()))
     }
 
-    pub fn new_unknown(t: UnknownTag) -> Self {
-        Self::Unknown(t)
+    pub fn new_unknown(d: UnknownData) -> Self {
+        Self::Unknown(d)
+    }
+
+
+    /// Encoding tag written before the data
+    pub fn tag(&self) -> [u8; 2] {
+        match self {
+            WitcherPacketData::Int8(_) => i8::ENCODING_TAG,
+            WitcherPacketData::Int16(_) => i16::ENCODING_TAG,
+            WitcherPacketData::Int32(_) => i32::ENCODING_TAG,
+            WitcherPacketData::Uint32(_) => u32::ENCODING_TAG,
+            WitcherPacketData::Int64(_) => i64::ENCODING_TAG,
+            WitcherPacketData::StringUTF8(_) => StringUtf8::ENCODING_TAG,
+            WitcherPacketData::StringUTF16(_) => StringUtf16::ENCODING_TAG,
+            WitcherPacketData::Unknown(d) => d.tag,
+        }
+    }
+
+    /// Type of the data, `None` if it is unknown
+    pub fn wire_type(&self) -> Option<WireType> {
+        WireType::from_tag(self.tag())
+    }
+
+    /// Name of the data type with given encoding tag, `None` if the tag is not known
+    pub fn type_name_for_tag(tag: [u8; 2]) -> Option<&'static str> {
+        match tag {
+            i8::ENCODING_TAG => Some("Int8"),
+            i16::ENCODING_TAG => Some("Int16"),
+            i32::ENCODING_TAG => Some("Int32"),
+            u32::ENCODING_TAG => Some("Uint32"),
+            i64::ENCODING_TAG => Some("Int64"),
+            StringUtf8::ENCODING_TAG => Some("StringUTF8"),
+            StringUtf16::ENCODING_TAG => Some("StringUTF16"),
+            _ => None
+        }
+    }
+}
+
+/// Known types of [`WitcherPacketData`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum WireType {
+    Int8,
+    Int16,
+    Int32,
+    Uint32,
+    Int64,
+    StringUTF8,
+    StringUTF16
+}
+
+impl WireType {
+    pub const ALL: [WireType; 7] = [
+        Self::Int8, Self::Int16, Self::Int32, Self::Uint32, Self::Int64, Self::StringUTF8, Self::StringUTF16
+    ];
+
+    /// Encoding tag written before data of this type
+    pub fn tag(&self) -> [u8; 2] {
+        match self {
+            Self::Int8 => i8::ENCODING_TAG,
+            Self::Int16 => i16::ENCODING_TAG,
+            Self::Int32 => i32::ENCODING_TAG,
+            Self::Uint32 => u32::ENCODING_TAG,
+            Self::Int64 => i64::ENCODING_TAG,
+            Self::StringUTF8 => StringUtf8::ENCODING_TAG,
+            Self::StringUTF16 => StringUtf16::ENCODING_TAG,
+        }
+    }
+
+    pub fn from_tag(tag: [u8; 2]) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.tag() == tag)
     }
 }
 
+
+
 impl Encode for WitcherPacketData {
-    fn encode_into<S: std::io::Write>(&self, stream: &mut S) -> anyhow::Result<()> {
+    fn encode_into<S: std::io::Write>(&self, stream: &mut S) -> ProtocolResult<()> {
         match self {
             WitcherPacketData::Int8(d) => d.encode_into(stream),
             WitcherPacketData::Int16(d) => d.encode_into(stream),
@@ -81,10 +158,24 @@ impl DynSizedEncode for WitcherPacketData {
     }
 }
 
-impl Decode for WitcherPacketData {
-    fn decode_from<S: std::io::Read>(stream: &mut S) -> anyhow::Result<Self> {
+impl WitcherPacketData {
+    /// Checks whether the data can be encoded without breaking wire format limits
+    pub fn check_encodable(&self) -> ProtocolResult<()> {
+        match self {
+            WitcherPacketData::StringUTF8(s) => s.len_tagged().map(|_| ()),
+            WitcherPacketData::StringUTF16(s) => s.len_tagged().map(|_| ()),
+            _ => Ok(())
+        }
+    }
+
+    /// Decodes a single piece of data.
+    /// 
+    /// If the tag is not recognized, returned [`WitcherPacketData::Unknown`] holds only the tag,
+    /// because the size of the data behind it can't be known here. 
+    /// [`super::WitcherPacket`] fills in the rest of the bytes.
+    pub fn decode_limited<S: std::io::Read>(stream: &mut S, limits: &DecodeLimits) -> ProtocolResult<Self> {
         let mut tag = [0u8; 2];
-        stream.read_exact(&mut tag).context("Failed to read a tag")?;
+        stream.read_exact(&mut tag)?;
 
         match tag {
             i8::ENCODING_TAG => {
@@ -108,20 +199,26 @@ impl Decode for WitcherPacketData {
                 Ok(WitcherPacketData::new_int64(d))
             },
             StringUtf8::ENCODING_TAG => {
-                let d = StringUtf8::decode_from(stream)?;
+                let d = StringUtf8::decode_limited(stream, limits.max_string_len)?;
                 Ok(WitcherPacketData::new_string_utf8(d))
             },
             StringUtf16::ENCODING_TAG => {
-                let d = StringUtf16::decode_from(stream)?;
+                let d = StringUtf16::decode_limited(stream, limits.max_string_len)?;
                 Ok(WitcherPacketData::new_string_utf16(d))
             },
             _ => {
-                Ok(WitcherPacketData::new_unknown(tag.into()))
+                Ok(WitcherPacketData::new_unknown(UnknownData::new(tag, Vec::new())))
             }
         }
     }
 }
 
+impl Decode for WitcherPacketData {
+    fn decode_from<S: std::io::Read>(stream: &mut S) -> ProtocolResult<Self> {
+        Self::decode_limited(stream, &DecodeLimits::default())
+    }
+}
+
 impl std::fmt::Debug for WitcherPacketData {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -145,9 +242,9 @@ impl std::fmt::Debug for WitcherPacketData {
             }
             Self::StringUTF16(s) => {
                 write!(f, "Type: StringUTF16\nLength: {}\nValue: {}", s.chars().count(), s)
-            },
-            Self::Unknown(b) => {
-                write!(f, "Type: Unknown\nValue: {:?}", b)
+            }
+            Self::Unknown(d) => {
+                write!(f, "Type: Unknown {:02X?}\nLength: {}\nValue: {:02X?}", d.tag, d.bytes.len(), d.bytes)
             }
         }
     }
@@ -156,14 +253,14 @@ impl std::fmt::Debug for WitcherPacketData {
 impl std::fmt::Display for WitcherPacketData {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let s = match self {
-            Self::Int8(i) => format!("{}", i),
-            Self::Int16(i) => format!("{}", i),
-            Self::Int32(i) => format!("{}", i),
-            Self::Uint32(i) => format!("{}", i),
-            Self::Int64(i) => format!("{}", i),
-            Self::StringUTF8(s) => format!("{}", s),
-            Self::StringUTF16(s) => format!("{}", s),
-            Self::Unknown(s) => format!("{:?}", s)
+            Self::Int8(d) => format!("{}", d),
+            Self::Int16(d) => format!("{}", d),
+            Self::Int32(d) => format!("{}", d),
+            Self::Uint32(d) => format!("{}", d),
+            Self::Int64(d) => format!("{}", d),
+            Self::StringUTF8(d) => format!("{}", d),
+            Self::StringUTF16(d) => format!("{}", d),
+            Self::Unknown(d) => format!("{:02X?}", d.bytes)
         };
 
         if let Some(width) = f.width() {
@@ -267,5 +364,17 @@ mod tests {
         assert_eq!(packet, i);
         assert_eq!(bytes_read, 40);
     }
-    
+
+    #[test]
+    fn packet_data_unknown_tag_test() {
+        // tags that were never seen in game's traffic are not guessed at
+        for tag in [[0x71, 0x08], [0x71, 0x16], [0x71, 0x64], [0xF1, 0x32], [0xB0, 0x01], [0xCC, 0x08]] {
+            let mut bytes = VecDeque::from(vec![tag[0], tag[1], 0x01, 0x02]);
+            let d = WitcherPacketData::decode_from(&mut bytes).unwrap();
+
+            assert_eq!(d, WitcherPacketData::new_unknown(UnknownData::new(tag, Vec::new())));
+            assert_eq!(d.wire_type(), None);
+            assert_eq!(WitcherPacketData::type_name_for_tag(tag), None);
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,461 @@
+use std::borrow::Cow;
+
+use super::encoding::*;
+use super::error::{FieldContext, ProtocolError, ProtocolResult};
+use super::packet::WitcherPacket;
+use super::packet_data::WitcherPacketData;
+
+
+/// Borrowed view of an encoded packet frame.
+///
+/// Unlike [`WitcherPacket`] it doesn't decode anything up front and doesn't allocate.
+/// Payload data is decoded lazily when iterated over and strings are given out as slices of the frame.
+/// This makes it cheaper for inspecting lots of packets, e.g. when only the message id is of interest.
+///
+/// Frame bytes can be obtained for example with [`super::probe_frame_size`].
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct WitcherPacketRef<'a> {
+    frame: &'a [u8]
+}
+
+impl<'a> WitcherPacketRef<'a> {
+    /// Checks whether the frame has a correct head, size and tail. Payload is not validated until it is accessed.
+    pub fn new(frame: &'a [u8]) -> ProtocolResult<Self> {
+        if frame.len() < WitcherPacket::min_encoded_size() {
+            return Err(ProtocolError::MissingData);
+        }
+
+        let head = [frame[0], frame[1]];
+        if head != WitcherPacket::HEAD {
+            return Err(ProtocolError::InvalidHead(head));
+        }
+
+        let encoded_size = u16::from_be_bytes([frame[2], frame[3]]) as usize;
+        if encoded_size != frame.len() {
+            return Err(ProtocolError::InvalidSize(encoded_size));
+        }
+
+        let tail = [frame[frame.len() - 2], frame[frame.len() - 1]];
+        if tail != WitcherPacket::TAIL {
+            return Err(ProtocolError::InvalidTail(tail));
+        }
+
+        Ok(Self {
+            frame
+        })
+    }
+
+    /// All bytes of the frame, including head and tail
+    #[inline]
+    pub fn frame(&self) -> &'a [u8] {
+        self.frame
+    }
+
+    /// Bytes of the payload, i.e. the frame without head, size and tail
+    #[inline]
+    pub fn payload_bytes(&self) -> &'a [u8] {
+        &self.frame[WitcherPacket::HEAD.len() + u16::ENCODED_SIZE .. self.frame.len() - WitcherPacket::TAIL.len()]
+    }
+
+    /// Iterates over payload data, decoding it as it goes.
+    /// Stops after the first error.
+    #[inline]
+    pub fn iter(&self) -> WitcherPacketRefIter<'a> {
+        WitcherPacketRefIter {
+            remaining: self.payload_bytes(),
+            index: 0
+        }
+    }
+
+    /// Decodes payload data at given index. Data before it is skipped over, but still has to be valid.
+    pub fn get(&self, index: usize) -> Option<ProtocolResult<WitcherPacketDataRef<'a>>> {
+        self.iter().nth(index)
+    }
+
+    /// Number of data pieces in the payload
+    pub fn payload_len(&self) -> ProtocolResult<usize> {
+        let mut len = 0;
+        for data in self.iter() {
+            data?;
+            len += 1;
+        }
+        Ok(len)
+    }
+
+    /// Decodes the whole packet into its owned form
+    pub fn to_packet(&self) -> ProtocolResult<WitcherPacket> {
+        let payload = self.iter()
+            .map(|data| data.map(|d| d.to_owned()))
+            .collect::<ProtocolResult<Vec<_>>>()?;
+
+        Ok(WitcherPacket {
+            payload
+        })
+    }
+}
+
+impl<'a> IntoIterator for WitcherPacketRef<'a> {
+    type Item = ProtocolResult<WitcherPacketDataRef<'a>>;
+    type IntoIter = WitcherPacketRefIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> TryFrom<WitcherPacketRef<'a>> for WitcherPacket {
+    type Error = ProtocolError;
+
+    fn try_from(value: WitcherPacketRef<'a>) -> Result<Self, Self::Error> {
+        value.to_packet()
+    }
+}
+
+impl std::fmt::Debug for WitcherPacketRef<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.iter())
+            .finish()
+    }
+}
+
+
+
+/// Iterator over payload data of [`WitcherPacketRef`]
+#[derive(Debug, Clone)]
+pub struct WitcherPacketRefIter<'a> {
+    remaining: &'a [u8],
+    index: usize
+}
+
+impl<'a> WitcherPacketRefIter<'a> {
+    /// Number of payload bytes not yet decoded
+    #[inline]
+    pub(crate) fn remaining_len(&self) -> usize {
+        self.remaining.len()
+    }
+
+    fn take(&mut self, n: usize) -> ProtocolResult<&'a [u8]> {
+        if self.remaining.len() < n {
+            return Err(ProtocolError::MissingData);
+        }
+
+        let (taken, rest) = self.remaining.split_at(n);
+        self.remaining = rest;
+        Ok(taken)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> ProtocolResult<[u8; N]> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn take_string(&mut self, unit_size: usize) -> ProtocolResult<&'a [u8]> {
+        let tag = self.take_array::<2>()?;
+        if tag != i16::ENCODING_TAG {
+            return Err(ProtocolError::InvalidTag { 
+                expected: i16::ENCODING_TAG, 
+                actual: tag 
+            });
+        }
+
+        let len = i16::from_be_bytes(self.take_array()?);
+        if len < 0 {
+            return Err(ProtocolError::NegativeLength {
+                what: "string length",
+                value: len as i64
+            });
+        }
+
+        self.take(len as usize * unit_size)
+    }
+
+    fn decode_next(&mut self) -> ProtocolResult<WitcherPacketDataRef<'a>> {
+        let tag = self.take_array::<2>()?;
+
+        let data = match tag {
+            i8::ENCODING_TAG => WitcherPacketDataRef::Int8(i8::from_be_bytes(self.take_array()?)),
+            i16::ENCODING_TAG => WitcherPacketDataRef::Int16(i16::from_be_bytes(self.take_array()?)),
+            i32::ENCODING_TAG => WitcherPacketDataRef::Int32(i32::from_be_bytes(self.take_array()?)),
+            u32::ENCODING_TAG => WitcherPacketDataRef::Uint32(u32::from_be_bytes(self.take_array()?)),
+            i64::ENCODING_TAG => WitcherPacketDataRef::Int64(i64::from_be_bytes(self.take_array()?)),
+            StringUtf8::ENCODING_TAG => WitcherPacketDataRef::StringUTF8(Utf8Slice(self.take_string(1)?)),
+            StringUtf16::ENCODING_TAG => WitcherPacketDataRef::StringUTF16(Utf16Slice(self.take_string(2)?)),
+            _ => {
+                // same as with the owned packet, everything up to the tail belongs to the unknown data
+                let bytes = std::mem::take(&mut self.remaining);
+                WitcherPacketDataRef::Unknown { tag, bytes }
+            }
+        };
+
+        Ok(data)
+    }
+}
+
+impl<'a> Iterator for WitcherPacketRefIter<'a> {
+    type Item = ProtocolResult<WitcherPacketDataRef<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining.is_empty() {
+            return None;
+        }
+
+        let index = self.index;
+        self.index += 1;
+        match self.decode_next() {
+            Ok(data) => Some(Ok(data)),
+            Err(err) => {
+                // don't try to make sense of whatever comes after
+                self.remaining = &[];
+                Some(Err(err).field(format!("payload[{}]", index)))
+            }
+        }
+    }
+}
+
+impl std::iter::FusedIterator for WitcherPacketRefIter<'_> {}
+
+
+
+/// Borrowed counterpart of [`WitcherPacketData`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WitcherPacketDataRef<'a> {
+    Int8(i8),
+    Int16(i16),
+    Int32(i32),
+    Uint32(u32),
+    Int64(i64),
+    StringUTF8(Utf8Slice<'a>),
+    StringUTF16(Utf16Slice<'a>),
+    Unknown {
+        tag: [u8; 2],
+        bytes: &'a [u8]
+    }
+}
+
+impl WitcherPacketDataRef<'_> {
+    /// Encoding tag written before the data
+    pub fn tag(&self) -> [u8; 2] {
+        match self {
+            Self::Int8(_) => i8::ENCODING_TAG,
+            Self::Int16(_) => i16::ENCODING_TAG,
+            Self::Int32(_) => i32::ENCODING_TAG,
+            Self::Uint32(_) => u32::ENCODING_TAG,
+            Self::Int64(_) => i64::ENCODING_TAG,
+            Self::StringUTF8(_) => StringUtf8::ENCODING_TAG,
+            Self::StringUTF16(_) => StringUtf16::ENCODING_TAG,
+            Self::Unknown { tag, .. } => *tag,
+        }
+    }
+
+    /// Converts to owned data. Invalid string contents get replaced the same way as when decoding [`WitcherPacketData`].
+    pub fn to_owned(&self) -> WitcherPacketData {
+        match *self {
+            Self::Int8(d) => WitcherPacketData::new_int8(d),
+            Self::Int16(d) => WitcherPacketData::new_int16(d),
+            Self::Int32(d) => WitcherPacketData::new_int32(d),
+            Self::Uint32(d) => WitcherPacketData::new_uint32(d),
+            Self::Int64(d) => WitcherPacketData::new_int64(d),
+            Self::StringUTF8(s) => WitcherPacketData::new_string_utf8(s.to_string_lossy()),
+            Self::StringUTF16(s) => WitcherPacketData::new_string_utf16(s.to_string_lossy()),
+            Self::Unknown { tag, bytes } => WitcherPacketData::new_unknown(UnknownData::new(tag, bytes.to_vec())),
+        }
+    }
+
+    /// Contents of a UTF8 string, `None` for other data or if the contents are not valid UTF8
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::StringUTF8(s) => s.to_str().ok(),
+            _ => None
+        }
+    }
+}
+
+
+
+/// UTF8 string contents borrowed from a packet frame.
+/// The game doesn't guarantee the bytes are valid UTF8.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Utf8Slice<'a>(&'a [u8]);
+
+impl<'a> Utf8Slice<'a> {
+    #[inline]
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// Length in bytes
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    #[inline]
+    pub fn to_str(&self) -> Result<&'a str, std::str::Utf8Error> {
+        std::str::from_utf8(self.0)
+    }
+
+    /// Allocates only if the contents are not valid UTF8
+    #[inline]
+    pub fn to_string_lossy(&self) -> Cow<'a, str> {
+        String::from_utf8_lossy(self.0)
+    }
+}
+
+impl std::fmt::Debug for Utf8Slice<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&self.to_string_lossy(), f)
+    }
+}
+
+impl std::fmt::Display for Utf8Slice<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&self.to_string_lossy(), f)
+    }
+}
+
+impl PartialEq<str> for Utf8Slice<'_> {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other.as_bytes()
+    }
+}
+
+impl PartialEq<&str> for Utf8Slice<'_> {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == other.as_bytes()
+    }
+}
+
+
+/// UTF16 string contents borrowed from a packet frame, stored as big endian code units.
+/// The game doesn't guarantee the code units are valid UTF16.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Utf16Slice<'a>(&'a [u8]);
+
+impl<'a> Utf16Slice<'a> {
+    /// Raw big endian bytes of code units
+    #[inline]
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// Length in UTF16 code units
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len() / 2
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    #[inline]
+    pub fn code_units(&self) -> impl Iterator<Item = u16> + 'a {
+        self.0.chunks_exact(2)
+            .map(|hilo| u16::from_be_bytes([hilo[0], hilo[1]]))
+    }
+
+    #[inline]
+    pub fn chars(&self) -> impl Iterator<Item = Result<char, std::char::DecodeUtf16Error>> + 'a {
+        char::decode_utf16(self.code_units())
+    }
+
+    pub fn to_string_lossy(&self) -> String {
+        self.chars()
+            .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect()
+    }
+}
+
+impl std::fmt::Debug for Utf16Slice<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&self.to_string_lossy(), f)
+    }
+}
+
+impl std::fmt::Display for Utf16Slice<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for c in self.chars() {
+            std::fmt::Write::write_char(f, c.unwrap_or(char::REPLACEMENT_CHARACTER))?;
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq<str> for Utf16Slice<'_> {
+    fn eq(&self, other: &str) -> bool {
+        self.code_units().eq(other.encode_utf16())
+    }
+}
+
+impl PartialEq<&str> for Utf16Slice<'_> {
+    fn eq(&self, other: &&str) -> bool {
+        self.code_units().eq(other.encode_utf16())
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::*;
+
+
+    #[test]
+    fn packet_ref_matches_owned_test() {
+        let packet = WitcherPacketAssembler::new()
+            .string_utf8("ScriptCompiler")
+            .int32(-5)
+            .string_utf16("Zażółć gęślą jaźń")
+            .int64(i64::MAX)
+            .uint32(u32::MAX)
+            .int8(1)
+            .string_utf8("CR4Player")
+            .finish();
+
+        let mut bytes = Vec::new();
+        packet.encode_into(&mut bytes).unwrap();
+
+        let packet_ref = WitcherPacketRef::new(&bytes).unwrap();
+        assert_eq!(packet_ref.payload_len().unwrap(), 7);
+        assert_eq!(packet_ref.get(0).unwrap().unwrap().as_str(), Some("ScriptCompiler"));
+        assert_eq!(packet_ref.get(1).unwrap().unwrap(), WitcherPacketDataRef::Int32(-5));
+        match packet_ref.get(2).unwrap().unwrap() {
+            WitcherPacketDataRef::StringUTF16(s) => {
+                assert_eq!(s, "Zażółć gęślą jaźń");
+                assert_eq!(s.len(), 17);
+            },
+            other => panic!("unexpected data: {:?}", other)
+        }
+        assert!(packet_ref.get(7).is_none());
+
+        assert_eq!(packet_ref.to_packet().unwrap(), packet);
+    }
+
+    #[test]
+    fn packet_ref_invalid_test() {
+        let packet = WitcherPacketAssembler::new()
+            .string_utf8("Remote")
+            .int32(1)
+            .finish();
+
+        let mut bytes = Vec::new();
+        packet.encode_into(&mut bytes).unwrap();
+
+        assert!(matches!(WitcherPacketRef::new(&bytes[..bytes.len() - 1]), Err(ProtocolError::InvalidSize(_))));
+
+        // claim the string is longer than it really is
+        bytes[8..10].copy_from_slice(&100i16.to_be_bytes());
+        let packet_ref = WitcherPacketRef::new(&bytes).unwrap();
+        let mut iter = packet_ref.iter();
+        assert!(matches!(iter.next(), Some(Err(err)) if matches!(err.root_cause(), ProtocolError::MissingData)));
+        assert!(iter.next().is_none());
+    }
+}
@@ -0,0 +1,120 @@
+//! Serde implementations for types that can't simply derive them without losing information
+//!
+//! Strings are an exception: they are decoded lossily from the wire, so a packet with invalid UTF-8 or UTF-16
+//! is serialized with replacement characters and won't re-encode to the bytes that were received.
+
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+use super::encoding::*;
+
+
+/// Raw bytes are written as hex strings
+#[derive(Serialize, Deserialize)]
+struct UnknownDataRepr {
+    tag: String,
+    bytes: String
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter()
+        .map(|b| format!("{:02X}", b))
+        .collect()
+}
+
+fn from_hex<E: Error>(s: &str) -> Result<Vec<u8>, E> {
+    if !s.len().is_multiple_of(2) {
+        return Err(E::custom("hex string must have an even number of digits"));
+    }
+
+    s.as_bytes().chunks_exact(2)
+        .map(|pair| {
+            let hi = (pair[0] as char).to_digit(16);
+            let lo = (pair[1] as char).to_digit(16);
+            match (hi, lo) {
+                (Some(hi), Some(lo)) => Ok((hi * 16 + lo) as u8),
+                _ => Err(E::custom("hex string contains a character that is not a hex digit"))
+            }
+        })
+        .collect()
+}
+
+impl Serialize for UnknownData {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        UnknownDataRepr {
+            tag: to_hex(&self.tag),
+            bytes: to_hex(&self.bytes)
+        }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for UnknownData {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = UnknownDataRepr::deserialize(deserializer)?;
+
+        let tag = from_hex::<D::Error>(&repr.tag)?
+            .try_into()
+            .map_err(|_| D::Error::custom("tag must be exactly 2 bytes long"))?;
+        let bytes = from_hex(&repr.bytes)?;
+
+        Ok(Self::new(tag, bytes))
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::*;
+
+
+    #[test]
+    fn packet_serde_round_trip_test() {
+        let packet = WitcherPacketAssembler::new()
+            .string_utf8("ScriptDebugger")
+            .int8(-1)
+            .int16(300)
+            .int32(0x12345678)
+            .uint32(u32::MAX)
+            .int64(i64::MIN)
+            .string_utf16("Zażółć gęślą jaźń")
+            .string_utf8("CR4Player")
+            .finish();
+
+        let mut bytes = Vec::new();
+        packet.encode_into(&mut bytes).unwrap();
+        // make the last piece of data unrecognizable
+        let last_tag_pos = bytes.len() - 2 - "CR4Player".len() - 4 - 2;
+        bytes[last_tag_pos..last_tag_pos + 2].copy_from_slice(&[0xAB, 0xCD]);
+        let packet = WitcherPacket::decode_from(&mut bytes.as_slice()).unwrap();
+
+        let json = serde_json::to_string_pretty(&packet).unwrap();
+        let deserialized: WitcherPacket = serde_json::from_str(&json).unwrap();
+
+        let mut reencoded = Vec::new();
+        deserialized.encode_into(&mut reencoded).unwrap();
+        assert_eq!(reencoded, bytes);
+    }
+
+    #[test]
+    fn unknown_data_invalid_hex_test() {
+        let json = |bytes: &str| format!(r#"[{{"type":"Unknown","value":{{"tag":"ABCD","bytes":"{}"}}}}]"#, bytes);
+        assert!(serde_json::from_str::<WitcherPacket>(&json("0aFF")).is_ok());
+
+        for bytes in ["€0", "0G", "123"] {
+            assert!(serde_json::from_str::<WitcherPacket>(&json(bytes)).is_err(), "{}", bytes);
+        }
+    }
+
+    #[test]
+    fn packet_serde_format_test() {
+        let packet = WitcherPacketAssembler::new()
+            .string_utf8("Remote")
+            .int32(5)
+            .finish();
+
+        let json = serde_json::to_string(&packet).unwrap();
+        assert_eq!(json, r#"[{"type":"StringUTF8","value":"Remote"},{"type":"Int32","value":5}]"#);
+    }
+}
@@ -0,0 +1,314 @@
+//! Generation of a Wireshark dissector for the game's protocol
+
+use std::fmt::Write;
+
+use crate::connection::WitcherPort;
+use crate::messages::{message_catalogue, MessageKind};
+use crate::protocol::*;
+
+
+/// Generates a Lua script that lets Wireshark decode traffic on the game's ports.
+///
+/// The dissector reassembles packets from TCP segments, shows their framing and typed payload data
+/// and names messages known to the library. Encoding tags and message ids are taken from the library itself,
+/// so to keep the dissector up to date it just needs to be generated again.
+///
+/// To use it put the file in Wireshark's personal Lua plugins directory.
+pub fn lua_dissector() -> String {
+    let mut tags = String::new();
+    for wire_type in WireType::ALL {
+        let tag = wire_type.tag();
+        let name = WitcherPacketData::type_name_for_tag(tag).unwrap_or("Unknown");
+        let (kind, size) = lua_data_kind(wire_type);
+        writeln!(tags, "    [0x{:02X}{:02X}] = {{ name = {}, kind = \"{}\", size = {} }},", tag[0], tag[1], lua_string(name), kind, size).unwrap();
+    }
+
+    let mut messages = String::new();
+    for desc in message_catalogue() {
+        let kind = match &desc.kind {
+            MessageKind::Notification => "Notification",
+            MessageKind::Request { .. } => "Request",
+            MessageKind::Response { .. } => "Response",
+        };
+        let namespace = desc.namespace().map(|n| n.as_str()).unwrap_or("");
+        let id = desc.id().as_slice().iter()
+            .map(|data| {
+                let type_name = WitcherPacketData::type_name_for_tag(data.tag()).unwrap_or("Unknown");
+                format!("{{ type = {}, value = {} }}", lua_string(type_name), lua_string(&data.to_string()))
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        writeln!(messages, "    {{ name = {}, kind = \"{}\", direction = \"{:?}\", namespace = {}, experimental = {}, id = {{ {} }} }},",
+            lua_string(desc.type_name()), kind, desc.direction(), lua_string(namespace), desc.experimental, id).unwrap();
+    }
+
+    let ports = [WitcherPort::Editor.as_number(), WitcherPort::Game.as_number()];
+
+    LUA_TEMPLATE
+        .replace("{{VERSION}}", env!("CARGO_PKG_VERSION"))
+        .replace("{{HEAD}}", &format!("0x{:02X}{:02X}", WitcherPacket::HEAD[0], WitcherPacket::HEAD[1]))
+        .replace("{{TAIL}}", &format!("0x{:02X}{:02X}", WitcherPacket::TAIL[0], WitcherPacket::TAIL[1]))
+        .replace("{{MIN_PACKET_SIZE}}", &WitcherPacket::min_encoded_size().to_string())
+        .replace("{{PORTS}}", &ports.map(|p| format!("[{}] = true", p)).join(", "))
+        .replace("{{TAGS}}", tags.trim_end())
+        .replace("{{MESSAGES}}", messages.trim_end())
+}
+
+/// How the Lua script should read data of given type and how many bytes it takes after the tag.
+/// Strings have variable size, so for them it's 0.
+fn lua_data_kind(wire_type: WireType) -> (&'static str, usize) {
+    match wire_type {
+        WireType::Int8 => ("int", i8::ENCODED_SIZE),
+        WireType::Int16 => ("int", i16::ENCODED_SIZE),
+        WireType::Int32 => ("int", i32::ENCODED_SIZE),
+        WireType::Int64 => ("int", i64::ENCODED_SIZE),
+        WireType::Uint32 => ("uint", u32::ENCODED_SIZE),
+        WireType::StringUTF8 => ("utf8", 0),
+        WireType::StringUTF16 => ("utf16", 0),
+    }
+}
+
+fn lua_string(s: &str) -> String {
+    let mut lua = String::with_capacity(s.len() + 2);
+    lua.push('"');
+    for b in s.bytes() {
+        match b {
+            b'"' => lua.push_str("\\\""),
+            b'\\' => lua.push_str("\\\\"),
+            b'\n' => lua.push_str("\\n"),
+            0x20..=0x7E => lua.push(b as char),
+            // Lua strings are byte strings, so UTF-8 can be written byte by byte
+            _ => write!(lua, "\\{:03}", b).unwrap()
+        }
+    }
+    lua.push('"');
+    lua
+}
+
+
+const LUA_TEMPLATE: &str = r#"-- Wireshark dissector for the protocol The Witcher 3 uses to communicate with debugging tools.
+-- Generated by rw3d_net {{VERSION}}, generate it again when messages change.
+-- Put this file in Wireshark's personal Lua plugins directory.
+
+local witcher_proto = Proto("witcher3", "The Witcher 3 Debugging Protocol")
+
+local f_head = ProtoField.uint16("witcher3.head", "Head", base.HEX)
+local f_size = ProtoField.uint16("witcher3.size", "Size", base.DEC)
+local f_tail = ProtoField.uint16("witcher3.tail", "Tail", base.HEX)
+local f_message = ProtoField.string("witcher3.message", "Message")
+local f_kind = ProtoField.string("witcher3.kind", "Kind")
+local f_namespace = ProtoField.string("witcher3.namespace", "Namespace")
+local f_data = ProtoField.string("witcher3.data", "Data")
+local f_tag = ProtoField.uint16("witcher3.tag", "Tag", base.HEX)
+local f_length = ProtoField.int16("witcher3.length", "Length", base.DEC)
+local f_unknown = ProtoField.bytes("witcher3.unknown", "Unknown data")
+
+witcher_proto.fields = { f_head, f_size, f_tail, f_message, f_kind, f_namespace, f_data, f_tag, f_length, f_unknown }
+
+local HEAD = {{HEAD}}
+local TAIL = {{TAIL}}
+local MIN_PACKET_SIZE = {{MIN_PACKET_SIZE}}
+-- ports the game listens on
+local SERVER_PORTS = { {{PORTS}} }
+
+-- encoding tags of data, size is the number of bytes after the tag for types other than strings
+local TAGS = {
+{{TAGS}}
+}
+
+-- messages known to the library, ids are the data at the beginning of the payload
+local MESSAGES = {
+{{MESSAGES}}
+}
+
+
+-- Reads data at given offset. Returns its type name, value as a string, size in bytes including the tag
+-- and for strings the length written before them. Returns nil if the tag is unknown or the data doesn't fit before the limit.
+local function read_data(tvb, offset, limit)
+    if offset + 2 > limit then
+        return nil
+    end
+
+    local info = TAGS[tvb(offset, 2):uint()]
+    if info == nil then
+        return nil
+    end
+
+    if info.kind == "utf8" or info.kind == "utf16" then
+        -- length is written as a tagged int16
+        if offset + 6 > limit then
+            return nil
+        end
+        local len = tvb(offset + 4, 2):int()
+        local byte_len = len
+        if info.kind == "utf16" then
+            byte_len = len * 2
+        end
+        if len < 0 or offset + 6 + byte_len > limit then
+            return nil
+        end
+
+        local value = ""
+        if byte_len > 0 then
+            local range = tvb(offset + 6, byte_len)
+            if info.kind == "utf8" then
+                value = range:string(ENC_UTF_8)
+            else
+                value = range:ustring()
+            end
+        end
+        return info.name, value, 6 + byte_len, len
+    end
+
+    if offset + 2 + info.size > limit then
+        return nil
+    end
+
+    local range = tvb(offset + 2, info.size)
+    local value
+    if info.kind == "int" then
+        if info.size == 8 then value = tostring(range:int64()) else value = tostring(range:int()) end
+    else
+        value = tostring(range:uint())
+    end
+    return info.name, value, 2 + info.size
+end
+
+-- Finds the message with the longest id matching the beginning of the payload
+local function identify_message(fields, direction)
+    local best = nil
+    for _, message in ipairs(MESSAGES) do
+        if message.direction == direction and #message.id <= #fields and (best == nil or #message.id > #best.id) then
+            local matches = true
+            for i, id in ipairs(message.id) do
+                if fields[i].type ~= id.type or fields[i].value ~= id.value then
+                    matches = false
+                    break
+                end
+            end
+            if matches then
+                best = message
+            end
+        end
+    end
+    return best
+end
+
+local function get_packet_len(tvb, pinfo, offset)
+    return tvb(offset + 2, 2):uint()
+end
+
+local function dissect_packet(tvb, pinfo, tree)
+    local size = tvb(2, 2):uint()
+    local direction = "ServerToClient"
+    if SERVER_PORTS[pinfo.dst_port] then
+        direction = "ClientToServer"
+    end
+
+    pinfo.cols.protocol = "Witcher3"
+    local subtree = tree:add(witcher_proto, tvb(0, size))
+    subtree:add(f_head, tvb(0, 2))
+    subtree:add(f_size, tvb(2, 2))
+
+    local fields = {}
+    local items = {}
+    local offset = 4
+    local payload_end = size - 2
+    while offset < payload_end do
+        local type_name, value, data_size, len = read_data(tvb, offset, payload_end)
+        if type_name == nil then
+            local item = subtree:add(f_unknown, tvb(offset, payload_end - offset))
+            item:append_text(string.format(" (tag 0x%04X)", tvb(offset, 2):uint()))
+            break
+        end
+
+        local item = subtree:add(f_data, tvb(offset, data_size), value)
+        item:set_text(string.format("[%d] %s: %s", #fields, type_name, value))
+        item:add(f_tag, tvb(offset, 2))
+        if len ~= nil then
+            item:add(f_length, tvb(offset + 4, 2))
+        end
+
+        fields[#fields + 1] = { type = type_name, value = value }
+        items[#items + 1] = item
+        offset = offset + data_size
+    end
+
+    local tail = subtree:add(f_tail, tvb(payload_end, 2))
+    if tvb(payload_end, 2):uint() ~= TAIL then
+        tail:add_expert_info(PI_MALFORMED, PI_ERROR, "Invalid packet tail")
+    end
+
+    local message = identify_message(fields, direction)
+    if message ~= nil then
+        subtree:append_text(", " .. message.name)
+        subtree:add(f_message, message.name):set_generated()
+        subtree:add(f_kind, message.kind):set_generated()
+        if message.experimental then
+            subtree:add_expert_info(PI_ASSUMPTION, PI_NOTE, "Experimental message, its definition is not confirmed")
+        end
+        if message.namespace ~= "" then
+            subtree:add(f_namespace, message.namespace):set_generated()
+        end
+        for i = 1, #message.id do
+            items[i]:append_text(" (id)")
+        end
+        pinfo.cols.info = message.name
+    else
+        pinfo.cols.info = "Unknown message"
+    end
+end
+
+function witcher_proto.dissector(tvb, pinfo, tree)
+    if tvb:len() < 2 or tvb(0, 2):uint() ~= HEAD then
+        return 0
+    end
+
+    dissect_tcp_pdus(tvb, tree, MIN_PACKET_SIZE, get_packet_len, dissect_packet)
+    return tvb:len()
+end
+
+local tcp_port = DissectorTable.get("tcp.port")
+for port, _ in pairs(SERVER_PORTS) do
+    tcp_port:add(port, witcher_proto)
+end
+"#;
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{messages::message_catalogue, protocol::WireType};
+    use super::*;
+
+
+    #[test]
+    fn lua_dissector_test() {
+        let lua = lua_dissector();
+        assert!(!lua.contains("{{"));
+
+        assert!(lua.contains("[0xAC08] = { name = \"StringUTF8\", kind = \"utf8\", size = 0 },"));
+        assert!(lua.contains("[0x8132] = { name = \"Int32\", kind = \"int\", size = 4 },"));
+        // tags that were never seen in game's traffic are not advertised
+        for tag in ["[0x7108]", "[0x7116]", "[0x7164]", "[0xF132]", "[0xF164]", "[0xB001]", "[0xCC08]"] {
+            assert!(!lua.contains(tag), "{}", tag);
+        }
+        for wire_type in WireType::ALL {
+            let tag = wire_type.tag();
+            assert!(lua.contains(&format!("[0x{:02X}{:02X}]", tag[0], tag[1])));
+        }
+
+        assert!(lua.contains(r#"{ name = "ScriptsRootPath", kind = "Request", direction = "ClientToServer", namespace = "ScriptCompiler", experimental = false, id = { { type = "StringUTF8", value = "ScriptCompiler" }, { type = "StringUTF8", value = "RootPath" } } },"#));
+        for desc in message_catalogue() {
+            assert!(lua.contains(&format!("{{ name = \"{}\"", desc.type_name())));
+        }
+        #[cfg(feature = "experimental")]
+        assert!(lua.contains(r#"{ name = "AddBreakpoint", kind = "Request", direction = "ClientToServer", namespace = "ScriptDebugger", experimental = true,"#));
+
+        assert!(lua.contains("local SERVER_PORTS = { [37000] = true, [37001] = true }"));
+        assert_eq!(lua_string("a\"b\\\nż"), "\"a\\\"b\\\\\\n\\197\\188\"");
+    }
+}
@@ -0,0 +1,170 @@
+use std::path::PathBuf;
+
+use rw3d_net::protocol::*;
+
+
+#[derive(Debug, Default, PartialEq, AssemblePayload, DisassemblePayload)]
+#[witcher(id = ["Remote", 0x12345678, 0x81160008, -1i8, 7u32])]
+struct TestId;
+
+#[derive(Debug, Clone, PartialEq, AssemblePayload, DisassemblePayload)]
+struct TestBody {
+    #[witcher(fixed_i32 = 1)]
+    version: i32,
+    name: String,
+    #[witcher(utf16)]
+    description: String,
+    #[witcher(fixed_utf8 = "end")]
+    end: String,
+    count: i64
+}
+
+#[derive(Debug, Clone, PartialEq, AssemblePayload, DisassemblePayload)]
+struct TestTuple(i8, #[witcher(utf16)] PathBuf, StringUtf16);
+
+#[derive(Debug, Clone, PartialEq, AssemblePayload, DisassemblePayload)]
+struct TestWrapped {
+    #[witcher(with = EmptyAsNone)]
+    filter: Option<String>,
+    #[witcher(with = Utf16Path)]
+    path: PathBuf,
+    flag: Option<bool>
+}
+
+#[derive(Debug, Clone, PartialEq, AssemblePayload, DisassemblePayload)]
+struct TestGeneric<T> {
+    inner: T,
+    trailer: u32
+}
+
+
+fn assemble<T: AssemblePayload>(value: T) -> WitcherPacket {
+    value.assemble_payload(WitcherPacketAssembler::new()).finish()
+}
+
+fn disassemble<T: DisassemblePayload>(packet: WitcherPacket) -> ProtocolResult<T> {
+    let mut dasm = WitcherPacketDisassembler::new(packet);
+    T::disassemble_payload(&mut dasm)
+}
+
+
+#[test]
+fn derive_id_test() {
+    let packet = assemble(TestId);
+    let expected = WitcherPacketAssembler::new()
+        .string_utf8("Remote")
+        .int32(0x12345678)
+        .int32(0x81160008u32 as i32)
+        .int8(-1)
+        .uint32(7)
+        .finish();
+
+    assert_eq!(packet, expected);
+    assert_eq!(disassemble::<TestId>(packet).unwrap(), TestId);
+
+    let wrong = WitcherPacketAssembler::new()
+        .string_utf8("Remote")
+        .int32(0x12345678)
+        .int32(0)
+        .finish();
+    assert!(matches!(disassemble::<TestId>(wrong), Err(ProtocolError::UnexpectedValue { .. })));
+}
+
+#[test]
+fn derive_struct_test() {
+    let body = TestBody {
+        version: 1,
+        name: "name".into(),
+        description: "description".into(),
+        end: "end".into(),
+        count: 42
+    };
+
+    let packet = assemble(body.clone());
+    let expected = WitcherPacketAssembler::new()
+        .int32(1)
+        .string_utf8("name")
+        .string_utf16("description")
+        .string_utf8("end")
+        .int64(42)
+        .finish();
+
+    assert_eq!(packet, expected);
+    assert_eq!(disassemble::<TestBody>(packet).unwrap(), body);
+
+    let wrong = WitcherPacketAssembler::new()
+        .int32(1)
+        .string_utf8("name")
+        .string_utf8("description")
+        .finish();
+    let err = disassemble::<TestBody>(wrong).unwrap_err();
+    assert!(matches!(&err, ProtocolError::Field { field, .. } if field == "description"));
+    assert!(matches!(err.root_cause(), ProtocolError::TypeMismatch { .. }));
+}
+
+#[test]
+fn derive_tuple_and_generic_test() {
+    let tuple = TestTuple(3, PathBuf::from("dir/file.ws"), "text".into());
+    let generic = TestGeneric {
+        inner: tuple.clone(),
+        trailer: 9
+    };
+
+    let packet = assemble(generic.clone());
+    let expected = WitcherPacketAssembler::new()
+        .int8(3)
+        .string_utf16("dir/file.ws")
+        .string_utf16("text")
+        .uint32(9)
+        .finish();
+
+    assert_eq!(packet, expected);
+    assert_eq!(disassemble::<TestGeneric<TestTuple>>(packet).unwrap(), generic);
+}
+
+#[test]
+fn derive_with_test() {
+    let wrapped = TestWrapped {
+        filter: None,
+        path: PathBuf::from("dir/file.ws"),
+        flag: Some(true)
+    };
+
+    let packet = assemble(wrapped.clone());
+    let expected = WitcherPacketAssembler::new()
+        .string_utf8("")
+        .string_utf16("dir/file.ws")
+        .int8(1)
+        .int8(1)
+        .finish();
+
+    assert_eq!(packet, expected);
+    assert_eq!(disassemble::<TestWrapped>(packet).unwrap(), wrapped);
+}
+
+#[test]
+fn derive_partial_test() {
+    let packet = WitcherPacketAssembler::new()
+        .int8(3)
+        .string_utf16("dir/file.ws")
+        .string_utf16("text")
+        .int32(9)
+        .finish();
+
+    let mut dasm = WitcherPacketDisassembler::new(packet);
+    let (partial, err) = TestGeneric::<TestTuple>::disassemble_partial(&mut dasm).unwrap_err();
+    assert_eq!(partial.inner, Some(TestTuple(3, PathBuf::from("dir/file.ws"), "text".into())));
+    assert_eq!(partial.trailer, None);
+    assert!(matches!(err, ProtocolError::Position { index: 3, .. }));
+    assert_eq!(dasm.into_remaining(), vec![WitcherPacketData::new_int32(9)]);
+
+    let packet = WitcherPacketAssembler::new()
+        .int8(3)
+        .int32(1)
+        .finish();
+
+    let mut dasm = WitcherPacketDisassembler::new(packet);
+    let (partial, _) = TestTuple::disassemble_partial(&mut dasm).unwrap_err();
+    assert!(matches!(partial, TestTuplePartial(Some(3), None, None)));
+    assert_eq!(dasm.remaining(), 1);
+}
@@ -0,0 +1,79 @@
+use std::{net::Ipv4Addr, sync::{atomic::{AtomicBool, Ordering}, Arc}, time::Duration};
+
+use rw3d_mock_server::MockWitcherServer;
+use rw3d_net::{connection::{AsyncWitcherConnection, ConnectionError, WitcherPort}, messages::{notifications::*, requests::*, Message, WitcherNamespace}};
+
+
+
+#[tokio::test]
+async fn tokio_connection_test() -> anyhow::Result<()> {
+    let server_cancel_token = Arc::new(AtomicBool::new(false));
+    let cancel_token_cloned = server_cancel_token.clone();
+    let server_handle = std::thread::spawn(move || -> anyhow::Result<()> {
+        MockWitcherServer::new().listen(cancel_token_cloned)
+    });
+
+    // wait for the server to set up
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut conn = AsyncWitcherConnection::connect_timeout(Ipv4Addr::LOCALHOST.into(), WitcherPort::Game, Duration::from_secs(1)).await?;
+
+    conn.send(ListenToNamespace::assemble_packet(ListenToNamespaceParams {
+        namesp: WitcherNamespace::ScriptCompiler
+    })).await?;
+
+
+    conn.send(ScriptsRootPath::assemble_packet(())).await?;
+    let packet = conn.receive().await?;
+    let result = ScriptsRootPathResponse::disassemble_packet(packet)?;
+    assert!(!result.abs_path.as_os_str().is_empty());
+
+
+    conn.send(ExecuteCommand::assemble_packet(ExecuteCommandParams {
+        cmd: "spawnt(12)".into()
+    })).await?;
+    let packet = conn.receive().await?;
+    ExecuteCommandResponse::disassemble_packet(packet)?;
+
+
+    conn.send(ReloadScripts::assemble_packet(())).await?;
+    loop {
+        let packet = tokio::time::timeout(Duration::from_secs(5), conn.receive()).await??;
+        let params = ScriptsReloadProgress::disassemble_packet(packet)?;
+        if let ScriptsReloadProgressParams::Finished { success } = params {
+            assert!(success);
+            break;
+        }
+    }
+
+
+    conn.shutdown().await?;
+
+    server_cancel_token.store(true, Ordering::Relaxed);
+    server_handle.join().unwrap()?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn tokio_duplex_connection_test() -> anyhow::Result<()> {
+    let (client_stream, server_stream) = tokio::io::duplex(64);
+    let mut client_conn = AsyncWitcherConnection::from_stream(client_stream, WitcherPort::Game);
+    let mut server_conn = AsyncWitcherConnection::from_stream(server_stream, WitcherPort::Game);
+
+    // the packet is bigger than the duplex buffer, so both sides need to make progress at the same time
+    let params = ExecuteCommandParams {
+        cmd: "additem('Aerondight', 1)".repeat(4)
+    };
+    let (sent, received) = tokio::join!(
+        client_conn.send(ExecuteCommand::assemble_packet(params.clone())),
+        server_conn.receive()
+    );
+    sent?;
+    assert_eq!(ExecuteCommand::disassemble_packet(received?)?.cmd, params.cmd);
+
+    client_conn.shutdown().await?;
+    assert!(matches!(server_conn.receive().await, Err(ConnectionError::Closed)));
+
+    Ok(())
+}
@@ -10,7 +10,7 @@ pub const LINUX_STEAM_PFX_PATH: &str = ".local/share/Steam/steamapps/compatdata/
 /// For `printer` parameter you can pass any function or closure that you want to print log text with,
 /// the string passed to said printer will consist of one or more lines of text.
 pub fn tail_scriptslog<P>( printer: P, refresh_time_millis: u64, cancel_token: Receiver<()>, custom_path: Option<String> ) -> Option<String> 
-where P: Fn(&String) -> () {
+where P: Fn(&String) {
     let file_path: PathBuf;
     if let Some(p) = custom_path {
         file_path = Path::new(&p).to_path_buf();
@@ -51,9 +51,9 @@ fn scriptslog_file_path() -> Result<PathBuf, String> {
     }
 
     if let Some(docs) = docs {
-        return Ok( docs.join(Path::new("The Witcher 3").join(SCRIPTSLOG_FILE_NAME)) );
+        Ok( docs.join(Path::new("The Witcher 3").join(SCRIPTSLOG_FILE_NAME)) )
     } else {
-        return Err( "Documents directory could not be found.".to_owned() );
+        Err( "Documents directory could not be found.".to_owned() )
     }
 }
 
@@ -62,6 +62,7 @@ fn open_scriptslog(path: &PathBuf) -> Result<File, String> {
         .read(true)
         .write(true) // so that it can be created if doesn't exist
         .create(true)
+        .truncate(false)
         .open(path);
 
     if let Err(e) = file {
@@ -77,7 +78,7 @@ fn open_scriptslog(path: &PathBuf) -> Result<File, String> {
 
 #[cfg(target_os = "windows")]
 fn tail_scriptslog_loop<P>(scriptslog_path: PathBuf, printer: P, refresh_time_millis: u64, cancel_token: Receiver<()>) -> Option<String> 
-where P: Fn(&String) -> () {
+where P: Fn(&String) {
     match open_scriptslog(&scriptslog_path) {
         Ok(file) => {
             let mut reader = BufReader::new(&file);
@@ -130,7 +131,7 @@ where P: Fn(&String) -> () {
 
 #[cfg(target_os = "linux")]
 fn tail_scriptslog_loop<P>(scriptslog_path: PathBuf, printer: P, refresh_time_millis: u64, cancel_token: Receiver<()>) -> Option<String> 
-where P: Fn(&String) -> () {
+where P: Fn(&String) {
     let mut last_pos: u64;
     match open_scriptslog(&scriptslog_path) {
         Ok(file) => last_pos = file.metadata().unwrap().len(),